@@ -27,21 +27,21 @@ fn bench_columnate(c: &mut Criterion) {
     c.bench_function("columnate_100x10", |b| {
         b.iter(|| {
             let input = small.deep_copy();
-            black_box(Columnate.apply(input).unwrap())
+            black_box(Columnate::default().apply(input).unwrap())
         })
     });
 
     c.bench_function("columnate_10kx10", |b| {
         b.iter(|| {
             let input = medium.deep_copy();
-            black_box(Columnate.apply(input).unwrap())
+            black_box(Columnate::default().apply(input).unwrap())
         })
     });
 
     c.bench_function("columnate_100kx10", |b| {
         b.iter(|| {
             let input = large.deep_copy();
-            black_box(Columnate.apply(input).unwrap())
+            black_box(Columnate::default().apply(input).unwrap())
         })
     });
 }
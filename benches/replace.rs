@@ -15,7 +15,7 @@ fn bench_replace(c: &mut Criterion) {
     let small = make_lines(100);
     let medium = make_lines(10_000);
     let large = make_lines(100_000);
-    let replacer = Replace::new(Regex::new("ERROR: ").unwrap(), "".to_string(), None);
+    let replacer = Replace::new(Regex::new("ERROR: ").unwrap(), "".to_string(), None, false);
 
     c.bench_function("replace_100", |b| {
         b.iter(|| {
@@ -22,7 +22,7 @@ fn bench_select(c: &mut Criterion) {
     c.bench_function("select_single_10k", |b| {
         b.iter(|| {
             let input = medium.deep_copy();
-            black_box(Select::new(sel_single.clone()).apply(input).unwrap())
+            black_box(Select::new(sel_single.clone(), false).apply(input).unwrap())
         })
     });
 
@@ -37,14 +37,14 @@ fn bench_select(c: &mut Criterion) {
     c.bench_function("select_slice_100_from_10k", |b| {
         b.iter(|| {
             let input = medium.deep_copy();
-            black_box(Select::new(sel_slice.clone()).apply(input).unwrap())
+            black_box(Select::new(sel_slice.clone(), false).apply(input).unwrap())
         })
     });
 
     c.bench_function("select_slice_100_from_100k", |b| {
         b.iter(|| {
             let input = large.deep_copy();
-            black_box(Select::new(sel_slice.clone()).apply(input).unwrap())
+            black_box(Select::new(sel_slice.clone(), false).apply(input).unwrap())
         })
     });
 
@@ -59,7 +59,7 @@ fn bench_select(c: &mut Criterion) {
     c.bench_function("select_stride_10k", |b| {
         b.iter(|| {
             let input = medium.deep_copy();
-            black_box(Select::new(sel_stride.clone()).apply(input).unwrap())
+            black_box(Select::new(sel_stride.clone(), false).apply(input).unwrap())
         })
     });
 
@@ -74,7 +74,7 @@ fn bench_select(c: &mut Criterion) {
     c.bench_function("select_reverse_10k", |b| {
         b.iter(|| {
             let input = medium.deep_copy();
-            black_box(Select::new(sel_rev.clone()).apply(input).unwrap())
+            black_box(Select::new(sel_rev.clone(), false).apply(input).unwrap())
         })
     });
 }
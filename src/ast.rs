@@ -1,3 +1,8 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+use crate::value::Level;
+
 /// A programme is a sequence of operators.
 #[derive(Debug, Clone, PartialEq)]
 pub struct Programme {
@@ -11,6 +16,19 @@ pub enum Operator {
     Split,
     /// `S<delim>` - split on a custom delimiter
     SplitDelim(String),
+    /// `S[<chars>]` - split on any of a set of characters
+    SplitAny(Vec<char>),
+    /// `&sentences` - split text into sentences
+    SplitSentences,
+    /// `&kv` - parse `key=value` pairs into `[key, value]` rows
+    ParseKv,
+    /// `S[>|<]<delim>[{<n>}]` - split on a delimiter, optionally keeping it on the
+    /// following or preceding part, into at most n parts
+    SplitDelimWith {
+        delimiter: String,
+        keep: Option<KeepDelimiter>,
+        limit: Option<usize>,
+    },
     /// `j` - join/flatten natural
     Join,
     /// `J<delim>` - join with a custom delimiter
@@ -27,12 +45,24 @@ pub enum Operator {
     Lowercase,
     /// `L<selection>` - lowercase selected elements
     LowercaseSelected(Selection),
+    /// `&snake`, `&camel`, `&kebab` - rewrite identifiers in a naming convention
+    ConvertCase(CaseStyle),
+    /// `&slug` - rewrite text as a lowercase, hyphen-separated URL slug
+    Slugify,
+    /// `&rot<n>` - rotate ASCII letters `n` places through the alphabet (`&rot13`)
+    Rot(i64),
     /// `r[<selection>]/<old>/<new>/` - regex replace, optionally in selected elements
     Replace {
         selection: Option<Selection>,
         pattern: String,
         replacement: String,
+        /// `i` flag - match case-insensitively
+        case_insensitive: bool,
+        /// `M` flag - replace only the first match
+        first_only: bool,
     },
+    /// `R` - reverse the characters of each string
+    ReverseChars,
     /// `n` - convert to number
     ToNumber,
     /// `N<selection>` - convert to number selected elements
@@ -41,39 +71,675 @@ pub enum Operator {
     Trim,
     /// `T<selection>` - trim selected elements
     TrimSelected(Selection),
+    /// `t"<chars>"`, `t<<chars>`, `t><chars>` - trim the given characters from
+    /// both ends, the start or the end
+    TrimChars { chars: String, side: TrimSide },
+    /// `T<selection>"<chars>"` (or `<`/`>`) - trim the given characters from selected elements
+    TrimCharsSelected {
+        selection: Selection,
+        chars: String,
+        side: TrimSide,
+    },
+    /// `F"<template>"` - render each element through a template with `{0}`, `{1}`, `{}` placeholders
+    Format(String),
+    /// `C[!]<marker>` - strip comments from a marker to end of line, with `!`
+    /// keeping the lines that held only a comment as blank lines
+    StripComments { marker: String, keep_empty: bool },
     /// `x` - delete empty elements
     DeleteEmpty,
     /// `f` - flatten nested arrays by one level
     Flatten,
     /// `d` - dedupe with counts
     DedupeWithCounts,
+    /// `di` - dedupe with counts, ignoring case
+    DedupeWithCountsIgnoreCase,
+    /// `d%` - dedupe with each count as a percentage of the total
+    DedupeWithPercentages,
     /// `D<selection>` - dedupe by selection with counts
     DedupeSelectionWithCounts(Selection),
+    /// `Di<selection>` - dedupe by selection with counts, ignoring case
+    DedupeSelectionWithCountsIgnoreCase(Selection),
+    /// `&uniq` - collapse runs of equal adjacent elements
+    /// `&uniqc` - the same, as `[count, value]` for each run
+    AdjacentDedupe { counts: bool },
+    /// `&mode` - the most common element
+    Mode,
+    /// `k<selection>` - keep the first element for each distinct selected key
+    DedupeSelection(Selection),
     /// `+` - sum numeric values
     Sum,
+    /// `q<percent>` - the value at a percentile of the numeric values
+    Quantile(f64),
+    /// `&var` / `&pvar` - sample / population variance of numeric values
+    Variance { sample: bool },
+    /// `&std` / `&pstd` - sample / population standard deviation of numeric values
+    StdDev { sample: bool },
+    /// `&movavg<n>` - mean of the trailing window of n elements at each position
+    MovingAverage(usize),
+    /// `W<selection>*<selection>` - sum of the products of two selected columns
+    WeightedSum { a: Selection, b: Selection },
+    /// `=<selection><op><selection>` - per-row arithmetic on two selected columns
+    Arithmetic {
+        a: Selection,
+        op: ArithOp,
+        b: Selection,
+    },
+    /// `+{<number>}` - add a constant to each number
+    AddScalar(f64),
+    /// `*{<number>}` - multiply each number by a constant
+    MulScalar(f64),
+    /// `&clamp<min>:<max>` - limit each number to a range
+    Clamp { min: f64, max: f64 },
+    /// `&level"<name>"` - relabel an array as `file`, `line`, `word` or `char`
+    SetLevel(Level),
     /// `#` - count elements
     Count,
-    /// `c` - columnate
-    Columnate,
+    /// `##` - count leaves at any depth
+    CountLeaves,
+    /// `w` - `[lines, words, chars]`, not counting newlines
+    Stats,
+    /// `c[{<width>}]["<sep>"]` - columnate, cutting cells to at most `<width>`
+    /// chars and putting `<sep>` between columns
+    Columnate {
+        width: Option<usize>,
+        separator: Option<String>,
+    },
+    /// `b` - bar chart of `[count, label]` rows
+    Histogram,
     /// `p<selection>` - partition array at indices
     Partition(Selection),
     /// `o` - sort descending
     SortDescending,
     /// `O` - sort ascending
     SortAscending,
+    /// `&sortu` / `&rsortu` - sort ascending / descending, dropping repeats
+    SortUnique { descending: bool },
+    /// `&sortby<key>[<|>],...` - sort by several selected keys, each ascending
+    /// (`<`, the default) or descending (`>`)
+    SortBy {
+        keys: Selection,
+        descending: Vec<bool>,
+    },
+    /// `&sortchars` / `&sortcharsi` - sort the characters of each string,
+    /// optionally ignoring case
+    SortChars { ignore_case: bool },
+    /// `&rank` - `[rank, value]` for each element, largest first, in input order
+    /// `&ranko` - the same, in rank order
+    Rank { sorted: bool },
+    /// `h<n>` - keep the first n elements
+    Head(usize),
+    /// `H<n>` - keep the last n elements
+    Tail(usize),
+    /// `&lines<first>-<last>` - keep elements first to last, counting from 1
+    /// `&lines<first>-` - keep elements from first on
+    LineRange { first: usize, last: Option<usize> },
+    /// `&repeat<n>` - repeat each element n times
+    /// `&cycle<n>` - repeat the whole array n times
+    Repeat { n: usize, whole: bool },
+    /// `Z<n>` - keep n randomly chosen elements
+    Sample(usize),
+    /// `z` - shuffle elements randomly
+    Shuffle,
     /// Selection - select elements by index, slice, or multi-select
     Selection(Selection),
     /// `/<regex>/` - filter keep matching elements
     /// `!/<regex>/` - filter remove matching elements (keep non-matching)
     Filter { pattern: String, negate: bool },
+    /// `&context<n>/<regex>/` - keep matching elements and n on either side
+    /// `&context<before>:<after>/<regex>/` - ... with separate counts
+    FilterContext {
+        pattern: String,
+        negate: bool,
+        before: usize,
+        after: usize,
+    },
+    /// `?<selection>/<regex>/` - keep elements whose selected value matches
+    /// `?<selection>!/<regex>/` - keep elements whose selected value does not match
+    FilterSelected {
+        selection: Selection,
+        pattern: String,
+        negate: bool,
+    },
+    /// `?|/<regex>/` - keep elements where any field matches
+    /// `?&/<regex>/` - keep elements where every field matches
+    FilterFields {
+        pattern: String,
+        negate: bool,
+        all: bool,
+    },
+    /// `&matches/<regex>/` - map each element to whether it matches
+    /// `&matches!/<regex>/` - map each element to whether it does not match
+    Matches { pattern: String, negate: bool },
+    /// `?<selection><cmp><number>` - keep elements whose selected value compares true
+    Compare {
+        selection: Selection,
+        cmp: Comparison,
+        value: f64,
+    },
+    /// `?#<cmp><number>` - keep elements whose length compares true
+    CompareLength { cmp: Comparison, value: f64 },
     /// `m/<regex>/` - extract all regex matches from each element
     Match { pattern: String },
+    /// `e/<regex>/` - extract all matches (or capture group 1) from each element
+    Extract { pattern: String },
     /// `g<selection>` - group by the value(s) at the selection
     GroupBy(Selection),
+    /// `&chunk<selection>` - split into runs of elements with equal keys at the selection
+    ChunkBy(Selection),
+    /// `&ngram<n>` - overlapping runs of n consecutive elements
+    Ngram(usize),
+    /// `A<selection>[<funcs>][<selection>]` - group by key and aggregate a value column
+    Agg {
+        key: Selection,
+        funcs: Vec<AggFunc>,
+        value: Option<Selection>,
+    },
+    /// `G[<funcs>][<selection>]` - aggregate each group produced by `g`
+    GroupAgg {
+        funcs: Vec<AggFunc>,
+        value: Option<Selection>,
+    },
+    /// `&squeeze[<chars>]` - collapse runs of whitespace (or of the given characters)
+    Squeeze(Option<String>),
+    /// `&stripansi` - remove ANSI escape sequences such as colours
+    StripAnsi,
+    /// `&wrap[<n>]` - wrap text at n columns (default the terminal width)
+    Wrap(Option<usize>),
+    /// `&expand[<n>]` - replace tabs with spaces to every nth column (default 8)
+    ExpandTabs(usize),
+    /// `&parsedate"<format>"` - read dates in a strftime-style format as ISO 8601
+    ParseDate(String),
+    /// `&formatdate"<format>"` - write ISO 8601 dates in a strftime-style format
+    FormatDate(String),
+    /// `&epoch["<format>"]` - convert dates to seconds since the Unix epoch
+    ToEpoch(Option<String>),
+    /// `?/<regex>/<op>` - apply an operator only to elements matching the regex
+    /// `?!/<regex>/<op>` - apply an operator only to elements not matching it
+    ApplyIf {
+        pattern: String,
+        negate: bool,
+        op: Box<Operator>,
+    },
+    /// `*<op>` - apply an operator to every leaf, however deeply nested
+    DeepMap(Box<Operator>),
     /// `;` - no-op separator
     NoOp,
 }
 
+impl Operator {
+    /// The selections this operator takes, so column names can be resolved
+    /// before compiling.
+    pub fn selections_mut(&mut self) -> Vec<&mut Selection> {
+        match self {
+            Operator::UppercaseSelected(sel)
+            | Operator::LowercaseSelected(sel)
+            | Operator::ToNumberSelected(sel)
+            | Operator::TrimSelected(sel)
+            | Operator::TrimCharsSelected { selection: sel, .. }
+            | Operator::DedupeSelectionWithCounts(sel)
+            | Operator::DedupeSelectionWithCountsIgnoreCase(sel)
+            | Operator::DedupeSelection(sel)
+            | Operator::Partition(sel)
+            | Operator::Selection(sel)
+            | Operator::FilterSelected { selection: sel, .. }
+            | Operator::Compare { selection: sel, .. }
+            | Operator::GroupBy(sel)
+            | Operator::ChunkBy(sel)
+            | Operator::SortBy { keys: sel, .. } => vec![sel],
+            Operator::Replace { selection, .. }
+            | Operator::GroupAgg {
+                value: selection, ..
+            } => selection.iter_mut().collect(),
+            Operator::WeightedSum { a, b } | Operator::Arithmetic { a, b, .. } => vec![a, b],
+            Operator::Agg { key, value, .. } => std::iter::once(key).chain(value).collect(),
+            Operator::DeepMap(op) | Operator::ApplyIf { op, .. } => op.selections_mut(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl fmt::Display for Programme {
+    /// One operator per line, indented by how deep `@` has descended, as shown
+    /// by `--explain`. `;` separators are left out.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut depth = 0usize;
+        for op in &self.operators {
+            match op {
+                Operator::NoOp => continue,
+                Operator::Ascend => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+            writeln!(f, "{}{}", "  ".repeat(depth), op)?;
+            if *op == Operator::Descend {
+                depth += 1;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Operator {
+    /// A short description of what the operator does, with its arguments.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let matching = |negate: &bool| if *negate { "not matching" } else { "matching" };
+        match self {
+            Operator::Split => write!(f, "split by level"),
+            Operator::SplitDelim(d) => write!(f, "split on {:?}", d),
+            Operator::SplitAny(chars) => {
+                write!(f, "split on any of {:?}", chars.iter().collect::<String>())
+            }
+            Operator::SplitSentences => write!(f, "split into sentences"),
+            Operator::ParseKv => write!(f, "parse key=value pairs"),
+            Operator::SplitDelimWith {
+                delimiter,
+                keep,
+                limit,
+            } => {
+                write!(f, "split on {:?}", delimiter)?;
+                match keep {
+                    Some(KeepDelimiter::WithPrevious) => {
+                        write!(f, ", keeping it on the part before")?
+                    }
+                    Some(KeepDelimiter::WithNext) => write!(f, ", keeping it on the part after")?,
+                    None => {}
+                }
+                match limit {
+                    Some(n) => write!(f, ", into at most {} parts", n),
+                    None => Ok(()),
+                }
+            }
+            Operator::Join => write!(f, "join by level"),
+            Operator::JoinDelim(d) => write!(f, "join with {:?}", d),
+            Operator::Descend => write!(f, "descend into each element"),
+            Operator::Ascend => write!(f, "ascend"),
+            Operator::Uppercase => write!(f, "uppercase"),
+            Operator::UppercaseSelected(sel) => write!(f, "uppercase {}", sel),
+            Operator::Lowercase => write!(f, "lowercase"),
+            Operator::LowercaseSelected(sel) => write!(f, "lowercase {}", sel),
+            Operator::ConvertCase(style) => write!(f, "convert to {}", style),
+            Operator::Slugify => write!(f, "slugify"),
+            Operator::Rot(shift) => write!(f, "rotate letters by {}", shift),
+            Operator::Replace {
+                selection,
+                pattern,
+                replacement,
+                case_insensitive,
+                first_only,
+            } => {
+                let which = if *first_only {
+                    "first match"
+                } else {
+                    "matches"
+                };
+                write!(f, "replace {} of /{}/", which, pattern)?;
+                if *case_insensitive {
+                    write!(f, " ignoring case")?;
+                }
+                write!(f, " with {:?}", replacement)?;
+                match selection {
+                    Some(sel) => write!(f, " in {}", sel),
+                    None => Ok(()),
+                }
+            }
+            Operator::ReverseChars => write!(f, "reverse characters"),
+            Operator::ToNumber => write!(f, "convert to numbers"),
+            Operator::ToNumberSelected(sel) => write!(f, "convert {} to numbers", sel),
+            Operator::Trim => write!(f, "trim whitespace"),
+            Operator::TrimSelected(sel) => write!(f, "trim whitespace from {}", sel),
+            Operator::TrimChars { chars, side } => write!(f, "trim {:?}{}", chars, side),
+            Operator::TrimCharsSelected {
+                selection,
+                chars,
+                side,
+            } => write!(f, "trim {:?}{} in {}", chars, side, selection),
+            Operator::Format(template) => write!(f, "format as {:?}", template),
+            Operator::StripComments { marker, keep_empty } => {
+                write!(f, "strip comments from {:?}", marker)?;
+                if *keep_empty {
+                    write!(f, ", keeping comment-only lines")?;
+                }
+                Ok(())
+            }
+            Operator::DeleteEmpty => write!(f, "delete empty elements"),
+            Operator::Flatten => write!(f, "flatten one level"),
+            Operator::DedupeWithCounts => write!(f, "dedupe with counts"),
+            Operator::DedupeWithCountsIgnoreCase => write!(f, "dedupe with counts, ignoring case"),
+            Operator::DedupeWithPercentages => write!(f, "dedupe with percentages"),
+            Operator::DedupeSelectionWithCounts(sel) => write!(f, "dedupe by {} with counts", sel),
+            Operator::DedupeSelectionWithCountsIgnoreCase(sel) => {
+                write!(f, "dedupe by {} with counts, ignoring case", sel)
+            }
+            Operator::AdjacentDedupe { counts: false } => write!(f, "collapse adjacent repeats"),
+            Operator::AdjacentDedupe { counts: true } => {
+                write!(f, "collapse adjacent repeats with counts")
+            }
+            Operator::Mode => write!(f, "most common element"),
+            Operator::DedupeSelection(sel) => write!(f, "keep first of each {}", sel),
+            Operator::Sum => write!(f, "sum"),
+            Operator::Quantile(p) => write!(f, "percentile {}", p),
+            Operator::Variance { sample: true } => write!(f, "sample variance"),
+            Operator::Variance { sample: false } => write!(f, "population variance"),
+            Operator::StdDev { sample: true } => write!(f, "sample standard deviation"),
+            Operator::StdDev { sample: false } => write!(f, "population standard deviation"),
+            Operator::MovingAverage(n) => write!(f, "moving average over {}", n),
+            Operator::WeightedSum { a, b } => write!(f, "sum of {} * {}", a, b),
+            Operator::Arithmetic { a, op, b } => write!(f, "compute {} {} {}", a, op, b),
+            Operator::AddScalar(n) => write!(f, "add {}", n),
+            Operator::MulScalar(n) => write!(f, "multiply by {}", n),
+            Operator::Clamp { min, max } => write!(f, "clamp to {}..{}", min, max),
+            Operator::SetLevel(level) => write!(f, "set level to {}", level.name()),
+            Operator::Count => write!(f, "count"),
+            Operator::CountLeaves => write!(f, "count leaves"),
+            Operator::Stats => write!(f, "count lines, words and chars"),
+            Operator::Columnate { width, separator } => {
+                write!(f, "columnate")?;
+                if let Some(width) = width {
+                    write!(f, ", columns at most {} wide", width)?;
+                }
+                if let Some(separator) = separator {
+                    write!(f, ", separated by {:?}", separator)?;
+                }
+                Ok(())
+            }
+            Operator::Histogram => write!(f, "bar chart"),
+            Operator::Partition(sel) => write!(f, "partition at {}", sel),
+            Operator::SortDescending => write!(f, "sort descending"),
+            Operator::SortAscending => write!(f, "sort ascending"),
+            Operator::SortUnique { descending: false } => write!(f, "sort ascending, unique"),
+            Operator::SortUnique { descending: true } => write!(f, "sort descending, unique"),
+            Operator::SortBy { keys, descending } => {
+                write!(f, "sort by ")?;
+                for (i, (key, desc)) in keys.items.iter().zip(descending).enumerate() {
+                    let order = if *desc { "descending" } else { "ascending" };
+                    let sep = if i > 0 { ", " } else { "" };
+                    write!(f, "{}{} {}", sep, key, order)?;
+                }
+                Ok(())
+            }
+            Operator::SortChars { ignore_case: false } => write!(f, "sort characters"),
+            Operator::SortChars { ignore_case: true } => {
+                write!(f, "sort characters, ignoring case")
+            }
+            Operator::Rank { sorted: false } => write!(f, "rank"),
+            Operator::Rank { sorted: true } => write!(f, "rank, in rank order"),
+            Operator::Head(n) => write!(f, "first {}", n),
+            Operator::Tail(n) => write!(f, "last {}", n),
+            Operator::LineRange { first, last } => match last {
+                Some(last) => write!(f, "lines {} to {}", first, last),
+                None => write!(f, "lines {} on", first),
+            },
+            Operator::Repeat { n, whole: false } => write!(f, "repeat each {} times", n),
+            Operator::Repeat { n, whole: true } => write!(f, "repeat all {} times", n),
+            Operator::Sample(n) => write!(f, "sample {}", n),
+            Operator::Shuffle => write!(f, "shuffle"),
+            Operator::Selection(sel) => write!(f, "select {}", sel),
+            Operator::Filter { pattern, negate } => {
+                write!(f, "keep elements {} /{}/", matching(negate), pattern)
+            }
+            Operator::FilterContext {
+                pattern,
+                negate,
+                before,
+                after,
+            } => write!(
+                f,
+                "keep elements {} /{}/, with {} before and {} after",
+                matching(negate),
+                pattern,
+                before,
+                after
+            ),
+            Operator::FilterSelected {
+                selection,
+                pattern,
+                negate,
+            } => write!(
+                f,
+                "keep rows with {} {} /{}/",
+                selection,
+                matching(negate),
+                pattern
+            ),
+            Operator::FilterFields {
+                pattern,
+                negate,
+                all,
+            } => write!(
+                f,
+                "keep rows with {} field {} /{}/",
+                if *all { "every" } else { "any" },
+                matching(negate),
+                pattern
+            ),
+            Operator::Matches { pattern, negate } => {
+                write!(
+                    f,
+                    "true for elements {} /{}/, else false",
+                    matching(negate),
+                    pattern
+                )
+            }
+            Operator::Compare {
+                selection,
+                cmp,
+                value,
+            } => write!(f, "keep rows where {} {} {}", selection, cmp, value),
+            Operator::CompareLength { cmp, value } => {
+                write!(f, "keep elements whose length {} {}", cmp, value)
+            }
+            Operator::Match { pattern } => write!(f, "matches of /{}/", pattern),
+            Operator::Extract { pattern } => write!(f, "extract /{}/", pattern),
+            Operator::GroupBy(sel) => write!(f, "group by {}", sel),
+            Operator::ChunkBy(sel) => write!(f, "chunk by {}", sel),
+            Operator::Ngram(n) => write!(f, "{}-grams", n),
+            Operator::Agg { key, funcs, value } => {
+                write!(f, "group by {} and aggregate {}", key, AggFuncs(funcs))?;
+                match value {
+                    Some(sel) => write!(f, " of {}", sel),
+                    None => Ok(()),
+                }
+            }
+            Operator::GroupAgg { funcs, value } => {
+                write!(f, "aggregate each group {}", AggFuncs(funcs))?;
+                match value {
+                    Some(sel) => write!(f, " of {}", sel),
+                    None => Ok(()),
+                }
+            }
+            Operator::Squeeze(None) => write!(f, "squeeze whitespace"),
+            Operator::Squeeze(Some(chars)) => write!(f, "squeeze runs of {:?}", chars),
+            Operator::StripAnsi => write!(f, "strip ANSI escapes"),
+            Operator::Wrap(None) => write!(f, "wrap at the terminal width"),
+            Operator::Wrap(Some(n)) => write!(f, "wrap at {} columns", n),
+            Operator::ExpandTabs(n) => write!(f, "expand tabs every {} columns", n),
+            Operator::ParseDate(format) => write!(f, "parse dates as {:?}", format),
+            Operator::FormatDate(format) => write!(f, "format dates as {:?}", format),
+            Operator::ToEpoch(None) => write!(f, "convert dates to epoch seconds"),
+            Operator::ToEpoch(Some(format)) => {
+                write!(f, "convert dates in {:?} to epoch seconds", format)
+            }
+            Operator::ApplyIf {
+                pattern,
+                negate,
+                op,
+            } => write!(f, "for elements {} /{}/: {}", matching(negate), pattern, op),
+            Operator::DeepMap(op) => write!(f, "for every leaf: {}", op),
+            Operator::NoOp => write!(f, "nothing"),
+        }
+    }
+}
+
+/// Aggregate functions listed for display, e.g. `count, sum`.
+struct AggFuncs<'a>(&'a [AggFunc]);
+
+impl fmt::Display for AggFuncs<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, func) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            let name = match func {
+                AggFunc::Count => "count",
+                AggFunc::Sum => "sum",
+                AggFunc::Mean => "mean",
+                AggFunc::Min => "min",
+                AggFunc::Max => "max",
+            };
+            write!(f, "{}", name)?;
+        }
+        Ok(())
+    }
+}
+
+/// An aggregate function computed per group by `A`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AggFunc {
+    /// `#` - number of rows in the group
+    Count,
+    /// `+` - sum of numeric values
+    Sum,
+    /// `a` - arithmetic mean of numeric values
+    Mean,
+    /// `<` - smallest numeric value
+    Min,
+    /// `>` - largest numeric value
+    Max,
+}
+
+/// An arithmetic operator used by `=`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArithOp {
+    /// `+`
+    Add,
+    /// `-`
+    Sub,
+    /// `*`
+    Mul,
+    /// `/`
+    Div,
+}
+
+/// A naming convention for `ConvertCase`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CaseStyle {
+    /// `snake_case`
+    Snake,
+    /// `camelCase`
+    Camel,
+    /// `kebab-case`
+    Kebab,
+}
+
+/// Which ends of the text a trim applies to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TrimSide {
+    Both,
+    /// `<` - the start only
+    Start,
+    /// `>` - the end only
+    End,
+}
+
+/// Which part a kept delimiter stays attached to when splitting.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum KeepDelimiter {
+    /// `<` - end of the part before the delimiter
+    WithPrevious,
+    /// `>` - start of the part after the delimiter
+    WithNext,
+}
+
+/// A numeric comparison used by the `?` filters.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Comparison {
+    /// `<`
+    Lt,
+    /// `<=`
+    Le,
+    /// `>`
+    Gt,
+    /// `>=`
+    Ge,
+    /// `=`
+    Eq,
+    /// `!=`
+    Ne,
+}
+
+impl fmt::Display for ArithOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match self {
+            ArithOp::Add => "+",
+            ArithOp::Sub => "-",
+            ArithOp::Mul => "*",
+            ArithOp::Div => "/",
+        };
+        write!(f, "{}", symbol)
+    }
+}
+
+impl fmt::Display for CaseStyle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            CaseStyle::Snake => "snake_case",
+            CaseStyle::Camel => "camelCase",
+            CaseStyle::Kebab => "kebab-case",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl fmt::Display for TrimSide {
+    /// Which ends are trimmed, as a suffix for `trim "<chars>"`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrimSide::Both => Ok(()),
+            TrimSide::Start => write!(f, " from the start"),
+            TrimSide::End => write!(f, " from the end"),
+        }
+    }
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match self {
+            Comparison::Lt => "<",
+            Comparison::Le => "<=",
+            Comparison::Gt => ">",
+            Comparison::Ge => ">=",
+            Comparison::Eq => "=",
+            Comparison::Ne => "!=",
+        };
+        write!(f, "{}", symbol)
+    }
+}
+
+impl Comparison {
+    /// Returns whether `a <cmp> b` holds.
+    pub fn holds(self, a: f64, b: f64) -> bool {
+        match self {
+            Comparison::Lt => a < b,
+            Comparison::Le => a <= b,
+            Comparison::Gt => a > b,
+            Comparison::Ge => a >= b,
+            Comparison::Eq => a == b,
+            Comparison::Ne => a != b,
+        }
+    }
+
+    /// Returns whether `a <cmp> b` holds, given how `a` orders against `b`.
+    pub fn holds_for(self, ordering: Ordering) -> bool {
+        match self {
+            Comparison::Lt => ordering.is_lt(),
+            Comparison::Le => ordering.is_le(),
+            Comparison::Gt => ordering.is_gt(),
+            Comparison::Ge => ordering.is_ge(),
+            Comparison::Eq => ordering.is_eq(),
+            Comparison::Ne => ordering.is_ne(),
+        }
+    }
+}
+
 /// A selection is a comma-separated list of select items.
 /// It's a reduce operator that collapses an array to a subset.
 #[derive(Debug, Clone, PartialEq)]
@@ -81,13 +747,77 @@ pub struct Selection {
     pub items: Vec<SelectItem>,
 }
 
-/// A single item in a selection: either an index or a slice.
+impl fmt::Display for Selection {
+    /// The selection as it is written, e.g. `0,2:5`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, item) in self.items.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{}", item)?;
+        }
+        Ok(())
+    }
+}
+
+/// A single item in a selection: an index, a slice, a column name or a
+/// predicate.
 #[derive(Debug, Clone, PartialEq)]
 pub enum SelectItem {
     /// A single index (0-based, negative counts from end)
     Index(i64),
     /// A slice with optional start, end, and step
     Slice(Slice),
+    /// A column name from the `--header` row, resolved to an index when compiling
+    Name(String),
+    /// `[<selection><cmp><value>]` - the indices of elements whose selected
+    /// value compares true, found when the selection is applied
+    Predicate(Predicate),
+}
+
+/// A test on each element of an array, selecting those it holds for.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Predicate {
+    /// The part of each element to compare, as for `g`
+    pub column: Selection,
+    pub cmp: Comparison,
+    pub value: Literal,
+}
+
+/// The value a predicate compares against.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Literal {
+    /// Compared numerically; elements that aren't numbers never match
+    Number(f64),
+    /// Compared as text
+    Text(String),
+}
+
+impl fmt::Display for Literal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Literal::Number(n) => write!(f, "{}", n),
+            Literal::Text(s) => write!(f, "{:?}", s),
+        }
+    }
+}
+
+impl fmt::Display for SelectItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SelectItem::Index(i) => write!(f, "{}", i),
+            SelectItem::Slice(slice) => {
+                let bound = |b: Option<i64>| b.map(|n| n.to_string()).unwrap_or_default();
+                write!(f, "{}:{}", bound(slice.start), bound(slice.end))?;
+                match slice.step {
+                    Some(step) => write!(f, ":{}", step),
+                    None => Ok(()),
+                }
+            }
+            SelectItem::Name(name) => write!(f, "{:?}", name),
+            SelectItem::Predicate(p) => write!(f, "[{}{}{}]", p.column, p.cmp, p.value),
+        }
+    }
 }
 
 /// A slice selects a range of elements.
@@ -101,3 +831,41 @@ pub struct Slice {
     pub end: Option<i64>,
     pub step: Option<i64>,
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::parse_programme;
+
+    fn explain(programme: &str) -> String {
+        parse_programme(programme).unwrap().to_string()
+    }
+
+    #[test]
+    fn explain_indents_descended_operators() {
+        assert_eq!(
+            explain("s@8^do:10"),
+            "split by level\n\
+             descend into each element\n  \
+               select 8\n\
+             ascend\n\
+             dedupe with counts\n\
+             sort descending\n\
+             select :10\n"
+        );
+    }
+
+    #[test]
+    fn explain_shows_arguments() {
+        assert_eq!(
+            explain(r#"S",";!/x/?1>=3;r0/a/b/iM"#),
+            "split on \",\"\n\
+             keep elements not matching /x/\n\
+             keep rows where 1 >= 3\n\
+             replace first match of /a/ ignoring case with \"b\" in 0\n"
+        );
+        assert_eq!(
+            explain("A0[#,+]2*u"),
+            "group by 0 and aggregate count, sum of 2\nfor every leaf: uppercase\n"
+        );
+    }
+}
@@ -0,0 +1,207 @@
+//! Tab completion for interactive mode.
+
+/// Keywords of the named operators, written after `&`.
+const KEYWORDS: &[&str] = &[
+    "camel",
+    "chunk",
+    "clamp",
+    "context",
+    "cycle",
+    #[cfg(feature = "dates")]
+    "epoch",
+    "expand",
+    #[cfg(feature = "dates")]
+    "formatdate",
+    "kebab",
+    "kv",
+    "level",
+    "lines",
+    "matches",
+    "mode",
+    "movavg",
+    "ngram",
+    #[cfg(feature = "dates")]
+    "parsedate",
+    "pstd",
+    "pvar",
+    "rank",
+    "ranko",
+    "repeat",
+    "rot",
+    "rsortu",
+    "sentences",
+    "slug",
+    "snake",
+    "sortby",
+    "sortchars",
+    "sortcharsi",
+    "sortu",
+    "squeeze",
+    "std",
+    "stripansi",
+    "uniq",
+    "uniqc",
+    "var",
+    "wrap",
+];
+
+/// Argument templates for operators that take one. The cursor is left after
+/// the template's first character, e.g. inside the quotes of `S""`.
+const TEMPLATES: &[(&str, &str)] = &[
+    ("S", "\"\""),
+    ("J", "\"\""),
+    ("C", "\"\""),
+    ("F", "\"\""),
+    ("r", "///"),
+    ("m", "//"),
+    ("e", "//"),
+    ("!", "//"),
+    ("g", "0"),
+    ("&squeeze", "\"\""),
+    ("&level", "\"\""),
+    ("&matches", "//"),
+    ("&parsedate", "\"\""),
+    ("&formatdate", "\"\""),
+    ("&epoch", "\"\""),
+];
+
+/// Keywords that a partly typed keyword could be completed to.
+struct Cycle {
+    /// Byte range of the programme holding the current candidate.
+    start: usize,
+    end: usize,
+    candidates: Vec<&'static str>,
+    index: usize,
+}
+
+/// Completes operators under the cursor, cycling through candidates when
+/// Tab is pressed again.
+#[derive(Default)]
+pub struct Completer {
+    cycle: Option<Cycle>,
+}
+
+impl Completer {
+    /// Forget the candidates being cycled through, e.g. after another key.
+    pub fn reset(&mut self) {
+        self.cycle = None;
+    }
+
+    /// Complete the text before `cursor`, updating the programme and cursor.
+    ///
+    /// After `&`, a partial keyword is completed to the first keyword it
+    /// starts, and pressing Tab again moves on to the next. After an operator
+    /// that takes an argument, a template for the argument is inserted.
+    pub fn complete(&mut self, programme: &mut String, cursor: &mut usize) {
+        if let Some(cycle) = &mut self.cycle
+            && cycle.end == *cursor
+        {
+            cycle.index = (cycle.index + 1) % cycle.candidates.len();
+            let candidate = cycle.candidates[cycle.index];
+            programme.replace_range(cycle.start..cycle.end, candidate);
+            cycle.end = cycle.start + candidate.len();
+            *cursor = cycle.end;
+            return;
+        }
+        self.cycle = None;
+
+        let before = &programme[..*cursor];
+        let word_start = before
+            .trim_end_matches(|c: char| c.is_ascii_lowercase())
+            .len();
+        let op = if before[..word_start].ends_with('&') {
+            let word = &before[word_start..];
+            let candidates: Vec<&'static str> = KEYWORDS
+                .iter()
+                .copied()
+                .filter(|k| k.starts_with(word) && *k != word)
+                .collect();
+            if let Some(&first) = candidates.first() {
+                programme.replace_range(word_start..*cursor, first);
+                *cursor = word_start + first.len();
+                if candidates.len() > 1 {
+                    self.cycle = Some(Cycle {
+                        start: word_start,
+                        end: *cursor,
+                        candidates,
+                        index: 0,
+                    });
+                }
+                return;
+            }
+            &before[word_start - 1..]
+        } else {
+            match before.char_indices().next_back() {
+                Some((i, _)) => &before[i..],
+                None => return,
+            }
+        };
+
+        if let Some((_, template)) = TEMPLATES.iter().find(|(name, _)| *name == op) {
+            programme.insert_str(*cursor, template);
+            *cursor += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn complete(completer: &mut Completer, programme: &str) -> (String, usize) {
+        let mut programme = programme.to_string();
+        let mut cursor = programme.len();
+        completer.complete(&mut programme, &mut cursor);
+        (programme, cursor)
+    }
+
+    #[test]
+    fn test_completes_unique_keyword() {
+        let mut completer = Completer::default();
+        assert_eq!(complete(&mut completer, "s&mo"), ("s&mode".to_string(), 6));
+        assert_eq!(complete(&mut completer, "&rank"), ("&ranko".to_string(), 6));
+    }
+
+    #[test]
+    fn test_cycles_through_keywords() {
+        let mut completer = Completer::default();
+        let mut programme = "&sort".to_string();
+        let mut cursor = programme.len();
+        let mut seen = Vec::new();
+        for _ in 0..5 {
+            completer.complete(&mut programme, &mut cursor);
+            assert_eq!(cursor, programme.len());
+            seen.push(programme.clone());
+        }
+        assert_eq!(
+            seen,
+            ["&sortby", "&sortchars", "&sortcharsi", "&sortu", "&sortby"]
+        );
+
+        // Once the cursor moves, Tab starts afresh
+        completer.reset();
+        assert_eq!(complete(&mut completer, "&sq"), ("&squeeze".to_string(), 8));
+    }
+
+    #[test]
+    fn test_inserts_argument_templates() {
+        let mut completer = Completer::default();
+        assert_eq!(complete(&mut completer, "sS"), ("sS\"\"".to_string(), 3));
+        assert_eq!(complete(&mut completer, "r"), ("r///".to_string(), 2));
+        assert_eq!(complete(&mut completer, "sg"), ("sg0".to_string(), 3));
+        assert_eq!(
+            complete(&mut completer, "&squeeze"),
+            ("&squeeze\"\"".to_string(), 9)
+        );
+    }
+
+    #[test]
+    fn test_nothing_to_complete() {
+        let mut completer = Completer::default();
+        assert_eq!(complete(&mut completer, ""), (String::new(), 0));
+        assert_eq!(complete(&mut completer, "s@"), ("s@".to_string(), 2));
+        assert_eq!(complete(&mut completer, "&var"), ("&var".to_string(), 4));
+        // A keyword ending in a letter that takes a template is still a keyword
+        assert_eq!(complete(&mut completer, "&slug"), ("&slug".to_string(), 5));
+    }
+}
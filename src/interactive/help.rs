@@ -17,10 +17,29 @@ pub enum HelpLine {
 pub const OPERATOR_HELP: &[HelpLine] = &[
     HelpLine::Heading("Operators:"),
     HelpLine::Row("s", "split on whitespace", "S<d>", "split on delimiter"),
+    HelpLine::Row(
+        "S>\"<d>\"",
+        "split, keep on next",
+        "S<\"<d>\"",
+        "split, keep on prev",
+    ),
+    HelpLine::Row(
+        "S<d>{<n>}",
+        "split into n parts",
+        "S[<chars>]",
+        "split on any char",
+    ),
     HelpLine::Row("j", "join with level sep", "J<d>", "join with delimiter"),
     HelpLine::Row("l", "lowercase", "L<sel>", "lowercase selected"),
     HelpLine::Row("u", "uppercase", "U<sel>", "uppercase selected"),
+    HelpLine::Single("R", "reverse the chars of each string"),
     HelpLine::Row("t", "trim whitespace", "T<sel>", "trim selected"),
+    HelpLine::Row(
+        "t\"<c>\"",
+        "trim characters",
+        "t<<c>  t><c>",
+        "trim start / end",
+    ),
     HelpLine::Row("n", "to number", "N<sel>", "to number selected"),
     HelpLine::Row(
         "r/<p>/<r>/",
@@ -29,26 +48,124 @@ pub const OPERATOR_HELP: &[HelpLine] = &[
         "replace in selected",
     ),
     HelpLine::Row("/<pat>/", "filter keep", "!/<pat>/", "filter remove"),
-    HelpLine::Single("m/<pat>/", "matches to array"),
+    HelpLine::Row(
+        "?<sel>/<pat>/",
+        "filter on selected",
+        "?<sel>!/<pat>/",
+        "remove on selected",
+    ),
+    HelpLine::Single("&context<n>/<p>/", "keep matches and n either side"),
+    HelpLine::Single("&matches/<p>/", "true or false for each element"),
+    HelpLine::Row(
+        "?|/<pat>/",
+        "any field matches",
+        "?&/<pat>/",
+        "every field matches",
+    ),
+    HelpLine::Row(
+        "?<sel><cmp><n>",
+        "compare value",
+        "?#<cmp><n>",
+        "compare length",
+    ),
+    HelpLine::Row(
+        "?/<pat>/<op>",
+        "op on matching",
+        "?!/<pat>/<op>",
+        "op on the rest",
+    ),
+    HelpLine::Row(
+        "m/<pat>/",
+        "matches to array",
+        "e/<pat>/",
+        "extract group 1",
+    ),
+    HelpLine::Row(
+        "r/<p>/<r>/iM",
+        "ignore case, first",
+        "C[!]<m>",
+        "strip comments",
+    ),
     HelpLine::Row("d", "dedupe", "D<sel>", "dedupe on selected"),
+    HelpLine::Row("di", "dedupe ignoring case", "Di<sel>", "... on selected"),
+    HelpLine::Row("d%", "dedupe with percents", "k<sel>", "first row per key"),
+    HelpLine::Row("h<n>", "first n", "H<n>", "last n"),
+    HelpLine::Single("&lines<a>-<b>", "lines a to b, counting from 1"),
+    HelpLine::Row("Z<n>", "random sample of n", "z", "shuffle"),
     HelpLine::Row("o", "sort descending", "O", "sort ascending"),
     HelpLine::Row("x", "delete empty", "g<sel>", "group by"),
     HelpLine::Row("#", "count", "+", "sum"),
-    HelpLine::Row("c", "columnate", "p<sel>", "partition"),
+    HelpLine::Single("##", "count leaves at any depth"),
+    HelpLine::Single("q<percent>", "percentile (q50 median, q95, q99)"),
+    HelpLine::Single("w", "[lines, words, chars], no newlines"),
+    HelpLine::Row(
+        "W<sel>*<sel>",
+        "sum of products",
+        "A<sel>[f]<sel>",
+        "aggregate (# + a < >)",
+    ),
+    HelpLine::Single("G[f]<sel>", "aggregate groups from g"),
+    HelpLine::Single("=<sel><op><sel>", "arithmetic on each row (+ - * /)"),
+    HelpLine::Row("+{<num>}", "add constant", "*{<num>}", "multiply by"),
+    HelpLine::Row("c", "columnate", "p<sel>", "split before (p-1)"),
+    HelpLine::Single("c[{<n>}][\"<sep>\"]", "columns at most n wide, sep between"),
+    HelpLine::Single("b", "bar chart of [count, label] rows"),
+    HelpLine::Single("F\"<template>\"", "format each row via {0}, {1}, {}"),
     HelpLine::Row("@", "descend", "^", "ascend"),
+    HelpLine::Single("*<op>", "apply op to every leaf, any depth"),
+    HelpLine::Single("&level\"<name>\"", "relabel as file, line, word, char"),
+    HelpLine::Single("&squeeze[\"<c>\"]", "collapse repeated spaces"),
+    HelpLine::Single("&expand[<n>]", "tabs to spaces, stops every 8 (n)"),
+    HelpLine::Single("&stripansi", "remove colours and other escapes"),
+    HelpLine::Single("&wrap[<n>]", "wrap lines at n (or terminal) width"),
+    HelpLine::Single("&kv", "key=value pairs to [key, value] rows"),
+    HelpLine::Single("&sentences", "split text into sentences"),
+    HelpLine::Single("&slug", "lowercase, hyphen-separated URL slug"),
+    HelpLine::Single("&rot<n>", "rotate letters n places (&rot13)"),
+    HelpLine::Single("&snake &camel", "rewrite identifiers (also &kebab)"),
+    HelpLine::Single("&chunk<sel>", "split into runs of equal keys"),
+    HelpLine::Single("&ngram<n>", "overlapping runs of n elements"),
+    HelpLine::Row("&repeat<n>", "each n times", "&cycle<n>", "all n times"),
+    HelpLine::Single("&sortby<k><>,..", "sort by keys, each up < or down >"),
+    HelpLine::Single("&sortu &rsortu", "sort up / down, dropping repeats"),
+    HelpLine::Single("&mode", "most common element"),
+    HelpLine::Single("&sortchars[i]", "sort chars of each string (anagrams)"),
+    HelpLine::Single("&rank &ranko", "[rank, value], largest first"),
+    HelpLine::Single("&uniq / &uniqc", "collapse adjacent repeats (count)"),
+    HelpLine::Single("&clamp<lo>:<hi>", "limit numbers to a range"),
+    HelpLine::Single("&movavg<n>", "mean of the last n at each position"),
+    HelpLine::Single("&var &pvar", "variance: sample / population"),
+    HelpLine::Single("&std &pstd", "std deviation: sample / population"),
+    HelpLine::Single("&parsedate\"f\"", "read dates in format f as ISO 8601"),
+    HelpLine::Single("&formatdate\"f\"", "write ISO 8601 dates in format f"),
+    HelpLine::Single("&epoch[\"f\"]", "dates to seconds since 1970"),
     HelpLine::Row(
         ";",
         "separator (no-op)",
         "<sel>",
         "select (e.g. 0, 1:3, ::2)",
     ),
+    HelpLine::Single("~<n>", "select last n, same as -n:"),
+    HelpLine::Single("\"<name>\"", "select column named in --header row"),
+    HelpLine::Single("[<sel><cmp><v>]", "select rows whose field compares true"),
 ];
 
-pub const INTERACTIVE_KEYS: &[(&str, &str)] = &[
-    ("Enter", "Commit"),
-    ("^C/Esc", "Cancel"),
-    ("^J", "JSON"),
-    ("^H", "Help"),
+/// Keys for interactive mode, one row per line of help.
+pub const INTERACTIVE_KEYS: &[&[(&str, &str)]] = &[
+    &[
+        ("Enter", "Commit"),
+        ("^C/Esc", "Cancel"),
+        ("^J", "JSON"),
+        ("^B", "Debug"),
+        ("^H", "Help"),
+        ("^S/^O", "Save/Recall"),
+    ],
+    &[
+        ("Tab", "Complete"),
+        ("^R", "Search"),
+        ("^Z/^Y", "Undo/Redo"),
+        ("^W/^U", "Delete word/line"),
+    ],
 ];
 
 const OP_WIDTH: usize = 16;
@@ -56,8 +173,8 @@ const DESC_WIDTH: usize = 21;
 
 /// Returns the total number of lines in the help output.
 pub fn help_line_count() -> usize {
-    // OPERATOR_HELP lines + "Keys:" heading + keys row
-    OPERATOR_HELP.len() + 2
+    // OPERATOR_HELP lines + "Keys:" heading + keys rows
+    OPERATOR_HELP.len() + 1 + INTERACTIVE_KEYS.len()
 }
 
 /// Generate plain text help for CLI --help.
@@ -155,7 +272,7 @@ pub fn write_help_text<W: io::Write>(w: &mut W, use_color: bool) -> io::Result<(
 pub fn write_intro<W: io::Write>(w: &mut W, use_color: bool) -> io::Result<()> {
     let intro_before_example =
         "T is a concise language for manipulating text, replacing common usage
-patterns of Unix utilities like grep, sed, cut, awk, sort, and uniq.
+patterns of Unix utilities like grep, sed, cut, awk, sort, uniq, and wc.
 
 ";
     let example_heading = "Example:";
@@ -301,9 +418,12 @@ pub fn draw_help(stdout: &mut io::Stdout, max_lines: usize) -> io::Result<usize>
         )?;
         lines_below += 1;
     }
-    if lines_below < max_lines {
+    for row in INTERACTIVE_KEYS {
+        if lines_below >= max_lines {
+            break;
+        }
         execute!(stdout, Print("\r\n  "))?;
-        for (i, (key, desc)) in INTERACTIVE_KEYS.iter().enumerate() {
+        for (i, (key, desc)) in row.iter().enumerate() {
             if i > 0 {
                 execute!(stdout, Print("  "))?;
             }
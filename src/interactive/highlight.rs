@@ -0,0 +1,161 @@
+//! Syntax highlighting for the programme on the prompt line.
+
+use std::ops::Range;
+
+use crossterm::style::Color;
+
+/// What a span of the programme is, for colouring.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    /// An operator, including named operators like `&sortu`
+    Operator,
+    /// Selections and numeric arguments, e.g. `1:3` or the `20` of `h20`
+    Selection,
+    /// Quoted strings, patterns and delimiter sets
+    Argument,
+    /// Whitespace
+    Plain,
+}
+
+impl TokenKind {
+    pub fn color(self) -> Option<Color> {
+        match self {
+            TokenKind::Operator => Some(Color::Cyan),
+            TokenKind::Selection => Some(Color::Yellow),
+            TokenKind::Argument => Some(Color::Green),
+            TokenKind::Plain => None,
+        }
+    }
+}
+
+/// Split a programme into spans by kind.
+///
+/// This is a lightweight scan rather than a parse, so it copes with the
+/// half-typed programmes of interactive mode: an unclosed string or pattern
+/// runs to the end.
+pub fn tokenize(programme: &str) -> Vec<(TokenKind, &str)> {
+    let mut tokens = Vec::new();
+    let mut last_op = None;
+    let mut rest = programme;
+    while let Some(c) = rest.chars().next() {
+        let (kind, len) = match c {
+            '"' => (TokenKind::Argument, delimited_len(rest, '"', 1)),
+            '/' => {
+                // `r/<pattern>/<replacement>/` has two parts, then flags
+                let parts = if last_op == Some('r') { 2 } else { 1 };
+                let mut len = delimited_len(rest, '/', parts);
+                if parts == 2 {
+                    len += rest[len..]
+                        .find(|c| c != 'i' && c != 'M')
+                        .unwrap_or(rest.len() - len);
+                }
+                (TokenKind::Argument, len)
+            }
+            '[' if last_op == Some('S') => (TokenKind::Argument, delimited_len(rest, ']', 1)),
+            '&' => {
+                let word = rest[1..]
+                    .find(|c: char| !c.is_ascii_lowercase())
+                    .unwrap_or(rest.len() - 1);
+                (TokenKind::Operator, 1 + word)
+            }
+            c if c.is_ascii_digit() || matches!(c, '-' | ':' | ',' | '.') => {
+                let len = rest
+                    .find(|c: char| !(c.is_ascii_digit() || matches!(c, '-' | ':' | ',' | '.')))
+                    .unwrap_or(rest.len());
+                (TokenKind::Selection, len)
+            }
+            c if c.is_whitespace() => (TokenKind::Plain, c.len_utf8()),
+            c => {
+                last_op = Some(c);
+                (TokenKind::Operator, c.len_utf8())
+            }
+        };
+        let (token, tail) = rest.split_at(len);
+        tokens.push((kind, token));
+        rest = tail;
+    }
+    tokens
+}
+
+/// Byte ranges of the tokens of a programme other than whitespace, which
+/// serve as words when editing.
+pub fn word_spans(programme: &str) -> Vec<Range<usize>> {
+    let mut start = 0;
+    let mut spans = Vec::new();
+    for (kind, token) in tokenize(programme) {
+        if kind != TokenKind::Plain {
+            spans.push(start..start + token.len());
+        }
+        start += token.len();
+    }
+    spans
+}
+
+/// Length of `s` up to and including the `parts`th unescaped `close` after
+/// its opening character, or all of `s` if it isn't closed.
+fn delimited_len(s: &str, close: char, parts: usize) -> usize {
+    let mut seen = 0;
+    let mut escaped = false;
+    for (i, c) in s.char_indices().skip(1) {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == close {
+            seen += 1;
+            if seen == parts {
+                return i + c.len_utf8();
+            }
+        }
+    }
+    s.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use TokenKind::*;
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            tokenize(r#"s@1:3^S",";&sortu h20"#),
+            vec![
+                (Operator, "s"),
+                (Operator, "@"),
+                (Selection, "1:3"),
+                (Operator, "^"),
+                (Operator, "S"),
+                (Argument, "\",\""),
+                (Operator, ";"),
+                (Operator, "&sortu"),
+                (Plain, " "),
+                (Operator, "h"),
+                (Selection, "20"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_patterns() {
+        assert_eq!(
+            tokenize(r"r0/a\/b/c/iM1!/x/m/y"),
+            vec![
+                (Operator, "r"),
+                (Selection, "0"),
+                (Argument, r"/a\/b/c/iM"),
+                (Selection, "1"),
+                (Operator, "!"),
+                (Argument, "/x/"),
+                (Operator, "m"),
+                (Argument, "/y"),
+            ]
+        );
+        assert_eq!(tokenize("S[,;]"), vec![(Operator, "S"), (Argument, "[,;]")]);
+    }
+
+    #[test]
+    fn test_word_spans() {
+        assert_eq!(word_spans("s@10 /x/"), vec![0..1, 1..2, 2..4, 5..8]);
+    }
+}
@@ -1,5 +1,7 @@
 //! Command history for interactive mode.
 
+use std::env;
+use std::ffi::OsString;
 use std::fs::{self, File};
 use std::io::{BufRead, BufReader, Write};
 use std::path::PathBuf;
@@ -10,11 +12,15 @@ pub struct History {
     entries: Vec<String>,
     index: Option<usize>,
     draft: String,
+    /// Where the history is saved, or None if it isn't
+    path: Option<PathBuf>,
 }
 
 impl History {
     pub fn load() -> Self {
-        let entries = history_path()
+        let path = history_path();
+        let entries = path
+            .as_ref()
             .and_then(|path| File::open(path).ok())
             .map(|file| BufReader::new(file).lines().map_while(Result::ok).collect())
             .unwrap_or_default();
@@ -23,15 +29,26 @@ impl History {
             entries,
             index: None,
             draft: String::new(),
+            path,
+        }
+    }
+
+    /// An empty history that is never saved, for `--no-history`.
+    pub fn disabled() -> Self {
+        Self {
+            entries: Vec::new(),
+            index: None,
+            draft: String::new(),
+            path: None,
         }
     }
 
     pub fn save(&self) {
-        let Some(path) = history_path() else { return };
+        let Some(path) = &self.path else { return };
         if let Some(parent) = path.parent() {
             let _ = fs::create_dir_all(parent);
         }
-        let Ok(mut file) = File::create(&path) else {
+        let Ok(mut file) = File::create(path) else {
             return;
         };
         let start = self.entries.len().saturating_sub(MAX_HISTORY_SIZE);
@@ -84,10 +101,69 @@ impl History {
         self.index = None;
         self.draft.clear();
     }
+
+    /// The newest entry containing `query`, starting before index `before`
+    /// (or from the newest entry if None). Returns its index and text.
+    pub fn search(&self, query: &str, before: Option<usize>) -> Option<(usize, &str)> {
+        let end = before.unwrap_or(self.entries.len()).min(self.entries.len());
+        self.entries[..end]
+            .iter()
+            .rposition(|entry| entry.contains(query))
+            .map(|i| (i, self.entries[i].as_str()))
+    }
+}
+
+/// State of an incremental reverse search through the history (Ctrl+R).
+pub struct Search {
+    pub query: String,
+    /// Index of the entry last matched
+    pub matched: Option<usize>,
+    /// Whether the query has stopped matching anything
+    pub failed: bool,
+    /// The programme and cursor from before the search, restored on cancel
+    pub draft: String,
+    pub draft_cursor: usize,
+}
+
+impl Search {
+    pub fn new(draft: &str, draft_cursor: usize) -> Self {
+        Self {
+            query: String::new(),
+            matched: None,
+            failed: false,
+            draft: draft.to_string(),
+            draft_cursor,
+        }
+    }
+}
+
+/// Directory for files kept between runs: `t` under `$XDG_DATA_HOME` if
+/// that is set, else under the platform's data directory.
+pub fn data_dir() -> Option<PathBuf> {
+    data_dir_from(env::var_os("XDG_DATA_HOME"), dirs::data_dir())
+}
+
+/// `data_dir`, given the value of `XDG_DATA_HOME` and the platform's data
+/// directory. A relative `XDG_DATA_HOME` is ignored, as the spec says.
+fn data_dir_from(xdg_data_home: Option<OsString>, platform: Option<PathBuf>) -> Option<PathBuf> {
+    xdg_data_home
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or(platform)
+        .map(|dir| dir.join("t"))
 }
 
+/// `$T_HISTORY_FILE` if that is set, else `history` in the data directory.
 fn history_path() -> Option<PathBuf> {
-    dirs::data_dir().map(|p| p.join("t").join("history"))
+    history_path_from(env::var_os("T_HISTORY_FILE"), data_dir())
+}
+
+/// `history_path`, given the value of `T_HISTORY_FILE` and the data directory.
+fn history_path_from(history_file: Option<OsString>, data_dir: Option<PathBuf>) -> Option<PathBuf> {
+    history_file
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| data_dir.map(|dir| dir.join("history")))
 }
 
 #[cfg(test)]
@@ -104,6 +180,7 @@ mod tests {
             ],
             index: None,
             draft: String::new(),
+            path: None,
         };
 
         assert_eq!(history.up("current"), Some("third"));
@@ -134,6 +211,7 @@ mod tests {
             entries: vec!["old".to_string()],
             index: None,
             draft: String::new(),
+            path: None,
         };
 
         assert_eq!(history.up("my draft"), Some("old"));
@@ -148,6 +226,7 @@ mod tests {
             entries: vec!["first".to_string()],
             index: None,
             draft: String::new(),
+            path: None,
         };
 
         history.add("first");
@@ -166,6 +245,7 @@ mod tests {
             entries: vec![],
             index: None,
             draft: String::new(),
+            path: None,
         };
 
         history.add("");
@@ -179,9 +259,66 @@ mod tests {
             entries: vec![],
             index: None,
             draft: String::new(),
+            path: None,
         };
 
         assert_eq!(history.up("current"), None);
         assert_eq!(history.down("current"), None);
     }
+
+    #[test]
+    fn test_search() {
+        let history = History {
+            entries: vec![
+                "s@1^do".to_string(),
+                "sfldo".to_string(),
+                "s@1^o".to_string(),
+            ],
+            index: None,
+            draft: String::new(),
+            path: None,
+        };
+
+        assert_eq!(history.search("s@1", None), Some((2, "s@1^o")));
+        assert_eq!(history.search("s@1", Some(2)), Some((0, "s@1^do")));
+        assert_eq!(history.search("s@1", Some(0)), None);
+        assert_eq!(history.search("do", Some(10)), Some((1, "sfldo")));
+        assert_eq!(history.search("x", None), None);
+    }
+
+    #[test]
+    fn data_dir_prefers_absolute_xdg_data_home() {
+        let platform = Some(PathBuf::from("/home/me/.local/share"));
+        assert_eq!(
+            data_dir_from(Some("/data".into()), platform.clone()),
+            Some(PathBuf::from("/data/t"))
+        );
+        assert_eq!(
+            data_dir_from(Some("relative".into()), platform.clone()),
+            Some(PathBuf::from("/home/me/.local/share/t"))
+        );
+        assert_eq!(
+            data_dir_from(None, platform),
+            Some(PathBuf::from("/home/me/.local/share/t"))
+        );
+        assert_eq!(data_dir_from(None, None), None);
+    }
+
+    #[test]
+    fn history_path_prefers_t_history_file() {
+        let data_dir = Some(PathBuf::from("/data/t"));
+        assert_eq!(
+            history_path_from(Some("/tmp/h".into()), data_dir.clone()),
+            Some(PathBuf::from("/tmp/h"))
+        );
+        assert_eq!(
+            history_path_from(Some("".into()), data_dir.clone()),
+            Some(PathBuf::from("/data/t/history"))
+        );
+        assert_eq!(
+            history_path_from(None, data_dir),
+            Some(PathBuf::from("/data/t/history"))
+        );
+        assert_eq!(history_path_from(None, None), None);
+    }
 }
@@ -4,7 +4,7 @@ use std::io;
 
 use crossterm::style::{Attribute, Color, SetAttribute, SetForegroundColor};
 
-use crate::value::{Level, Value};
+use crate::value::{NumberFormat, Value};
 
 /// Format JSON preview as lines with depth-based highlighting and width truncation.
 pub fn format_json_preview(
@@ -81,11 +81,16 @@ impl JsonLineCtx {
             return;
         }
         let remaining = self.max_width.saturating_sub(self.visible_len);
-        if s.len() <= remaining {
+        let len = s.chars().count();
+        if len <= remaining {
             self.buf.push_str(s);
-            self.visible_len += s.len();
+            self.visible_len += len;
         } else if remaining > 3 {
-            self.buf.push_str(&s[..remaining - 3]);
+            let end = s
+                .char_indices()
+                .nth(remaining - 3)
+                .map_or(s.len(), |(i, _)| i);
+            self.buf.push_str(&s[..end]);
             self.buf.push_str("...");
             self.visible_len = self.max_width;
             self.truncated = true;
@@ -125,6 +130,13 @@ impl JsonLineCtx {
         write!(&mut self.buf, "{}", SetForegroundColor(Color::Reset)).unwrap();
     }
 
+    fn write_bool(&mut self, b: bool) {
+        use std::fmt::Write;
+        write!(&mut self.buf, "{}", SetForegroundColor(Color::Magenta)).unwrap();
+        self.write_str(if b { "true" } else { "false" });
+        write!(&mut self.buf, "{}", SetForegroundColor(Color::Reset)).unwrap();
+    }
+
     fn write_value(&mut self, value: &Value, highlight: bool, depth: usize) {
         use std::fmt::Write;
         if self.truncated {
@@ -179,9 +191,10 @@ impl JsonLineCtx {
             }
             Value::Number(n) => {
                 write!(&mut self.buf, "{}", SetForegroundColor(Color::Cyan)).unwrap();
-                self.write_str(&n.to_string());
+                self.write_str(&NumberFormat::default().format_json(*n));
                 write!(&mut self.buf, "{}", SetForegroundColor(Color::Reset)).unwrap();
             }
+            Value::Bool(b) => self.write_bool(*b),
             Value::Array(arr) => {
                 self.write_punct("[");
                 for (i, elem) in arr.elements.iter().enumerate() {
@@ -219,7 +232,7 @@ fn format_debug_value(
     let indent_str = "  ".repeat(indent);
     match value {
         Value::Array(arr) => {
-            let level_str = level_name(arr.level);
+            let level_str = arr.level.name();
             let mut ctx = JsonLineCtx::new(max_width);
             ctx.write_str(&indent_str);
             ctx.write_level_comment(level_str);
@@ -258,7 +271,13 @@ fn format_debug_value(
         Value::Number(n) => {
             let mut ctx = JsonLineCtx::new(max_width);
             ctx.write_str(&indent_str);
-            ctx.write_number(&n.to_string());
+            ctx.write_number(&NumberFormat::default().format_json(*n));
+            lines.push(ctx.finish());
+        }
+        Value::Bool(b) => {
+            let mut ctx = JsonLineCtx::new(max_width);
+            ctx.write_str(&indent_str);
+            ctx.write_bool(*b);
             lines.push(ctx.finish());
         }
     }
@@ -278,7 +297,7 @@ fn format_debug_element(
     let indent_str = "  ".repeat(indent);
     match value {
         Value::Array(arr) => {
-            let level_str = level_name(arr.level);
+            let level_str = arr.level.name();
             let mut ctx = JsonLineCtx::new(max_width);
             ctx.write_str(&indent_str);
             ctx.write_level_comment(level_str);
@@ -323,7 +342,16 @@ fn format_debug_element(
         Value::Number(n) => {
             let mut ctx = JsonLineCtx::new(max_width);
             ctx.write_str(&indent_str);
-            ctx.write_number(&n.to_string());
+            ctx.write_number(&NumberFormat::default().format_json(*n));
+            if has_comma {
+                ctx.write_punct(",");
+            }
+            lines.push(ctx.finish());
+        }
+        Value::Bool(b) => {
+            let mut ctx = JsonLineCtx::new(max_width);
+            ctx.write_str(&indent_str);
+            ctx.write_bool(*b);
             if has_comma {
                 ctx.write_punct(",");
             }
@@ -343,53 +371,126 @@ fn write_json_punct<W: io::Write>(w: &mut W, s: &str) -> io::Result<()> {
     )
 }
 
-/// Write compact JSON with syntax highlighting (but no depth highlight).
-fn write_json_compact_highlighted<W: io::Write>(w: &mut W, value: &Value) -> io::Result<()> {
+/// Write compact JSON, optionally with syntax highlighting (but no depth highlight).
+fn write_json_compact<W: io::Write>(
+    w: &mut W,
+    value: &Value,
+    use_color: bool,
+    numbers: &NumberFormat,
+) -> io::Result<()> {
     match value {
         Value::Text(s) => {
             let escaped = serde_json::to_string(s).unwrap_or_else(|_| format!("{:?}", s));
-            write!(
-                w,
-                "{}{}{}",
-                SetForegroundColor(Color::Green),
-                escaped,
-                SetForegroundColor(Color::Reset)
-            )
+            if use_color {
+                write!(
+                    w,
+                    "{}{}{}",
+                    SetForegroundColor(Color::Green),
+                    escaped,
+                    SetForegroundColor(Color::Reset)
+                )
+            } else {
+                write!(w, "{}", escaped)
+            }
         }
         Value::Number(n) => {
-            write!(
-                w,
-                "{}{}{}",
-                SetForegroundColor(Color::Cyan),
-                n,
-                SetForegroundColor(Color::Reset)
-            )
+            if use_color {
+                write!(
+                    w,
+                    "{}{}{}",
+                    SetForegroundColor(Color::Cyan),
+                    numbers.format_json(*n),
+                    SetForegroundColor(Color::Reset)
+                )
+            } else {
+                write!(w, "{}", numbers.format_json(*n))
+            }
+        }
+        Value::Bool(b) => {
+            if use_color {
+                write!(
+                    w,
+                    "{}{}{}",
+                    SetForegroundColor(Color::Magenta),
+                    b,
+                    SetForegroundColor(Color::Reset)
+                )
+            } else {
+                write!(w, "{}", b)
+            }
         }
         Value::Array(arr) => {
-            write_json_punct(w, "[")?;
+            let punct = |w: &mut W, s: &str| {
+                if use_color {
+                    write_json_punct(w, s)
+                } else {
+                    write!(w, "{}", s)
+                }
+            };
+            punct(w, "[")?;
             for (i, elem) in arr.elements.iter().enumerate() {
                 if i > 0 {
-                    write_json_punct(w, ",")?;
+                    punct(w, ",")?;
                 }
-                write_json_compact_highlighted(w, elem)?;
+                write_json_compact(w, elem, use_color, numbers)?;
             }
-            write_json_punct(w, "]")
+            punct(w, "]")
+        }
+    }
+}
+
+/// How JSON output is laid out.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum JsonStyle {
+    /// The top-level array one element per line, inner values compact
+    #[default]
+    Lines,
+    /// Fully indented, with this many spaces per level
+    Pretty(usize),
+    /// All on one line, without spaces
+    Compact,
+}
+
+impl JsonStyle {
+    /// The command line flag that selects this style.
+    pub fn flag(self) -> String {
+        match self {
+            JsonStyle::Lines => "-j".to_string(),
+            JsonStyle::Pretty(indent) => format!("--pretty={}", indent),
+            JsonStyle::Compact => "--compact-json".to_string(),
         }
     }
 }
 
-/// Write syntax-highlighted JSON to a writer (non-interactive).
+/// Write JSON to a writer (non-interactive) in the given style.
+pub fn write_json<W: io::Write>(
+    w: &mut W,
+    value: &Value,
+    use_color: bool,
+    numbers: &NumberFormat,
+    style: JsonStyle,
+) -> io::Result<()> {
+    match style {
+        JsonStyle::Lines => write_json_highlighted(w, value, use_color, numbers),
+        JsonStyle::Pretty(indent) => write_json_pretty(w, value, use_color, numbers, indent),
+        JsonStyle::Compact => write_json_compact(w, value, use_color, numbers),
+    }
+}
+
+/// Write JSON to a writer (non-interactive): the top-level array one element
+/// per line, inner values compact.
 pub fn write_json_highlighted<W: io::Write>(
     w: &mut W,
     value: &Value,
     use_color: bool,
+    numbers: &NumberFormat,
 ) -> io::Result<()> {
     match value {
         Value::Array(arr) => {
             write!(w, "[")?;
             for (i, elem) in arr.elements.iter().enumerate() {
                 write!(w, "\n  ")?;
-                write_json_value_noninteractive(w, elem, use_color)?;
+                write_json_compact(w, elem, use_color, numbers)?;
                 if i < arr.elements.len() - 1 {
                     write!(w, ",")?;
                 }
@@ -397,45 +498,72 @@ pub fn write_json_highlighted<W: io::Write>(
             write!(w, "\n]")?;
         }
         _ => {
-            write_json_value_noninteractive(w, value, use_color)?;
+            write_json_compact(w, value, use_color, numbers)?;
         }
     }
     Ok(())
 }
 
-/// Write a JSON value for non-interactive output (compact inner arrays).
-fn write_json_value_noninteractive<W: io::Write>(
+/// Write fully indented JSON, each array element on its own line with
+/// `indent` more spaces than its array.
+pub fn write_json_pretty<W: io::Write>(
     w: &mut W,
     value: &Value,
     use_color: bool,
+    numbers: &NumberFormat,
+    indent: usize,
 ) -> io::Result<()> {
-    if use_color {
-        write_json_compact_highlighted(w, value)
-    } else {
-        let json =
-            serde_json::to_string(value).unwrap_or_else(|e| format!("\"JSON error: {}\"", e));
-        write!(w, "{}", json)
-    }
+    write_json_pretty_inner(w, value, use_color, numbers, indent, 0)
 }
 
-fn level_name(level: Level) -> &'static str {
-    match level {
-        Level::File => "file",
-        Level::Line => "line",
-        Level::Word => "word",
-        Level::Char => "char",
+fn write_json_pretty_inner<W: io::Write>(
+    w: &mut W,
+    value: &Value,
+    use_color: bool,
+    numbers: &NumberFormat,
+    indent: usize,
+    depth: usize,
+) -> io::Result<()> {
+    let Value::Array(arr) = value else {
+        return write_json_compact(w, value, use_color, numbers);
+    };
+    let punct = |w: &mut W, s: &str| {
+        if use_color {
+            write_json_punct(w, s)
+        } else {
+            write!(w, "{}", s)
+        }
+    };
+    if arr.is_empty() {
+        return punct(w, "[]");
     }
+    punct(w, "[")?;
+    for (i, elem) in arr.elements.iter().enumerate() {
+        if i > 0 {
+            punct(w, ",")?;
+        }
+        write!(w, "\n{}", " ".repeat(indent * (depth + 1)))?;
+        write_json_pretty_inner(w, elem, use_color, numbers, indent, depth + 1)?;
+    }
+    write!(w, "\n{}", " ".repeat(indent * depth))?;
+    punct(w, "]")
 }
 
 /// Write debug JSON output showing semantic level before each array.
-pub fn write_json_debug<W: io::Write>(w: &mut W, value: &Value, use_color: bool) -> io::Result<()> {
-    write_json_debug_inner(w, value, use_color, 0)
+pub fn write_json_debug<W: io::Write>(
+    w: &mut W,
+    value: &Value,
+    use_color: bool,
+    numbers: &NumberFormat,
+) -> io::Result<()> {
+    write_json_debug_inner(w, value, use_color, numbers, 0)
 }
 
 fn write_json_debug_inner<W: io::Write>(
     w: &mut W,
     value: &Value,
     use_color: bool,
+    numbers: &NumberFormat,
     indent: usize,
 ) -> io::Result<()> {
     let indent_str = "  ".repeat(indent);
@@ -446,17 +574,17 @@ fn write_json_debug_inner<W: io::Write>(
                     w,
                     "{}/*{}*/{}",
                     SetForegroundColor(Color::Yellow),
-                    level_name(arr.level),
+                    arr.level.name(),
                     SetForegroundColor(Color::Reset)
                 )?;
             } else {
-                write!(w, "/*{}*/", level_name(arr.level))?;
+                write!(w, "/*{}*/", arr.level.name())?;
             }
             write!(w, " [")?;
             for (i, elem) in arr.elements.iter().enumerate() {
                 writeln!(w)?;
                 write!(w, "{}  ", indent_str)?;
-                write_json_debug_inner(w, elem, use_color, indent + 1)?;
+                write_json_debug_inner(w, elem, use_color, numbers, indent + 1)?;
                 if i < arr.elements.len() - 1 {
                     write!(w, ",")?;
                 }
@@ -487,13 +615,178 @@ fn write_json_debug_inner<W: io::Write>(
                     w,
                     "{}{}{}",
                     SetForegroundColor(Color::Cyan),
-                    n,
+                    numbers.format_json(*n),
                     SetForegroundColor(Color::Reset)
                 )?;
             } else {
-                write!(w, "{}", n)?;
+                write!(w, "{}", numbers.format_json(*n))?;
+            }
+        }
+        Value::Bool(b) => {
+            if use_color {
+                write!(
+                    w,
+                    "{}{}{}",
+                    SetForegroundColor(Color::Magenta),
+                    b,
+                    SetForegroundColor(Color::Reset)
+                )?;
+            } else {
+                write!(w, "{}", b)?;
             }
         }
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value::{Array, Level};
+
+    fn json_output(value: &Value) -> String {
+        let mut buf = Vec::new();
+        write_json_highlighted(&mut buf, value, false, &NumberFormat::default()).unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+
+    #[test]
+    fn json_and_text_numbers_agree() {
+        for n in [3.0, 0.1 * 30.0, 1.0 / 3.0, 1e21, 1e20, -0.0, 2.5e-8] {
+            let value = Value::Number(n);
+            assert_eq!(json_output(&value), value.to_string(), "{}", n);
+        }
+    }
+
+    #[test]
+    fn json_and_text_numbers_agree_in_arrays() {
+        let value = Value::Array(Array::from((
+            vec![Value::Number(42.0), Value::Number(0.1 * 30.0)],
+            Level::Word,
+        )));
+        assert_eq!(json_output(&value), "[\n  42,\n  3\n]");
+        assert_eq!(value.to_string(), "42 3");
+    }
+
+    #[test]
+    fn json_writes_null_for_non_finite_numbers() {
+        let value = Value::Array(Array::from((
+            vec![
+                Value::Number(f64::INFINITY),
+                Value::Number(f64::NEG_INFINITY),
+                Value::Number(f64::NAN),
+            ],
+            Level::Word,
+        )));
+        assert_eq!(json_output(&value), "[\n  null,\n  null,\n  null\n]");
+        assert_eq!(json_output(&Value::Number(f64::NAN)), "null");
+        let mut buf = Vec::new();
+        let numbers = NumberFormat {
+            precision: Some(2),
+            ..NumberFormat::default()
+        };
+        write_json_highlighted(&mut buf, &Value::Number(f64::INFINITY), false, &numbers).unwrap();
+        assert_eq!(String::from_utf8(buf).unwrap(), "null");
+    }
+
+    fn pretty_output(value: &Value, indent: usize) -> String {
+        let mut buf = Vec::new();
+        write_json_pretty(&mut buf, value, false, &NumberFormat::default(), indent).unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+
+    #[test]
+    fn pretty_json_indents_nested_arrays() {
+        let row = |words: &[&str]| {
+            Value::Array(Array::from((
+                words.iter().map(|w| Value::Text(w.to_string())).collect(),
+                Level::Word,
+            )))
+        };
+        let value = Value::Array(Array::from((
+            vec![row(&["a", "b"]), row(&[]), Value::Number(0.1 * 30.0)],
+            Level::Line,
+        )));
+        assert_eq!(
+            pretty_output(&value, 2),
+            "[\n  [\n    \"a\",\n    \"b\"\n  ],\n  [],\n  3\n]"
+        );
+        assert_eq!(
+            pretty_output(&value, 4),
+            "[\n    [\n        \"a\",\n        \"b\"\n    ],\n    [],\n    3\n]"
+        );
+        assert_eq!(pretty_output(&Value::Number(1.5), 2), "1.5");
+    }
+
+    #[test]
+    fn pretty_json_writes_null_for_non_finite_numbers() {
+        let value = Value::Array(Array::from((
+            vec![
+                Value::Array(Array::from((
+                    vec![Value::Number(f64::INFINITY)],
+                    Level::Word,
+                ))),
+                Value::Number(f64::NAN),
+            ],
+            Level::Line,
+        )));
+        assert_eq!(
+            pretty_output(&value, 2),
+            "[\n  [\n    null\n  ],\n  null\n]"
+        );
+        assert_eq!(
+            pretty_output(&value, 2),
+            serde_json::to_string_pretty(&value).unwrap()
+        );
+        assert_eq!(pretty_output(&Value::Number(f64::NEG_INFINITY), 2), "null");
+    }
+
+    #[test]
+    fn compact_json_matches_serde() {
+        let value = Value::Array(Array::from((
+            vec![
+                Value::Text("a b".to_string()),
+                Value::Array(Array::from((vec![Value::Number(2.5)], Level::Word))),
+            ],
+            Level::Line,
+        )));
+        let mut buf = Vec::new();
+        let numbers = NumberFormat::default();
+        write_json(&mut buf, &value, false, &numbers, JsonStyle::Compact).unwrap();
+        let output = String::from_utf8(buf).unwrap();
+        assert_eq!(output, r#"["a b",[2.5]]"#);
+        assert_eq!(output, serde_json::to_string(&value).unwrap());
+    }
+
+    #[test]
+    fn compact_json_writes_null_for_non_finite_numbers() {
+        let value = Value::Array(Array::from((
+            vec![
+                Value::Number(f64::INFINITY),
+                Value::Array(Array::from((vec![Value::Number(f64::NAN)], Level::Word))),
+            ],
+            Level::Line,
+        )));
+        let mut buf = Vec::new();
+        let numbers = NumberFormat::default();
+        write_json(&mut buf, &value, false, &numbers, JsonStyle::Compact).unwrap();
+        let output = String::from_utf8(buf).unwrap();
+        assert_eq!(output, "[null,[null]]");
+        assert_eq!(output, serde_json::to_string(&value).unwrap());
+    }
+
+    #[test]
+    fn pretty_json_matches_serde() {
+        let value = Value::Array(Array::from((
+            vec![
+                Value::Text("x \"y\"".to_string()),
+                Value::Array(Array::from((vec![Value::Number(-2.0)], Level::Word))),
+            ],
+            Level::Line,
+        )));
+        assert_eq!(
+            pretty_output(&value, 2),
+            serde_json::to_string_pretty(&value).unwrap()
+        );
+    }
+}
@@ -0,0 +1,151 @@
+//! Programmes saved by name for recall in interactive mode.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use crossterm::{
+    execute,
+    style::{Attribute, Color, Print, ResetColor, SetAttribute, SetForegroundColor},
+};
+
+use super::{history, text};
+
+pub struct Library {
+    /// Programmes by name
+    entries: BTreeMap<String, String>,
+}
+
+impl Library {
+    pub fn load() -> Self {
+        let entries = library_path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+
+        Self { entries }
+    }
+
+    pub fn save(&self) {
+        let Some(path) = library_path() else { return };
+        if let Some(parent) = path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        if let Ok(json) = serde_json::to_string_pretty(&self.entries) {
+            let _ = fs::write(&path, json + "\n");
+        }
+    }
+
+    /// Save a programme under a name, replacing any saved under it before.
+    pub fn insert(&mut self, name: &str, programme: &str) {
+        let (name, programme) = (name.trim(), programme.trim());
+        if name.is_empty() || programme.is_empty() {
+            return;
+        }
+        self.entries.insert(name.to_string(), programme.to_string());
+    }
+
+    pub fn remove(&mut self, name: &str) {
+        self.entries.remove(name);
+    }
+
+    /// Saved `(name, programme)` pairs whose name contains `query`, by name.
+    pub fn matching(&self, query: &str) -> Vec<(&str, &str)> {
+        self.entries
+            .iter()
+            .filter(|(name, _)| name.contains(query))
+            .map(|(name, programme)| (name.as_str(), programme.as_str()))
+            .collect()
+    }
+}
+
+/// A prompt for a name, shown in place of the usual one.
+pub enum Prompt {
+    /// Naming the current programme to save it
+    Save { name: String },
+    /// Picking a saved programme by name
+    Recall { query: String, selected: usize },
+}
+
+/// Draw the saved programmes matching a recall query below the prompt,
+/// highlighting the selected one. Returns the number of lines drawn.
+pub fn draw_matches(
+    stdout: &mut io::Stdout,
+    matches: &[(&str, &str)],
+    selected: usize,
+    max_lines: usize,
+    width: usize,
+) -> io::Result<usize> {
+    if matches.is_empty() {
+        execute!(
+            stdout,
+            Print("\r\n"),
+            SetAttribute(Attribute::Dim),
+            Print("no saved programmes match"),
+            SetAttribute(Attribute::NormalIntensity)
+        )?;
+        return Ok(1);
+    }
+
+    let name_width = matches.iter().map(|(name, _)| name.chars().count()).max();
+    let name_width = name_width.unwrap_or(0);
+    // Keep the selected programme in view
+    let skip = (selected + 1).saturating_sub(max_lines);
+    let mut lines_below = 0;
+    for (i, (name, programme)) in matches.iter().enumerate().skip(skip).take(max_lines) {
+        let line = format!("  {:<name_width$}  {}", name, programme);
+        let line = text::truncate_line(&line, width);
+        execute!(stdout, Print("\r\n"))?;
+        if i == selected {
+            execute!(
+                stdout,
+                SetAttribute(Attribute::Reverse),
+                Print(&line),
+                SetAttribute(Attribute::NoReverse)
+            )?;
+        } else {
+            execute!(
+                stdout,
+                SetForegroundColor(Color::Cyan),
+                Print(&line),
+                ResetColor
+            )?;
+        }
+        lines_below += 1;
+    }
+    Ok(lines_below)
+}
+
+fn library_path() -> Option<PathBuf> {
+    history::data_dir().map(|dir| dir.join("library.json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_and_match() {
+        let mut library = Library {
+            entries: BTreeMap::new(),
+        };
+        library.insert("top-words", "sfldo:20");
+        library.insert("status", "s@8^do");
+        library.insert("words", "sf");
+        library.insert("empty", "  ");
+
+        assert_eq!(
+            library.matching("word"),
+            vec![("top-words", "sfldo:20"), ("words", "sf")]
+        );
+        assert_eq!(library.matching("").len(), 3);
+
+        library.insert("words", "sfd");
+        library.remove("status");
+        assert_eq!(
+            library.matching(""),
+            vec![("top-words", "sfldo:20"), ("words", "sfd")]
+        );
+    }
+}
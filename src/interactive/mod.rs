@@ -1,9 +1,13 @@
 //! Interactive mode for live previewing programmes.
 
+mod complete;
 mod help;
+mod highlight;
 mod history;
 mod json;
+mod library;
 mod text;
+mod undo;
 
 use std::io::{self, Write};
 use std::time::Instant;
@@ -11,7 +15,9 @@ use std::time::Instant;
 use anyhow::{Context, Result};
 use crossterm::{
     cursor,
-    event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
+    event::{
+        self, DisableBracketedPaste, EnableBracketedPaste, Event, KeyCode, KeyEvent, KeyModifiers,
+    },
     execute,
     style::{Attribute, Color, Print, ResetColor, SetAttribute, SetForegroundColor},
     terminal::{self, ClearType},
@@ -23,7 +29,8 @@ use crate::parser;
 use crate::value::{Array, Value};
 
 pub use help::{help_text, write_footer, write_help_text, write_intro, write_options};
-pub use json::{write_json_debug, write_json_highlighted};
+pub use json::{JsonStyle, write_json, write_json_debug};
+pub use text::write_trace;
 
 /// Batch sizes for adaptive preview execution.
 const PREVIEW_BATCH_SIZES: &[usize] = &[100, 500, 2000, usize::MAX];
@@ -43,6 +50,43 @@ pub struct InteractiveMode {
     history: history::History,
     /// Compile configuration for split/join modes.
     config: CompileConfig,
+    /// Tab completion of operators.
+    completer: complete::Completer,
+    /// Reverse history search, while Ctrl+R is active.
+    search: Option<history::Search>,
+    /// Edits to the programme, for undo and redo.
+    undo: undo::UndoStack,
+    /// Programmes saved by name.
+    library: library::Library,
+    /// Prompt for saving or recalling a programme, while one is open.
+    library_prompt: Option<library::Prompt>,
+}
+
+/// What the preview produced, for the status on the prompt line.
+#[derive(Clone, Copy)]
+struct PreviewStats {
+    /// Elements in the output
+    elements: usize,
+    /// Input lines the preview ran on, if fewer than all of them
+    partial: Option<usize>,
+}
+
+impl PreviewStats {
+    /// The status entries: how many input lines the preview ran on, if not
+    /// all `input_lines` of them, then the size of the output.
+    fn status(&self, input_lines: usize) -> Vec<String> {
+        let mut status = Vec::new();
+        if let Some(lines) = self.partial {
+            status.push(format!("(preview: {} of {} lines)", lines, input_lines));
+        }
+        let noun = if self.elements == 1 {
+            "element"
+        } else {
+            "elements"
+        };
+        status.push(format!("{} {}", self.elements, noun));
+        status
+    }
 }
 
 struct CachedOutput {
@@ -56,13 +100,16 @@ struct CachedOutput {
     depth: usize,
     /// Error info if any: (offset, message)
     error_info: Option<(usize, String)>,
+    stats: PreviewStats,
 }
 
 impl InteractiveMode {
+    /// With `save_history` false, history is neither loaded nor saved.
     pub fn new_with_config(
         input: Array,
         json_output: bool,
         debug_output: bool,
+        save_history: bool,
         config: CompileConfig,
     ) -> Self {
         Self {
@@ -74,8 +121,17 @@ impl InteractiveMode {
             show_help: false,
             prompt_row: 0,
             cached_output: None,
-            history: history::History::load(),
+            history: if save_history {
+                history::History::load()
+            } else {
+                history::History::disabled()
+            },
             config,
+            completer: complete::Completer::default(),
+            search: None,
+            undo: undo::UndoStack::default(),
+            library: library::Library::load(),
+            library_prompt: None,
         }
     }
 
@@ -85,7 +141,11 @@ impl InteractiveMode {
         // Query cursor position after enabling raw mode - some terminals require
         // raw mode for the position query to work correctly
         self.prompt_row = cursor::position().map(|(_, row)| row).unwrap_or(0);
+        // Not every terminal supports bracketed paste; without it a paste
+        // arrives as keys
+        let _ = execute!(io::stdout(), EnableBracketedPaste);
         let result = self.event_loop();
+        let _ = execute!(io::stdout(), DisableBracketedPaste);
         terminal::disable_raw_mode().context("failed to disable raw mode")?;
         result
     }
@@ -118,6 +178,11 @@ impl InteractiveMode {
                     }
                     self.draw(&mut stdout, Some(start), true)?;
                 }
+                Event::Paste(text) => {
+                    let start = Instant::now();
+                    self.paste(&text);
+                    self.draw(&mut stdout, Some(start), true)?;
+                }
                 Event::Resize(_, height) => {
                     // Invalidate cache since terminal dimensions changed
                     self.cached_output = None;
@@ -154,17 +219,79 @@ impl InteractiveMode {
         lines_below.max(help::help_line_count())
     }
 
-    fn truncate_line(line: &str, max_width: usize) -> String {
-        if line.len() <= max_width {
-            line.to_string()
-        } else if max_width > 3 {
-            format!("{}...", &line[..max_width - 3])
-        } else {
-            line[..max_width].to_string()
-        }
+    /// Byte index of the char boundary before the cursor.
+    fn prev_char_boundary(&self) -> usize {
+        self.programme[..self.cursor]
+            .chars()
+            .next_back()
+            .map_or(0, |c| self.cursor - c.len_utf8())
+    }
+
+    /// Byte index of the char boundary after the cursor.
+    fn next_char_boundary(&self) -> usize {
+        self.programme[self.cursor..]
+            .chars()
+            .next()
+            .map_or(self.cursor, |c| self.cursor + c.len_utf8())
+    }
+
+    /// Byte index of the start of the word before the cursor.
+    fn prev_word_boundary(&self) -> usize {
+        highlight::word_spans(&self.programme)
+            .iter()
+            .rev()
+            .map(|span| span.start)
+            .find(|&start| start < self.cursor)
+            .unwrap_or(0)
+    }
+
+    /// Byte index of the end of the word after the cursor.
+    fn next_word_boundary(&self) -> usize {
+        highlight::word_spans(&self.programme)
+            .iter()
+            .map(|span| span.end)
+            .find(|&end| end > self.cursor)
+            .unwrap_or(self.programme.len())
     }
 
     fn handle_key(&mut self, key: KeyEvent) -> KeyAction {
+        // Ctrl+Z / Ctrl+Y: undo / redo
+        let current = || (self.programme.clone(), self.cursor);
+        let restored = match (key.code, key.modifiers) {
+            (KeyCode::Char('z'), KeyModifiers::CONTROL) => self.undo.undo(current()),
+            (KeyCode::Char('y'), KeyModifiers::CONTROL) => self.undo.redo(current()),
+            _ => return self.edit_and_record(key),
+        };
+        if let Some((programme, cursor)) = restored {
+            // The output cache is keyed by the programme text, so it
+            // refreshes itself for the restored programme
+            self.programme = programme;
+            self.cursor = cursor;
+            self.search = None;
+            self.library_prompt = None;
+            self.show_help = false;
+            self.history.reset();
+        }
+        KeyAction::Continue
+    }
+
+    /// Handle a key, remembering the programme from before it for undo if
+    /// the key changes it.
+    fn edit_and_record(&mut self, key: KeyEvent) -> KeyAction {
+        // A history search is a single edit, from the programme before it
+        // to the match accepted
+        let before = match &self.search {
+            Some(search) => (search.draft.clone(), search.draft_cursor),
+            None => (self.programme.clone(), self.cursor),
+        };
+        let action = self.edit(key);
+        if self.search.is_none() && self.programme != before.0 {
+            self.undo.record(before);
+        }
+        action
+    }
+
+    fn edit(&mut self, key: KeyEvent) -> KeyAction {
         // Esc dismisses help, other keys pass through
         if self.show_help {
             if matches!(key.code, KeyCode::Esc) {
@@ -174,6 +301,18 @@ impl InteractiveMode {
             self.show_help = false;
         }
 
+        if key.code != KeyCode::Tab {
+            self.completer.reset();
+        }
+
+        if let Some(action) = self.handle_search_key(key) {
+            return action;
+        }
+
+        if let Some(action) = self.handle_library_key(key) {
+            return action;
+        }
+
         match (key.code, key.modifiers) {
             // Ctrl+C or Escape: cancel
             (KeyCode::Char('c'), KeyModifiers::CONTROL) | (KeyCode::Esc, _) => KeyAction::Cancel,
@@ -193,6 +332,37 @@ impl InteractiveMode {
                 KeyAction::Continue
             }
 
+            // Ctrl+R: search history
+            (KeyCode::Char('r'), KeyModifiers::CONTROL) => {
+                self.search = Some(history::Search::new(&self.programme, self.cursor));
+                KeyAction::Continue
+            }
+
+            // Ctrl+S: save the programme under a name
+            (KeyCode::Char('s'), KeyModifiers::CONTROL) => {
+                if !self.programme.trim().is_empty() {
+                    self.library_prompt = Some(library::Prompt::Save {
+                        name: String::new(),
+                    });
+                }
+                KeyAction::Continue
+            }
+
+            // Ctrl+O: recall a saved programme
+            (KeyCode::Char('o'), KeyModifiers::CONTROL) => {
+                self.library_prompt = Some(library::Prompt::Recall {
+                    query: String::new(),
+                    selected: 0,
+                });
+                KeyAction::Continue
+            }
+
+            // Ctrl+B: toggle debug output (semantic levels)
+            (KeyCode::Char('b'), KeyModifiers::CONTROL) => {
+                self.debug_output = !self.debug_output;
+                KeyAction::Continue
+            }
+
             // Ctrl+H: show help
             (KeyCode::Char('h'), KeyModifiers::CONTROL) => {
                 self.show_help = true;
@@ -205,8 +375,8 @@ impl InteractiveMode {
             // Backspace: delete char before cursor
             (KeyCode::Backspace, _) => {
                 if self.cursor > 0 {
-                    self.programme.remove(self.cursor - 1);
-                    self.cursor -= 1;
+                    self.cursor = self.prev_char_boundary();
+                    self.programme.remove(self.cursor);
                     self.history.reset();
                 }
                 KeyAction::Continue
@@ -221,19 +391,48 @@ impl InteractiveMode {
                 KeyAction::Continue
             }
 
+            // Ctrl+W: delete word before cursor
+            (KeyCode::Char('w'), KeyModifiers::CONTROL) => {
+                let start = self.prev_word_boundary();
+                self.programme.replace_range(start..self.cursor, "");
+                self.cursor = start;
+                self.history.reset();
+                KeyAction::Continue
+            }
+
+            // Ctrl+U: delete to start of line
+            (KeyCode::Char('u'), KeyModifiers::CONTROL) => {
+                self.programme.replace_range(..self.cursor, "");
+                self.cursor = 0;
+                self.history.reset();
+                KeyAction::Continue
+            }
+
+            // Ctrl+Left or Alt+Left: move cursor to start of word
+            (KeyCode::Left, modifiers)
+                if modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+            {
+                self.cursor = self.prev_word_boundary();
+                KeyAction::Continue
+            }
+
+            // Ctrl+Right or Alt+Right: move cursor to end of word
+            (KeyCode::Right, modifiers)
+                if modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+            {
+                self.cursor = self.next_word_boundary();
+                KeyAction::Continue
+            }
+
             // Left arrow: move cursor left
             (KeyCode::Left, _) => {
-                if self.cursor > 0 {
-                    self.cursor -= 1;
-                }
+                self.cursor = self.prev_char_boundary();
                 KeyAction::Continue
             }
 
             // Right arrow: move cursor right
             (KeyCode::Right, _) => {
-                if self.cursor < self.programme.len() {
-                    self.cursor += 1;
-                }
+                self.cursor = self.next_char_boundary();
                 KeyAction::Continue
             }
 
@@ -255,22 +454,30 @@ impl InteractiveMode {
                 KeyAction::Continue
             }
 
-            // Home: move cursor to start
-            (KeyCode::Home, _) => {
+            // Home or Ctrl+A: move cursor to start
+            (KeyCode::Home, _) | (KeyCode::Char('a'), KeyModifiers::CONTROL) => {
                 self.cursor = 0;
                 KeyAction::Continue
             }
 
-            // End: move cursor to end
-            (KeyCode::End, _) => {
+            // End or Ctrl+E: move cursor to end
+            (KeyCode::End, _) | (KeyCode::Char('e'), KeyModifiers::CONTROL) => {
                 self.cursor = self.programme.len();
                 KeyAction::Continue
             }
 
+            // Tab: complete the operator before the cursor
+            (KeyCode::Tab, _) => {
+                self.completer
+                    .complete(&mut self.programme, &mut self.cursor);
+                self.history.reset();
+                KeyAction::Continue
+            }
+
             // Regular character: insert at cursor
             (KeyCode::Char(c), KeyModifiers::NONE | KeyModifiers::SHIFT) => {
                 self.programme.insert(self.cursor, c);
-                self.cursor += 1;
+                self.cursor += c.len_utf8();
                 self.history.reset();
                 KeyAction::Continue
             }
@@ -279,6 +486,186 @@ impl InteractiveMode {
         }
     }
 
+    /// Handle a key during a history search. Returns None if there is no
+    /// search, or if the key ends it by accepting the match and should then
+    /// be handled as usual.
+    fn handle_search_key(&mut self, key: KeyEvent) -> Option<KeyAction> {
+        let search = self.search.as_mut()?;
+        match (key.code, key.modifiers) {
+            // Escape or Ctrl+G: cancel, restoring the programme from before
+            (KeyCode::Esc, _) | (KeyCode::Char('g'), KeyModifiers::CONTROL) => {
+                let search = self.search.take()?;
+                self.programme = search.draft;
+                self.cursor = search.draft_cursor;
+            }
+
+            // Ctrl+R again: the next older match
+            (KeyCode::Char('r'), KeyModifiers::CONTROL) => {
+                let before = search.matched;
+                self.search_history(before);
+            }
+
+            // Backspace: shorten the query and search again from the newest
+            (KeyCode::Backspace, _) => {
+                search.query.pop();
+                self.search_history(None);
+            }
+
+            // Regular character: extend the query, keeping the current match
+            // if it still matches
+            (KeyCode::Char(c), KeyModifiers::NONE | KeyModifiers::SHIFT) => {
+                search.query.push(c);
+                let before = search.matched.map(|i| i + 1);
+                self.search_history(before);
+            }
+
+            _ => {
+                self.search = None;
+                self.history.reset();
+                return None;
+            }
+        }
+        Some(KeyAction::Continue)
+    }
+
+    /// Insert pasted text at the cursor as one edit. Line breaks are dropped
+    /// rather than committing, so a programme copied with its newline (or
+    /// wrapped over lines) pastes whole. While searching or naming, the text
+    /// is typed into the query or name instead.
+    fn paste(&mut self, text: &str) {
+        let text: String = text.chars().filter(|c| !c.is_control()).collect();
+        if self.search.is_some() || self.library_prompt.is_some() {
+            for c in text.chars() {
+                self.handle_key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+            }
+            return;
+        }
+        if text.is_empty() {
+            return;
+        }
+        self.show_help = false;
+        self.completer.reset();
+        self.undo.record((self.programme.clone(), self.cursor));
+        self.programme.insert_str(self.cursor, &text);
+        self.cursor += text.len();
+        self.history.reset();
+    }
+
+    /// Handle a key while saving or recalling a programme. Returns None if
+    /// neither prompt is open.
+    fn handle_library_key(&mut self, key: KeyEvent) -> Option<KeyAction> {
+        let prompt = self.library_prompt.as_mut()?;
+        if matches!(
+            (key.code, key.modifiers),
+            (KeyCode::Esc, _) | (KeyCode::Char('c'), KeyModifiers::CONTROL)
+        ) {
+            self.library_prompt = None;
+            return Some(KeyAction::Continue);
+        }
+
+        match prompt {
+            library::Prompt::Save { name } => match (key.code, key.modifiers) {
+                (KeyCode::Enter, _) => {
+                    self.library.insert(name, &self.programme);
+                    self.library.save();
+                    self.library_prompt = None;
+                }
+                (KeyCode::Backspace, _) => {
+                    name.pop();
+                }
+                (KeyCode::Char(c), KeyModifiers::NONE | KeyModifiers::SHIFT) => name.push(c),
+                _ => {}
+            },
+            library::Prompt::Recall { query, selected } => {
+                let matches = self.library.matching(query);
+                match (key.code, key.modifiers) {
+                    (KeyCode::Enter, _) => {
+                        if let Some((_, programme)) = matches.get(*selected) {
+                            self.programme = programme.to_string();
+                            self.cursor = self.programme.len();
+                            self.history.reset();
+                        }
+                        self.library_prompt = None;
+                    }
+                    (KeyCode::Up, _) => *selected = selected.saturating_sub(1),
+                    (KeyCode::Down, _) if *selected + 1 < matches.len() => *selected += 1,
+                    // Ctrl+D: delete the selected programme from the library
+                    (KeyCode::Char('d'), KeyModifiers::CONTROL) => {
+                        if let Some((name, _)) = matches.get(*selected) {
+                            let name = name.to_string();
+                            let last = matches.len() - 1;
+                            self.library.remove(&name);
+                            self.library.save();
+                            *selected = (*selected).min(last.saturating_sub(1));
+                        }
+                    }
+                    (KeyCode::Backspace, _) => {
+                        query.pop();
+                        *selected = 0;
+                    }
+                    (KeyCode::Char(c), KeyModifiers::NONE | KeyModifiers::SHIFT) => {
+                        query.push(c);
+                        *selected = 0;
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Some(KeyAction::Continue)
+    }
+
+    /// Show the newest history entry before `before` that matches the
+    /// search query, or mark the search as failed if there is none.
+    fn search_history(&mut self, before: Option<usize>) {
+        let Some(search) = &mut self.search else {
+            return;
+        };
+        match self.history.search(&search.query, before) {
+            Some((index, entry)) => {
+                search.matched = Some(index);
+                search.failed = false;
+                self.programme = entry.to_string();
+                self.cursor = self.programme.len();
+            }
+            None => search.failed = true,
+        }
+    }
+
+    /// The text drawn before the programme on the prompt line.
+    fn prompt_prefix(&self) -> String {
+        match (&self.library_prompt, &self.search) {
+            (Some(library::Prompt::Save { name }), _) => format!("(save as '{}') t> ", name),
+            (Some(library::Prompt::Recall { query, .. }), _) => {
+                format!("(recall '{}') t> ", query)
+            }
+            (None, Some(search)) if search.failed => {
+                format!("(no match '{}') t> ", search.query)
+            }
+            (None, Some(search)) => format!("(search '{}') t> ", search.query),
+            (None, None) => "t> ".to_string(),
+        }
+    }
+
+    fn recalling(&self) -> bool {
+        matches!(self.library_prompt, Some(library::Prompt::Recall { .. }))
+    }
+
+    /// The programme drawn on the prompt line and the cursor within it: the
+    /// selected saved programme while recalling, else the one being edited.
+    fn shown_programme(&self) -> (&str, usize) {
+        match &self.library_prompt {
+            Some(library::Prompt::Recall { query, selected }) => {
+                let programme = self
+                    .library
+                    .matching(query)
+                    .get(*selected)
+                    .map_or("", |(_, programme)| programme);
+                (programme, programme.len())
+            }
+            _ => (&self.programme, self.cursor),
+        }
+    }
+
     fn draw(
         &mut self,
         stdout: &mut io::Stdout,
@@ -290,7 +677,7 @@ impl InteractiveMode {
         let max_lines = self.available_preview_lines();
 
         // Get cached or compute formatted output before clearing screen to reduce flicker
-        let output = if self.show_help {
+        let output = if self.show_help || self.recalling() {
             None
         } else {
             Some(self.get_formatted_output(max_lines, term_width))
@@ -304,24 +691,43 @@ impl InteractiveMode {
         )?;
 
         // Draw prompt with help hint on the right (timing added at end)
-        let prompt = format!("t> {}", self.programme);
         let help_hint = "^H Help";
-        execute!(stdout, Print(&prompt),)?;
+        let prefix = self.prompt_prefix();
+        let prefix_width = prefix.chars().count();
+        let (programme, cursor) = self.shown_programme();
+        // The cursor is a byte index, the column counts chars
+        let cursor_col = prefix_width + programme[..cursor].chars().count();
+        let prompt_width = prefix_width + programme.chars().count();
+        execute!(stdout, Print(&prefix))?;
+        for (kind, token) in highlight::tokenize(programme) {
+            match kind.color() {
+                Some(color) => {
+                    execute!(stdout, SetForegroundColor(color), Print(token), ResetColor)?
+                }
+                None => execute!(stdout, Print(token))?,
+            }
+        }
 
         // Count lines below prompt
         let mut lines_below = 0;
+        let mut preview_stats = None;
 
         if self.show_help {
             lines_below = help::draw_help(stdout, max_lines)?;
+        } else if let Some(library::Prompt::Recall { query, selected }) = &self.library_prompt {
+            let matches = self.library.matching(query);
+            lines_below =
+                library::draw_matches(stdout, &matches, *selected, max_lines, term_width)?;
         } else {
-            let (lines, depth, error_info) = output.unwrap();
+            let (lines, depth, error_info, stats) = output.unwrap();
+            preview_stats = Some((stats, depth));
 
             // Show error first if present
             if let Some((offset, message)) = error_info {
-                let caret_pos = 3 + offset; // "t> " is 3 chars
+                let caret_pos = prefix_width + offset;
                 let caret_line = format!("{:>width$}", "^", width = caret_pos + 1);
                 let error_line = format!("{} {}", caret_line, message);
-                let truncated = Self::truncate_line(&error_line, term_width);
+                let truncated = text::truncate_line(&error_line, term_width);
                 execute!(
                     stdout,
                     Print("\r\n"),
@@ -371,13 +777,22 @@ impl InteractiveMode {
             execute!(stdout, cursor::MoveUp(lines_below as u16))?;
         }
 
-        // Draw timing and help hint on the right side of prompt line
-        let timing = start.map(|s| format!("{:.1}ms", s.elapsed().as_secs_f64() * 1000.0));
-        let right_text = match &timing {
-            Some(t) => format!("{} {}", t, help_hint),
-            None => help_hint.to_string(),
-        };
-        let right_col = term_width.saturating_sub(right_text.len()) as u16;
+        // Draw output size, timing, depth and help hint on the right side of
+        // prompt line, dropping from the left whatever doesn't fit beside the
+        // prompt
+        let mut status = Vec::new();
+        if let Some((stats, _)) = preview_stats {
+            status.extend(stats.status(self.input.len()));
+        }
+        if let Some(start) = start {
+            status.push(format!("{:.1}ms", start.elapsed().as_secs_f64() * 1000.0));
+        }
+        if let Some((_, depth)) = preview_stats {
+            status.extend(depth_status(depth));
+        }
+        status.push(help_hint.to_string());
+        let right_text = fit_status(status, term_width.saturating_sub(prompt_width + 1));
+        let right_col = term_width.saturating_sub(right_text.chars().count()) as u16;
         execute!(
             stdout,
             cursor::MoveToColumn(right_col),
@@ -386,7 +801,6 @@ impl InteractiveMode {
             SetAttribute(Attribute::NormalIntensity)
         )?;
 
-        let cursor_col = 3 + self.cursor; // "t> " is 3 chars
         execute!(stdout, cursor::MoveToColumn(cursor_col as u16))?;
 
         stdout.flush()?;
@@ -394,12 +808,12 @@ impl InteractiveMode {
     }
 
     /// Get formatted output lines, using cache if programme hasn't changed.
-    /// Returns (lines, depth, error_info).
+    /// Returns (lines, depth, error_info, stats).
     fn get_formatted_output(
         &mut self,
         max_lines: usize,
         term_width: usize,
-    ) -> (Vec<String>, usize, Option<(usize, String)>) {
+    ) -> (Vec<String>, usize, Option<(usize, String)>, PreviewStats) {
         // Check if we can use cached result
         if let Some(ref cached) = self.cached_output
             && cached.programme == self.programme
@@ -411,12 +825,20 @@ impl InteractiveMode {
                 cached.lines.clone(),
                 cached.depth,
                 cached.error_info.clone(),
+                cached.stats,
             );
         }
 
         // Compute fresh result
-        let (value, depth, error) = self.try_execute(max_lines);
+        let (value, depth, error, partial) = self.try_execute(max_lines);
         let error_info = error.as_ref().map(parse_error_info);
+        let stats = PreviewStats {
+            elements: match &value {
+                Value::Array(arr) => arr.len(),
+                _ => 1,
+            },
+            partial,
+        };
 
         let display_lines = if error_info.is_some() {
             max_lines.saturating_sub(1)
@@ -442,13 +864,18 @@ impl InteractiveMode {
             lines: lines.clone(),
             depth,
             error_info: error_info.clone(),
+            stats,
         });
 
-        (lines, depth, error_info)
+        (lines, depth, error_info, stats)
     }
 
-    /// Try to execute the programme. Returns (value, depth, optional error).
-    fn try_execute(&self, needed_lines: usize) -> (Value, usize, Option<anyhow::Error>) {
+    /// Try to execute the programme. Returns (value, depth, optional error,
+    /// input lines run on if fewer than all of them).
+    fn try_execute(
+        &self,
+        needed_lines: usize,
+    ) -> (Value, usize, Option<anyhow::Error>, Option<usize>) {
         // Try parsing the full programme
         let parse_result = parser::parse_programme(&self.programme);
 
@@ -458,6 +885,9 @@ impl InteractiveMode {
                 // Try to find the longest valid prefix
                 let mut valid_prog = None;
                 for i in (0..self.programme.len()).rev() {
+                    if !self.programme.is_char_boundary(i) {
+                        continue;
+                    }
                     if let Ok(prog) = parser::parse_programme(&self.programme[..i])
                         && !prog.operators.is_empty()
                     {
@@ -477,7 +907,14 @@ impl InteractiveMode {
         // Compile and run whatever we successfully parsed
         let ops = match interpreter::compile_with_config(&programme, &self.config) {
             Ok(ops) => ops,
-            Err(e) => return (Value::Array(self.input.deep_copy()), depth, Some(e.into())),
+            Err(e) => {
+                return (
+                    Value::Array(self.input.deep_copy()),
+                    depth,
+                    Some(e.into()),
+                    None,
+                );
+            }
         };
 
         // Check if any operator requires full input (sort, dedupe, count, etc.)
@@ -497,10 +934,11 @@ impl InteractiveMode {
                 self.input.truncated_copy(batch_size)
             };
 
+            let partial = (batch_size < self.input.len()).then_some(batch_size);
             let mut ctx = interpreter::Context::new(Value::Array(input));
 
             if let Err(e) = interpreter::run(&ops, &mut ctx) {
-                return (ctx.into_value(), depth, Some(e.into()));
+                return (ctx.into_value(), depth, Some(e.into()), partial);
             }
 
             let result = ctx.into_value();
@@ -508,7 +946,7 @@ impl InteractiveMode {
 
             // If we have enough lines or processed all input, return
             if output_lines >= needed_lines || batch_size >= self.input.len() {
-                return (result, depth, parse_error);
+                return (result, depth, parse_error, partial);
             }
         }
 
@@ -541,13 +979,30 @@ fn compute_depth(programme: &ast::Programme) -> usize {
     depth.max(0) as usize
 }
 
+/// The status entry for the level the next operator acts on, once inside `@`.
+fn depth_status(depth: usize) -> Option<String> {
+    (depth > 0).then(|| format!("[depth {}]", depth))
+}
+
+/// Join status entries with spaces, dropping entries from the left until the
+/// text fits in `width` columns. The last entry is always kept.
+fn fit_status(mut status: Vec<String>, width: usize) -> String {
+    let mut text = status.join(" ");
+    while status.len() > 1 && text.chars().count() > width {
+        status.remove(0);
+        text = status.join(" ");
+    }
+    text
+}
+
 /// Extract error offset and message from a parse error string.
 fn parse_error_info(err: &anyhow::Error) -> (usize, String) {
     let err_str = err.to_string();
 
     // Parse errors from our parser look like:
     // "parse error: expected <selection>\n  sg\n    ^"
-    // The input line has a 2-space prefix, so we subtract 2 from caret position
+    // The input line has a 2-space prefix, so we subtract 2 from caret position.
+    // The caret is already placed by char column, not byte offset.
 
     if err_str.rfind('^').is_some() {
         let lines: Vec<&str> = err_str.lines().collect();
@@ -567,3 +1022,87 @@ fn parse_error_info(err: &anyhow::Error) -> (usize, String) {
     // Fallback for runtime errors or unexpected format
     (0, err_str)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value::Level;
+
+    #[test]
+    fn preview_status_counts_elements() {
+        let stats = |elements| PreviewStats {
+            elements,
+            partial: None,
+        };
+        assert_eq!(stats(0).status(10), vec!["0 elements"]);
+        assert_eq!(stats(1).status(10), vec!["1 element"]);
+        assert_eq!(stats(42).status(10), vec!["42 elements"]);
+    }
+
+    #[test]
+    fn preview_status_shows_coverage_when_partial() {
+        let stats = PreviewStats {
+            elements: 7,
+            partial: Some(100),
+        };
+        assert_eq!(
+            stats.status(5000),
+            vec!["(preview: 100 of 5000 lines)", "7 elements"]
+        );
+    }
+
+    #[test]
+    fn fit_status_drops_entries_from_the_left() {
+        let status = || {
+            vec![
+                "(preview: 100 of 5000 lines)".to_string(),
+                "7 elements".to_string(),
+                "^H Help".to_string(),
+            ]
+        };
+        assert_eq!(
+            fit_status(status(), 80),
+            "(preview: 100 of 5000 lines) 7 elements ^H Help"
+        );
+        assert_eq!(fit_status(status(), 18), "7 elements ^H Help");
+        assert_eq!(fit_status(status(), 17), "^H Help");
+        // The last entry stays even when nothing fits
+        assert_eq!(fit_status(status(), 0), "^H Help");
+    }
+
+    #[test]
+    fn depth_status_only_inside_descend() {
+        let depth =
+            |programme| depth_status(compute_depth(&parser::parse_programme(programme).unwrap()));
+        assert_eq!(depth("s"), None);
+        assert_eq!(depth("s@"), Some("[depth 1]".to_string()));
+        assert_eq!(depth("s@s@u"), Some("[depth 2]".to_string()));
+        assert_eq!(depth("s@s@^"), Some("[depth 1]".to_string()));
+        assert_eq!(depth("s@^^"), None);
+    }
+
+    fn mode() -> InteractiveMode {
+        InteractiveMode::new_with_config(
+            Array::from((vec![], Level::Line)),
+            false,
+            false,
+            false,
+            CompileConfig::default(),
+        )
+    }
+
+    #[test]
+    fn paste_inserts_whole_without_line_breaks() {
+        let mut mode = mode();
+        mode.paste("s@1^\n");
+        assert_eq!((mode.programme.as_str(), mode.cursor), ("s@1^", 4));
+
+        mode.cursor = 2;
+        mode.paste("u\r\n\t");
+        assert_eq!((mode.programme.as_str(), mode.cursor), ("s@u1^", 3));
+
+        // The paste is undone as one edit
+        mode.handle_key(KeyEvent::new(KeyCode::Char('z'), KeyModifiers::CONTROL));
+        assert_eq!((mode.programme.as_str(), mode.cursor), ("s@1^", 2));
+    }
+}
@@ -1,13 +1,22 @@
 //! Text formatting for interactive output.
 
-use crate::value::Value;
+use std::io;
+
+use crate::ast;
+use crate::value::{Value, format_number};
+
+/// Lines of each value shown by `--trace`.
+const TRACE_LINES: usize = 5;
+
+/// Width that lines shown by `--trace` are cut to.
+const TRACE_WIDTH: usize = 100;
 
 /// Count the number of output lines a value would produce when displayed.
 pub fn count_output_lines(value: &Value) -> usize {
     match value {
         Value::Array(arr) => arr.len(),
         Value::Text(s) => s.lines().count().max(1),
-        Value::Number(_) => 1,
+        Value::Number(_) | Value::Bool(_) => 1,
     }
 }
 
@@ -42,18 +51,49 @@ pub fn format_text_with_depth(
             .take(max_lines)
             .map(|l| truncate_line(l, max_width))
             .collect(),
-        Value::Number(n) => vec![truncate_line(&n.to_string(), max_width)],
+        Value::Number(n) => vec![truncate_line(&format_number(*n), max_width)],
+        Value::Bool(b) => vec![truncate_line(&b.to_string(), max_width)],
+    }
+}
+
+/// Write an operator and the value it produced, for `--trace`: a header
+/// naming the operator, then the first few lines of the value, indented, and
+/// how many more there are. `@` and `^` don't change the value, so only their
+/// header is written, and `;` is skipped.
+pub fn write_trace<W: io::Write>(w: &mut W, op: &ast::Operator, value: &Value) -> io::Result<()> {
+    match op {
+        ast::Operator::NoOp => return Ok(()),
+        ast::Operator::Descend | ast::Operator::Ascend => return writeln!(w, "== {}", op),
+        _ => writeln!(w, "== {}", op)?,
+    }
+    for line in format_text_with_depth(value, 0, TRACE_LINES, TRACE_WIDTH) {
+        writeln!(w, "  {}", line)?;
     }
+    let more = count_output_lines(value).saturating_sub(TRACE_LINES);
+    if more > 0 {
+        writeln!(w, "  ... {} more", more)?;
+    }
+    Ok(())
 }
 
-/// Truncate a line to fit within max_width, adding "..." if truncated.
-fn truncate_line(line: &str, max_width: usize) -> String {
-    if line.len() <= max_width {
-        line.to_string()
-    } else if max_width > 3 {
-        format!("{}...", &line[..max_width - 3])
+/// Truncate a line to fit within max_width characters, adding "..." if truncated.
+///
+/// Width is measured in chars and the cut always lands on a char boundary,
+/// so multibyte text never panics.
+pub fn truncate_line(line: &str, max_width: usize) -> String {
+    if line.chars().count() <= max_width {
+        return line.to_string();
+    }
+    let keep = if max_width > 3 {
+        max_width - 3
     } else {
-        line[..max_width].to_string()
+        max_width
+    };
+    let end = line.char_indices().nth(keep).map_or(line.len(), |(i, _)| i);
+    if max_width > 3 {
+        format!("{}...", &line[..end])
+    } else {
+        line[..end].to_string()
     }
 }
 
@@ -107,3 +147,75 @@ fn format_text_element_highlighted(
         _ => truncate_line(&format!("{}", value), max_width),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value::{Array, Level};
+
+    fn trace(op: ast::Operator, value: &Value) -> String {
+        let mut buf = Vec::new();
+        write_trace(&mut buf, &op, value).unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+
+    #[test]
+    fn write_trace_shows_first_lines() {
+        let lines: Vec<Value> = (1..=8).map(|n| Value::Number(n as f64)).collect();
+        let value = Value::Array(Array::from((lines, Level::Line)));
+        assert_eq!(
+            trace(ast::Operator::ToNumber, &value),
+            "== convert to numbers\n  1\n  2\n  3\n  4\n  5\n  ... 3 more\n"
+        );
+        assert_eq!(
+            trace(ast::Operator::Count, &Value::Number(8.0)),
+            "== count\n  8\n"
+        );
+    }
+
+    #[test]
+    fn write_trace_skips_values_of_navigation() {
+        let value = Value::Text("a".to_string());
+        assert_eq!(
+            trace(ast::Operator::Descend, &value),
+            "== descend into each element\n"
+        );
+        assert_eq!(trace(ast::Operator::NoOp, &value), "");
+    }
+
+    #[test]
+    fn truncate_line_short_line_unchanged() {
+        assert_eq!(truncate_line("café", 4), "café");
+    }
+
+    #[test]
+    fn truncate_line_ascii() {
+        assert_eq!(truncate_line("hello world", 8), "hello...");
+        assert_eq!(truncate_line("hello world", 3), "hel");
+    }
+
+    #[test]
+    fn truncate_line_multibyte() {
+        let line = "café…😀 and more";
+        assert_eq!(truncate_line(line, 7), "café...");
+        assert_eq!(truncate_line(line, 9), "café…😀...");
+        assert_eq!(truncate_line(line, 2), "ca");
+        assert_eq!(truncate_line("😀😀😀😀", 3), "😀😀😀");
+    }
+
+    #[test]
+    fn format_preview_multibyte_narrow_width() {
+        use crate::value::{Array, Level};
+
+        let value = Value::Array(Array::from((
+            vec![Value::Text("café…😀 café…😀".to_string())],
+            Level::Line,
+        )));
+        for width in 0..20 {
+            let lines = format_text_with_depth(&value, 0, 10, width);
+            assert!(lines[0].chars().count() <= width);
+        }
+        let lines = format_text_with_depth(&value, 0, 10, 8);
+        assert_eq!(lines, vec!["café…..."]);
+    }
+}
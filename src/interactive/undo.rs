@@ -0,0 +1,89 @@
+//! Undo and redo of edits to the programme in interactive mode.
+
+use std::collections::VecDeque;
+
+/// Most edits remembered for undo; older ones are dropped.
+const MAX_UNDO: usize = 200;
+
+/// A programme and the cursor position within it.
+pub type Snapshot = (String, usize);
+
+#[derive(Default)]
+pub struct UndoStack {
+    undo: VecDeque<Snapshot>,
+    redo: Vec<Snapshot>,
+}
+
+impl UndoStack {
+    /// Remember the state from before an edit. A fresh edit can't be redone
+    /// over, so this clears the redo stack.
+    pub fn record(&mut self, before: Snapshot) {
+        if self.undo.len() == MAX_UNDO {
+            self.undo.pop_front();
+        }
+        self.undo.push_back(before);
+        self.redo.clear();
+    }
+
+    /// Step back to the state before the last edit, given the current state.
+    pub fn undo(&mut self, current: Snapshot) -> Option<Snapshot> {
+        let previous = self.undo.pop_back()?;
+        self.redo.push(current);
+        Some(previous)
+    }
+
+    /// Re-apply the last undone edit, given the current state.
+    pub fn redo(&mut self, current: Snapshot) -> Option<Snapshot> {
+        let next = self.redo.pop()?;
+        self.undo.push_back(current);
+        Some(next)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snap(s: &str) -> Snapshot {
+        (s.to_string(), s.len())
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let mut stack = UndoStack::default();
+        stack.record(snap(""));
+        stack.record(snap("s"));
+
+        assert_eq!(stack.undo(snap("s@")), Some(snap("s")));
+        assert_eq!(stack.undo(snap("s")), Some(snap("")));
+        assert_eq!(stack.undo(snap("")), None);
+
+        assert_eq!(stack.redo(snap("")), Some(snap("s")));
+        assert_eq!(stack.redo(snap("s")), Some(snap("s@")));
+        assert_eq!(stack.redo(snap("s@")), None);
+    }
+
+    #[test]
+    fn test_edit_clears_redo() {
+        let mut stack = UndoStack::default();
+        stack.record(snap(""));
+        assert_eq!(stack.undo(snap("s")), Some(snap("")));
+
+        stack.record(snap(""));
+        assert_eq!(stack.redo(snap("l")), None);
+        assert_eq!(stack.undo(snap("l")), Some(snap("")));
+    }
+
+    #[test]
+    fn test_size_is_capped() {
+        let mut stack = UndoStack::default();
+        for i in 0..MAX_UNDO + 10 {
+            stack.record((i.to_string(), 0));
+        }
+        let mut undone = 0;
+        while stack.undo(snap("")).is_some() {
+            undone += 1;
+        }
+        assert_eq!(undone, MAX_UNDO);
+    }
+}
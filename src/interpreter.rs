@@ -3,16 +3,26 @@
 //! The interpreter executes a programme by applying operators to a value.
 //! Operators are either transforms (Value -> Value) or navigations (mutate depth).
 
-use regex::Regex;
+use regex::{Regex, RegexBuilder};
 
 use crate::ast;
 use crate::error::{Error, Result};
 use crate::operators::{
-    Ascend, Columnate, Count, DedupeSelectionWithCounts, DedupeWithCounts, DeleteEmpty, Descend,
-    Filter, Flatten, GroupBy, Join, JoinDelim, JoinMode, Lowercase, LowercaseSelected, MatchAll,
-    NoOp, Partition, Replace, Select, SortAscending, SortDescending, Split, SplitDelim, SplitMode,
-    Sum, ToNumber, ToNumberSelected, Trim, TrimSelected, Uppercase, UppercaseSelected,
+    AddScalar, AdjacentDedupe, Agg, ApplyIf, Arithmetic, Ascend, ChunkBy, Clamp, Columnate,
+    Compare, CompareLength, ConvertCase, Count, CountLeaves, DedupeSelection,
+    DedupeSelectionWithCounts, DedupeWithCounts, DedupeWithCountsIgnoreCase, DedupeWithPercentages,
+    DeepMap, DeleteEmpty, Descend, ExpandTabs, Extract, Filter, FilterContext, FilterFields,
+    FilterSelected, Flatten, Format, GroupAgg, GroupBy, Head, Histogram, Join, JoinDelim, JoinMode,
+    LineRange, Lowercase, LowercaseSelected, MatchAll, Matches, Mode, MovingAverage, MulScalar,
+    Ngram, NoOp, ParseKv, Partition, Quantile, Rank, Repeat, Replace, ReverseChars, Rot, Sample,
+    Select, SetLevel, Shuffle, Slugify, SortAscending, SortBy, SortChars, SortDescending,
+    SortUnique, Split, SplitAny, SplitDelim, SplitMode, SplitSentences, Squeeze, Stats, StdDev,
+    StripAnsi, StripComments, Sum, Tail, ToNumber, ToNumberSelected, Trim, TrimChars,
+    TrimCharsSelected, TrimSelected, Uppercase, UppercaseSelected, Variance, WeightedSum, Wrap,
+    output_width, random_seed,
 };
+#[cfg(feature = "dates")]
+use crate::operators::{FormatDate, ParseDate, ToEpoch};
 use crate::value::Value;
 
 /// Configuration for the compiler.
@@ -22,6 +32,14 @@ pub struct CompileConfig {
     pub split_mode: SplitMode,
     /// Mode for the `j` (join) operator
     pub join_mode: JoinMode,
+    /// Make `n`, `N` and `+` fail on non-numeric text instead of coercing it
+    pub strict: bool,
+    /// Make an out-of-range index in a selection an error instead of skipping it
+    pub strict_select: bool,
+    /// Seed for `Z` and `z`; a random seed is used when unset
+    pub seed: Option<u64>,
+    /// Column names from the `--header` row, for selecting columns by name
+    pub header: Option<Vec<String>>,
 }
 
 /// A transform operator converts a value to a new value.
@@ -83,6 +101,11 @@ impl Context {
         self.root.expect("context should have root value")
     }
 
+    /// The root value as it stands, e.g. to inspect it between operators.
+    pub fn value(&self) -> &Value {
+        self.root.as_ref().expect("context should have root value")
+    }
+
     /// Get the current depth.
     #[allow(dead_code)] // Reserved for future use
     pub fn depth(&self) -> usize {
@@ -133,11 +156,21 @@ fn replace_at_depth(value: Value, depth: usize, op: &dyn Transform) -> Result<Va
 
 /// Run a programme (sequence of operators) on a context.
 pub fn run(ops: &[Operator], ctx: &mut Context) -> Result<()> {
-    for op in ops {
+    run_with(ops, ctx, |_, _| {})
+}
+
+/// Run a programme on a context, calling `after_step` with the index of each
+/// operator once it has run, e.g. to trace the intermediate values.
+pub fn run_with<F>(ops: &[Operator], ctx: &mut Context, mut after_step: F) -> Result<()>
+where
+    F: FnMut(usize, &Context),
+{
+    for (i, op) in ops.iter().enumerate() {
         match op {
             Operator::Transform(t) => ctx.execute(t.as_ref())?,
             Operator::Navigate(n) => n.apply(ctx),
         }
+        after_step(i, ctx);
     }
     Ok(())
 }
@@ -157,6 +190,8 @@ pub fn compile_with_config(
     programme: &ast::Programme,
     config: &CompileConfig,
 ) -> Result<Vec<Operator>> {
+    let mut programme = programme.clone();
+    resolve_names(&mut programme, config.header.as_deref())?;
     programme
         .operators
         .iter()
@@ -164,6 +199,97 @@ pub fn compile_with_config(
         .collect()
 }
 
+/// Compile the operator wrapped by a combinator such as `*`, which must be a
+/// transform rather than `@` or `^`.
+fn compile_inner(
+    op: &ast::Operator,
+    config: &CompileConfig,
+    combinator: &str,
+) -> Result<Box<dyn Transform>> {
+    match compile_op(op, config)? {
+        Operator::Transform(t) => Ok(t),
+        Operator::Navigate(_) => Err(Error::runtime(format!(
+            "'{}' cannot be followed by '@' or '^'",
+            combinator
+        ))),
+    }
+}
+
+/// Replace column names in selections with their index in the header row.
+fn resolve_names(programme: &mut ast::Programme, header: Option<&[String]>) -> Result<()> {
+    for op in &mut programme.operators {
+        for sel in op.selections_mut() {
+            resolve_selection_names(sel, header)?;
+        }
+    }
+    Ok(())
+}
+
+/// Replace column names in one selection, including those in predicates.
+fn resolve_selection_names(sel: &mut ast::Selection, header: Option<&[String]>) -> Result<()> {
+    for item in &mut sel.items {
+        let name = match item {
+            ast::SelectItem::Name(name) => name,
+            ast::SelectItem::Predicate(predicate) => {
+                resolve_selection_names(&mut predicate.column, header)?;
+                continue;
+            }
+            _ => continue,
+        };
+        let Some(header) = header else {
+            return Err(Error::runtime(format!(
+                "cannot select column \"{}\" by name without --header",
+                name
+            )));
+        };
+        let Some(idx) = header.iter().position(|h| h == name) else {
+            return Err(Error::runtime(format!(
+                "no column named \"{}\" in header",
+                name
+            )));
+        };
+        *item = ast::SelectItem::Index(idx as i64);
+    }
+    Ok(())
+}
+
+/// Returns how many input lines a programme can possibly look at, if bounded.
+///
+/// A programme that starts with `h<n>`, `&lines<first>-<last>`, or a selection of non-negative indices
+/// and slices with non-negative ends, only ever sees that prefix of the input,
+/// so everything after it (including full-input operators like sort) gets the
+/// same result whether or not the rest of the input was read. Negative indices,
+/// open-ended slices and negative steps need the full length and disable the
+/// limit.
+pub fn input_line_limit(programme: &ast::Programme) -> Option<usize> {
+    let first = programme
+        .operators
+        .iter()
+        .find(|op| !matches!(op, ast::Operator::NoOp))?;
+    let sel = match first {
+        ast::Operator::Selection(sel) => sel,
+        ast::Operator::Head(n) => return Some(*n),
+        ast::Operator::LineRange { last, .. } => return *last,
+        _ => return None,
+    };
+
+    sel.items
+        .iter()
+        .map(|item| match item {
+            ast::SelectItem::Index(i) => usize::try_from(*i).ok().map(|i| i + 1),
+            ast::SelectItem::Slice(slice) => {
+                let step_ok = slice.step.is_none_or(|s| s > 0);
+                let start_ok = slice.start.is_none_or(|s| s >= 0);
+                match slice.end {
+                    Some(end) if step_ok && start_ok => usize::try_from(end).ok(),
+                    _ => None,
+                }
+            }
+            ast::SelectItem::Name(_) | ast::SelectItem::Predicate(_) => None,
+        })
+        .try_fold(0, |acc, limit| limit.map(|l| acc.max(l)))
+}
+
 /// Compile a single AST operator into an Operator.
 ///
 /// Returns an error if a regex pattern is invalid.
@@ -175,6 +301,20 @@ fn compile_op(op: &ast::Operator, config: &CompileConfig) -> Result<Operator> {
         ast::Operator::SplitDelim(delim) => {
             Operator::Transform(Box::new(SplitDelim::new(delim.clone())))
         }
+        ast::Operator::SplitAny(chars) => {
+            Operator::Transform(Box::new(SplitAny::new(chars.clone())))
+        }
+        ast::Operator::ParseKv => Operator::Transform(Box::new(ParseKv)),
+        ast::Operator::SplitSentences => Operator::Transform(Box::new(SplitSentences)),
+        ast::Operator::SplitDelimWith {
+            delimiter,
+            keep,
+            limit,
+        } => Operator::Transform(Box::new(SplitDelim::with_options(
+            delimiter.clone(),
+            *keep,
+            *limit,
+        ))),
         ast::Operator::Join => Operator::Transform(Box::new(Join::new(config.join_mode.clone()))),
         ast::Operator::JoinDelim(delim) => {
             Operator::Transform(Box::new(JoinDelim::new(delim.clone())))
@@ -186,54 +326,261 @@ fn compile_op(op: &ast::Operator, config: &CompileConfig) -> Result<Operator> {
             Operator::Transform(Box::new(UppercaseSelected::new(sel.clone())))
         }
         ast::Operator::Lowercase => Operator::Transform(Box::new(Lowercase)),
+        ast::Operator::ReverseChars => Operator::Transform(Box::new(ReverseChars)),
+        ast::Operator::Slugify => Operator::Transform(Box::new(Slugify)),
+        ast::Operator::Rot(shift) => Operator::Transform(Box::new(Rot::new(*shift))),
+        ast::Operator::ConvertCase(style) => {
+            Operator::Transform(Box::new(ConvertCase::new(*style)))
+        }
         ast::Operator::LowercaseSelected(sel) => {
             Operator::Transform(Box::new(LowercaseSelected::new(sel.clone())))
         }
-        ast::Operator::ToNumber => Operator::Transform(Box::new(ToNumber)),
+        ast::Operator::ToNumber => Operator::Transform(Box::new(ToNumber::new(config.strict))),
         ast::Operator::ToNumberSelected(sel) => {
-            Operator::Transform(Box::new(ToNumberSelected::new(sel.clone())))
+            Operator::Transform(Box::new(ToNumberSelected::new(sel.clone(), config.strict)))
         }
         ast::Operator::Replace {
             selection,
             pattern,
             replacement,
+            case_insensitive,
+            first_only,
         } => {
-            let regex = Regex::new(pattern)
+            let regex = RegexBuilder::new(pattern)
+                .case_insensitive(*case_insensitive)
+                .build()
                 .map_err(|e| Error::runtime(format!("invalid regex '{}': {}", pattern, e)))?;
             Operator::Transform(Box::new(Replace::new(
                 regex,
                 replacement.clone(),
                 selection.clone(),
+                *first_only,
             )))
         }
         ast::Operator::Trim => Operator::Transform(Box::new(Trim)),
+        ast::Operator::TrimChars { chars, side } => {
+            Operator::Transform(Box::new(TrimChars::new(chars, *side)))
+        }
+        ast::Operator::TrimCharsSelected {
+            selection,
+            chars,
+            side,
+        } => Operator::Transform(Box::new(TrimCharsSelected::new(
+            selection.clone(),
+            chars,
+            *side,
+        ))),
         ast::Operator::TrimSelected(sel) => {
             Operator::Transform(Box::new(TrimSelected::new(sel.clone())))
         }
+        ast::Operator::StripComments { marker, keep_empty } => {
+            Operator::Transform(Box::new(StripComments::new(marker.clone(), *keep_empty)))
+        }
         ast::Operator::DeleteEmpty => Operator::Transform(Box::new(DeleteEmpty)),
         ast::Operator::Flatten => Operator::Transform(Box::new(Flatten)),
         ast::Operator::DedupeWithCounts => Operator::Transform(Box::new(DedupeWithCounts)),
+        ast::Operator::DedupeWithPercentages => {
+            Operator::Transform(Box::new(DedupeWithPercentages))
+        }
         ast::Operator::DedupeSelectionWithCounts(sel) => {
-            Operator::Transform(Box::new(DedupeSelectionWithCounts::new(sel.clone())))
+            Operator::Transform(Box::new(DedupeSelectionWithCounts::new(sel.clone(), false)))
+        }
+        ast::Operator::DedupeSelection(sel) => {
+            Operator::Transform(Box::new(DedupeSelection::new(sel.clone())))
+        }
+        ast::Operator::DedupeWithCountsIgnoreCase => {
+            Operator::Transform(Box::new(DedupeWithCountsIgnoreCase))
+        }
+        ast::Operator::DedupeSelectionWithCountsIgnoreCase(sel) => {
+            Operator::Transform(Box::new(DedupeSelectionWithCounts::new(sel.clone(), true)))
         }
-        ast::Operator::Sum => Operator::Transform(Box::new(Sum)),
+        ast::Operator::Sum => Operator::Transform(Box::new(Sum::new(config.strict))),
+        ast::Operator::WeightedSum { a, b } => {
+            Operator::Transform(Box::new(WeightedSum::new(a.clone(), b.clone())))
+        }
+        ast::Operator::Arithmetic { a, op, b } => Operator::Transform(Box::new(Arithmetic::new(
+            a.clone(),
+            *op,
+            b.clone(),
+            config.strict,
+        ))),
+        ast::Operator::AddScalar(n) => {
+            Operator::Transform(Box::new(AddScalar::new(*n, config.strict)))
+        }
+        ast::Operator::MulScalar(n) => {
+            Operator::Transform(Box::new(MulScalar::new(*n, config.strict)))
+        }
+        ast::Operator::Clamp { min, max } => {
+            Operator::Transform(Box::new(Clamp::new(*min, *max, config.strict)?))
+        }
+        ast::Operator::SortChars { ignore_case } => {
+            Operator::Transform(Box::new(SortChars::new(*ignore_case)))
+        }
+        ast::Operator::Rank { sorted } => Operator::Transform(Box::new(Rank::new(*sorted))),
+        ast::Operator::Quantile(percent) => {
+            Operator::Transform(Box::new(Quantile::new(*percent, config.strict)?))
+        }
+        ast::Operator::Variance { sample } => {
+            Operator::Transform(Box::new(Variance::new(*sample, config.strict)))
+        }
+        ast::Operator::StdDev { sample } => {
+            Operator::Transform(Box::new(StdDev::new(*sample, config.strict)))
+        }
+        ast::Operator::MovingAverage(n) => {
+            Operator::Transform(Box::new(MovingAverage::new(*n, config.strict)?))
+        }
+        ast::Operator::Mode => Operator::Transform(Box::new(Mode)),
+        ast::Operator::Ngram(n) => Operator::Transform(Box::new(Ngram::new(*n)?)),
+        ast::Operator::Repeat { n, whole } => {
+            Operator::Transform(Box::new(Repeat::new(*n, *whole)))
+        }
+        ast::Operator::SetLevel(level) => Operator::Transform(Box::new(SetLevel::new(*level))),
         ast::Operator::Count => Operator::Transform(Box::new(Count)),
-        ast::Operator::Columnate => Operator::Transform(Box::new(Columnate)),
+        ast::Operator::CountLeaves => Operator::Transform(Box::new(CountLeaves)),
+        ast::Operator::Stats => Operator::Transform(Box::new(Stats)),
+        ast::Operator::Format(template) => Operator::Transform(Box::new(Format::new(template)?)),
+        ast::Operator::Columnate { width, separator } => {
+            Operator::Transform(Box::new(Columnate::new(*width, separator.clone())))
+        }
+        ast::Operator::Histogram => Operator::Transform(Box::new(Histogram::new(output_width()))),
         ast::Operator::Partition(sel) => Operator::Transform(Box::new(Partition::new(sel.clone()))),
         ast::Operator::SortDescending => Operator::Transform(Box::new(SortDescending)),
         ast::Operator::SortAscending => Operator::Transform(Box::new(SortAscending)),
-        ast::Operator::Selection(sel) => Operator::Transform(Box::new(Select::new(sel.clone()))),
+        ast::Operator::ChunkBy(sel) => Operator::Transform(Box::new(ChunkBy::new(sel.clone()))),
+        ast::Operator::SortBy { keys, descending } => {
+            Operator::Transform(Box::new(SortBy::new(keys.clone(), descending.clone())))
+        }
+        ast::Operator::SortUnique { descending } => {
+            Operator::Transform(Box::new(SortUnique::new(*descending)))
+        }
+        ast::Operator::Head(n) => Operator::Transform(Box::new(Head::new(*n))),
+        ast::Operator::Tail(n) => Operator::Transform(Box::new(Tail::new(*n))),
+        ast::Operator::LineRange { first, last } => {
+            Operator::Transform(Box::new(LineRange::new(*first, *last)))
+        }
+        ast::Operator::Sample(n) => Operator::Transform(Box::new(Sample::new(
+            *n,
+            config.seed.unwrap_or_else(random_seed),
+        ))),
+        ast::Operator::Shuffle => Operator::Transform(Box::new(Shuffle::new(
+            config.seed.unwrap_or_else(random_seed),
+        ))),
+        ast::Operator::Selection(sel) => {
+            Operator::Transform(Box::new(Select::new(sel.clone(), config.strict_select)))
+        }
         ast::Operator::Filter { pattern, negate } => {
             let regex = Regex::new(pattern)
                 .map_err(|e| Error::runtime(format!("invalid regex '{}': {}", pattern, e)))?;
             Operator::Transform(Box::new(Filter::new(regex, *negate)))
         }
+        ast::Operator::FilterSelected {
+            selection,
+            pattern,
+            negate,
+        } => {
+            let regex = Regex::new(pattern)
+                .map_err(|e| Error::runtime(format!("invalid regex '{}': {}", pattern, e)))?;
+            Operator::Transform(Box::new(FilterSelected::new(
+                selection.clone(),
+                regex,
+                *negate,
+            )))
+        }
+        ast::Operator::FilterContext {
+            pattern,
+            negate,
+            before,
+            after,
+        } => {
+            let regex = Regex::new(pattern)
+                .map_err(|e| Error::runtime(format!("invalid regex '{}': {}", pattern, e)))?;
+            Operator::Transform(Box::new(FilterContext::new(
+                regex, *negate, *before, *after,
+            )))
+        }
+        ast::Operator::FilterFields {
+            pattern,
+            negate,
+            all,
+        } => {
+            let regex = Regex::new(pattern)
+                .map_err(|e| Error::runtime(format!("invalid regex '{}': {}", pattern, e)))?;
+            Operator::Transform(Box::new(FilterFields::new(regex, *negate, *all)))
+        }
+        ast::Operator::Matches { pattern, negate } => {
+            let regex = Regex::new(pattern)
+                .map_err(|e| Error::runtime(format!("invalid regex '{}': {}", pattern, e)))?;
+            Operator::Transform(Box::new(Matches::new(regex, *negate)))
+        }
+        ast::Operator::Compare {
+            selection,
+            cmp,
+            value,
+        } => Operator::Transform(Box::new(Compare::new(selection.clone(), *cmp, *value))),
+        ast::Operator::CompareLength { cmp, value } => {
+            Operator::Transform(Box::new(CompareLength::new(*cmp, *value)))
+        }
         ast::Operator::Match { pattern } => {
             let regex = Regex::new(pattern)
                 .map_err(|e| Error::runtime(format!("invalid regex '{}': {}", pattern, e)))?;
             Operator::Transform(Box::new(MatchAll::new(regex)))
         }
+        ast::Operator::Extract { pattern } => {
+            let regex = Regex::new(pattern)
+                .map_err(|e| Error::runtime(format!("invalid regex '{}': {}", pattern, e)))?;
+            Operator::Transform(Box::new(Extract::new(regex)))
+        }
         ast::Operator::GroupBy(sel) => Operator::Transform(Box::new(GroupBy::new(sel.clone()))),
+        ast::Operator::Agg { key, funcs, value } => Operator::Transform(Box::new(Agg::new(
+            key.clone(),
+            funcs.clone(),
+            value.clone(),
+        ))),
+        ast::Operator::GroupAgg { funcs, value } => {
+            Operator::Transform(Box::new(GroupAgg::new(funcs.clone(), value.clone())))
+        }
+        #[cfg(feature = "dates")]
+        ast::Operator::ParseDate(format) => {
+            Operator::Transform(Box::new(ParseDate::new(format, config.strict)?))
+        }
+        #[cfg(feature = "dates")]
+        ast::Operator::FormatDate(format) => {
+            Operator::Transform(Box::new(FormatDate::new(format, config.strict)?))
+        }
+        #[cfg(feature = "dates")]
+        ast::Operator::ToEpoch(format) => {
+            Operator::Transform(Box::new(ToEpoch::new(format.as_deref(), config.strict)?))
+        }
+        #[cfg(not(feature = "dates"))]
+        ast::Operator::ParseDate(_) | ast::Operator::FormatDate(_) | ast::Operator::ToEpoch(_) => {
+            return Err(Error::runtime(
+                "date operators are not available (built without the `dates` feature)",
+            ));
+        }
+        ast::Operator::AdjacentDedupe { counts } => {
+            Operator::Transform(Box::new(AdjacentDedupe::new(*counts)))
+        }
+        ast::Operator::StripAnsi => Operator::Transform(Box::new(StripAnsi::new())),
+        ast::Operator::Wrap(width) => {
+            Operator::Transform(Box::new(Wrap::new(width.unwrap_or_else(output_width))))
+        }
+        ast::Operator::ExpandTabs(n) => Operator::Transform(Box::new(ExpandTabs::new(*n))),
+        ast::Operator::Squeeze(chars) => {
+            Operator::Transform(Box::new(Squeeze::new(chars.as_deref())))
+        }
+        ast::Operator::ApplyIf {
+            pattern,
+            negate,
+            op,
+        } => {
+            let regex = Regex::new(pattern)
+                .map_err(|e| Error::runtime(format!("invalid regex '{}': {}", pattern, e)))?;
+            let inner = compile_inner(op, config, "?/<regex>/")?;
+            Operator::Transform(Box::new(ApplyIf::new(regex, *negate, inner)))
+        }
+        ast::Operator::DeepMap(op) => {
+            Operator::Transform(Box::new(DeepMap::new(compile_inner(op, config, "*")?)))
+        }
         ast::Operator::NoOp => Operator::Transform(Box::new(NoOp)),
     })
 }
@@ -276,6 +623,27 @@ mod tests {
         assert_eq!(ctx.depth(), 0);
     }
 
+    #[test]
+    fn run_with_calls_hook_after_each_step() {
+        let programme = crate::parser::parse_programme("@u^#").unwrap();
+        let ops = compile(&programme).unwrap();
+        let mut ctx = Context::new(line_array(&["a", "b"]));
+        let mut steps = Vec::new();
+        run_with(&ops, &mut ctx, |i, ctx| {
+            steps.push((i, ctx.depth(), ctx.value().to_string()));
+        })
+        .unwrap();
+        assert_eq!(
+            steps,
+            vec![
+                (0, 1, "a\nb".to_string()),
+                (1, 1, "A\nB".to_string()),
+                (2, 0, "A\nB".to_string()),
+                (3, 0, "2".to_string()),
+            ]
+        );
+    }
+
     #[test]
     fn run_empty_programme() {
         let mut ctx = Context::new(line_array(&["hello", "world"]));
@@ -317,6 +685,146 @@ mod tests {
         assert_eq!(ops.len(), 1);
     }
 
+    fn limit_of(prog: &str) -> Option<usize> {
+        input_line_limit(&crate::parser::parse_programme(prog).unwrap())
+    }
+
+    #[test]
+    fn last_n_matches_negative_slice() {
+        let run_prog = |prog: &str, len: usize| {
+            let lines: Vec<String> = (0..len).map(|i| i.to_string()).collect();
+            let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+            let ops = compile(&crate::parser::parse_programme(prog).unwrap()).unwrap();
+            let mut ctx = Context::new(line_array(&lines));
+            run(&ops, &mut ctx).unwrap();
+            ctx.into_value()
+        };
+        // Longer and shorter than 20, and empty
+        for len in [30, 5, 0] {
+            assert_eq!(run_prog("~20", len), run_prog("-20:", len));
+        }
+        assert_eq!(run_prog("~2", 5), line_array(&["3", "4"]));
+        assert_eq!(limit_of("~20"), None);
+    }
+
+    #[test]
+    fn input_line_limit_prefix_slice() {
+        assert_eq!(limit_of(":20"), Some(20));
+        assert_eq!(limit_of("5:20:2s"), Some(20));
+        assert_eq!(limit_of(";:20o"), Some(20));
+    }
+
+    #[test]
+    fn input_line_limit_indices() {
+        assert_eq!(limit_of("0"), Some(1));
+        assert_eq!(limit_of("3,1,:2"), Some(4));
+    }
+
+    #[test]
+    fn input_line_limit_head() {
+        assert_eq!(limit_of("h20"), Some(20));
+        assert_eq!(limit_of(";h3s"), Some(3));
+        assert_eq!(limit_of("H20"), None);
+        assert_eq!(limit_of("&lines10-20"), Some(20));
+        assert_eq!(limit_of("&lines10-"), None);
+    }
+
+    #[test]
+    fn input_line_limit_needs_full_length() {
+        assert_eq!(limit_of("-1"), None);
+        assert_eq!(limit_of(":-5"), None);
+        assert_eq!(limit_of("-5:10"), None);
+        assert_eq!(limit_of("10:"), None);
+        assert_eq!(limit_of("10:0:-1"), None);
+        assert_eq!(limit_of("0,-1"), None);
+    }
+
+    #[test]
+    fn input_line_limit_not_leading_selection() {
+        assert_eq!(limit_of(""), None);
+        assert_eq!(limit_of("o:20"), None);
+        assert_eq!(limit_of("s:20"), None);
+    }
+
+    fn run_with_header(prog: &str, header: &[&str], input: Value) -> Result<Value> {
+        let config = CompileConfig {
+            header: Some(header.iter().map(|h| h.to_string()).collect()),
+            ..CompileConfig::default()
+        };
+        let ops = compile_with_config(&crate::parser::parse_programme(prog).unwrap(), &config)?;
+        let mut ctx = Context::new(input);
+        run(&ops, &mut ctx)?;
+        Ok(ctx.into_value())
+    }
+
+    #[test]
+    fn select_columns_by_name() {
+        let input = line_array(&["a 200 10", "b 404 20"]);
+        let result = run_with_header(r#"s@"size","name""#, &["name", "status", "size"], input);
+        assert_eq!(
+            result.unwrap(),
+            Value::Array(Array::from((
+                vec![
+                    Value::Array(Array::from((vec![text("10"), text("a")], Level::Word))),
+                    Value::Array(Array::from((vec![text("20"), text("b")], Level::Word))),
+                ],
+                Level::Line,
+            )))
+        );
+    }
+
+    #[test]
+    fn predicate_columns_by_name() {
+        let input = line_array(&["a 200 10", "b 404 20", "c 200 30"]);
+        let result = run_with_header(r#"s["status"=200]@2^"#, &["name", "status", "size"], input);
+        assert_eq!(
+            result.unwrap(),
+            Value::Array(Array::from((vec![text("10"), text("30")], Level::Line)))
+        );
+    }
+
+    #[test]
+    fn select_unknown_name_fails() {
+        let result = run_with_header(r#"s@"nope""#, &["name"], line_array(&["a"]));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn select_name_without_header_fails() {
+        let programme = crate::parser::parse_programme(r#"s?"status"=200"#).unwrap();
+        assert!(compile(&programme).is_err());
+    }
+
+    #[test]
+    fn input_line_limit_by_name() {
+        assert_eq!(limit_of(r#""a""#), None);
+    }
+
+    #[test]
+    fn deep_map_rejects_navigation() {
+        let programme = crate::parser::parse_programme("*@").unwrap();
+        assert!(compile(&programme).is_err());
+    }
+
+    #[test]
+    fn deep_map_lowercases_any_depth() {
+        let programme = crate::parser::parse_programme("s@s^*l").unwrap();
+        let ops = compile(&programme).unwrap();
+        let mut ctx = Context::new(line_array(&["Ab C"]));
+        run(&ops, &mut ctx).unwrap();
+        let words = Value::Array(Array::from((
+            vec![
+                Value::Array(Array::from((vec![text("a"), text("b")], Level::Char))),
+                Value::Array(Array::from((vec![text("c")], Level::Char))),
+            ],
+            Level::Word,
+        )));
+        assert_eq!(
+            ctx.into_value(),
+            Value::Array(Array::from((vec![words], Level::Line)))
+        );
+    }
+
     #[test]
     fn compile_invalid_regex() {
         let programme = ast::Programme {
@@ -1,4 +1,4 @@
-use std::io::{self, IsTerminal, Write};
+use std::io::{self, BufRead, IsTerminal, Read, Write};
 use std::path::PathBuf;
 
 use clap::{CommandFactory, Parser};
@@ -11,12 +11,13 @@ mod operators;
 mod parser;
 mod value;
 
+use interactive::JsonStyle;
 use interpreter::{CompileConfig, Context};
-use operators::{JoinMode, SplitMode};
-use value::{Array, Level, Value};
+use operators::{CsvFormat, JoinMode, SplitMode};
+use value::{Array, Level, NumberFormat, SetOp, Value};
 
 const ABOUT_INTRO: &str = r#"T is a concise language for manipulating text, replacing common usage
-patterns of Unix utilities like grep, sed, cut, awk, sort, and uniq.
+patterns of Unix utilities like grep, sed, cut, awk, sort, uniq, and wc.
 
 Example:
 Top 20 most frequent words (lowercased):
@@ -69,10 +70,40 @@ struct Cli {
     #[arg(short = 'j', long = "json")]
     json: bool,
 
+    /// Output as fully indented JSON, N spaces per level (default 2)
+    #[arg(
+        long = "pretty",
+        value_name = "N",
+        num_args = 0..=1,
+        default_missing_value = "2",
+        require_equals = true
+    )]
+    pretty: Option<usize>,
+
+    /// Output as JSON on a single line
+    #[arg(long = "compact-json", conflicts_with = "pretty")]
+    compact_json: bool,
+
+    /// Report errors on stderr as JSON objects, `{"error": "..."}`
+    #[arg(long = "json-errors")]
+    json_errors: bool,
+
+    /// Exit with status 1 if the output is empty and 2 on errors, like grep
+    #[arg(long = "exit-status")]
+    exit_status: bool,
+
     /// Interactive mode
     #[arg(short = 'i', long = "interactive")]
     interactive: bool,
 
+    /// Print what each operator of the programme does instead of running it
+    #[arg(long = "explain", conflicts_with = "interactive")]
+    explain: bool,
+
+    /// Print the value after each operator to stderr
+    #[arg(long = "trace")]
+    trace: bool,
+
     /// Print equivalent command line (with -i)
     #[arg(short = 'p', long = "print")]
     print_command: bool,
@@ -85,13 +116,106 @@ struct Cli {
     #[arg(short = 'D')]
     output_delim: Option<String>,
 
-    /// CSV mode (split/join use CSV parsing)
+    /// CSV mode (input is read as CSV records; split/join use CSV parsing)
     #[arg(short = 'c', long = "csv")]
     csv: bool,
 
+    /// Split into fixed-width fields of these widths in chars, e.g. `10,5,8`
+    #[arg(
+        long = "widths",
+        value_name = "W,...",
+        value_delimiter = ',',
+        conflicts_with_all = ["csv", "input_delim", "auto"]
+    )]
+    widths: Option<Vec<usize>>,
+
+    /// Guess whether input is comma-, tab- or pipe-separated (-c and -d override)
+    #[arg(long = "auto")]
+    auto: bool,
+
+    /// CSV field delimiter, e.g. `;` (with -c; `\t` for tabs)
+    #[arg(long = "csv-delimiter", requires = "csv", value_parser = parse_csv_char)]
+    csv_delimiter: Option<u8>,
+
+    /// CSV quote character (with -c)
+    #[arg(long = "csv-quote", requires = "csv", value_parser = parse_csv_char)]
+    csv_quote: Option<u8>,
+
     /// Debug mode (show semantic level before arrays)
     #[arg(long = "debug")]
     debug: bool,
+
+    /// Strict numbers (`n`, `+` and scalar arithmetic fail on non-numeric text)
+    #[arg(long = "strict")]
+    strict: bool,
+
+    /// Fail on selecting an index past either end instead of skipping it
+    #[arg(long = "strict-select")]
+    strict_select: bool,
+
+    /// Decimal places for non-integral numbers on output
+    #[arg(long = "precision", value_name = "N")]
+    precision: Option<usize>,
+
+    /// Group digits of numbers in text output in threes, with SEP (default `,`)
+    #[arg(
+        long = "group-digits",
+        value_name = "SEP",
+        num_args = 0..=1,
+        default_missing_value = ",",
+        require_equals = true
+    )]
+    group_digits: Option<char>,
+
+    /// Treat the first N lines as a header; columns can be selected by name
+    /// from the last of them (e.g. `"status"`)
+    #[arg(long = "header", value_name = "N")]
+    header: Option<usize>,
+
+    /// Read each input file whole, as one element, instead of as lines
+    #[arg(
+        short = 'w',
+        long = "whole",
+        conflicts_with_all = ["header", "paragraphs", "zip", "diff", "common", "union", "join_on"]
+    )]
+    whole: bool,
+
+    /// Read blank-line separated paragraphs instead of lines
+    #[arg(long = "paragraphs")]
+    paragraphs: bool,
+
+    /// Pair up the lines of the input files instead of concatenating them
+    #[arg(long = "zip", conflicts_with = "paragraphs")]
+    zip: bool,
+
+    /// Lines of the first file that are not in the second
+    #[arg(long = "diff", conflicts_with_all = ["common", "union", "zip", "paragraphs"])]
+    diff: bool,
+
+    /// Lines of the first file that are also in the second
+    #[arg(long = "common", conflicts_with_all = ["union", "zip", "paragraphs"])]
+    common: bool,
+
+    /// Lines of either file, each once
+    #[arg(long = "union", conflicts_with_all = ["zip", "paragraphs"])]
+    union: bool,
+
+    /// Join the rows of two files on the column at this index
+    #[arg(
+        long = "join-on",
+        value_name = "INDEX",
+        allow_negative_numbers = true,
+        conflicts_with_all = ["header", "zip", "paragraphs", "diff", "common", "union"]
+    )]
+    join_on: Option<i64>,
+
+    /// Don't load or save interactive mode's history
+    #[arg(long = "no-history")]
+    no_history: bool,
+
+    /// Seed for random operators (`Z`, `z`), for reproducible output
+    #[arg(long = "seed", value_name = "N")]
+    seed: Option<u64>,
 }
 
 fn main() {
@@ -113,6 +237,14 @@ fn main() {
 
     let cli = Cli::parse();
 
+    if cli.explain {
+        match parser::parse_programme(&cli.prog) {
+            Ok(programme) => print!("{}", programme),
+            Err(e) => fail(&e, &cli),
+        }
+        return;
+    }
+
     // In interactive mode, prog is treated as the first file argument
     let (prog, files) = if cli.interactive {
         let mut all_files = Vec::new();
@@ -126,12 +258,18 @@ fn main() {
     };
 
     if cli.interactive && files.is_empty() {
-        eprintln!("Error: interactive mode requires file arguments (cannot read from stdin)");
-        std::process::exit(1);
+        fail(
+            "Error: interactive mode requires file arguments (cannot read from stdin)",
+            &cli,
+        );
     }
 
     // Build compile config from CLI flags
-    let config = build_compile_config(&cli);
+    let mut config = build_compile_config(&cli, &files);
+    let numbers = NumberFormat {
+        precision: cli.precision,
+        group_separator: cli.group_digits,
+    };
 
     // Check which files are regular files (before reading, as pipes become invalid after)
     let regular_files: Vec<_> = files
@@ -144,141 +282,412 @@ fn main() {
         .cloned()
         .collect();
 
-    let input = if files.is_empty() {
-        Array::from_stdin(Level::Line)
+    // A leading prefix selection (e.g. `:20`) only needs the first few lines.
+    // Parse errors are reported later by run_batch.
+    let line_limit = if cli.interactive
+        || cli.whole
+        || cli.paragraphs
+        || cli.diff
+        || cli.common
+        || cli.union
+        || cli.join_on.is_some()
+    {
+        None
     } else {
-        let paths: Vec<PathBuf> = files.iter().map(PathBuf::from).collect();
-        Array::from_files(&paths, Level::Line)
+        parser::parse_programme(&prog)
+            .ok()
+            .and_then(|p| interpreter::input_line_limit(&p))
+            .map(|limit| limit + cli.header.unwrap_or(0))
     };
 
-    let array = match input {
-        Ok(a) => a,
-        Err(e) => {
-            eprintln!("Error reading input: {}", e);
-            std::process::exit(1);
+    if cli.zip && files.is_empty() {
+        fail("Error: --zip needs input files", &cli);
+    }
+
+    let set_op = if cli.diff {
+        Some(SetOp::Difference)
+    } else if cli.common {
+        Some(SetOp::Intersection)
+    } else if cli.union {
+        Some(SetOp::Union)
+    } else {
+        None
+    };
+
+    let input = if let Some(op) = set_op {
+        if files.len() != 2 {
+            fail(
+                "Error: --diff, --common and --union need exactly two files",
+                &cli,
+            );
+        }
+        Array::from_files(&[&files[0]], Level::Line, None).and_then(|a| {
+            Array::from_files(&[&files[1]], Level::Line, None).map(|b| a.combine(b, op))
+        })
+    } else if let Some(key) = cli.join_on {
+        if files.len() != 2 {
+            fail("Error: --join-on needs exactly two files", &cli);
+        }
+        let read_rows = |path: &String| {
+            Array::from_files(&[path], Level::Line, None)
+                .map(|lines| operators::split_rows(lines, &config.split_mode))
+        };
+        read_rows(&files[0]).and_then(|a| read_rows(&files[1]).map(|b| a.join_on(b, key)))
+    } else if let SplitMode::Csv(format) = config.split_mode
+        && !cli.whole
+        && !cli.zip
+        && !cli.paragraphs
+    {
+        // Read CSV records rather than lines, so quoted fields can hold newlines
+        if files.is_empty() {
+            Array::from_csv_stdin(format, line_limit)
+        } else {
+            Array::from_csv_files(&files, format, line_limit)
+        }
+    } else {
+        let level = if cli.whole { Level::File } else { Level::Line };
+        if files.is_empty() {
+            Array::from_stdin(level, line_limit)
+        } else {
+            let paths: Vec<PathBuf> = files.iter().map(PathBuf::from).collect();
+            if cli.zip {
+                Array::from_files_zipped(&paths, line_limit)
+            } else {
+                Array::from_files(&paths, level, line_limit)
+            }
         }
     };
 
-    if cli.interactive {
-        run_interactive(
+    let mut array = match input {
+        Ok(a) => a,
+        Err(e) => fail(&format!("Error reading input: {}", e), &cli),
+    };
+
+    if let Some(n) = cli.header {
+        let header: Vec<Value> = array.elements.drain(..n.min(array.len())).collect();
+        config.header = match header.last() {
+            Some(Value::Text(line)) => Some(operators::header_names(line, &config.split_mode)),
+            Some(Value::Array(row)) => Some(row.elements.iter().map(|v| v.to_string()).collect()),
+            _ => Some(Vec::new()),
+        };
+    }
+
+    if cli.paragraphs {
+        array = array.into_paragraphs();
+    }
+
+    let json_style = if cli.compact_json {
+        Some(JsonStyle::Compact)
+    } else {
+        cli.pretty.map(JsonStyle::Pretty)
+    };
+
+    let result = if cli.interactive {
+        let mode = interactive::InteractiveMode::new_with_config(
             array,
+            cli.json || json_style.is_some(),
+            cli.debug,
+            !cli.no_history,
+            config.clone(),
+        );
+        run_interactive(
+            mode,
             &regular_files,
             cli.print_command,
-            cli.json,
-            cli.debug,
             &config,
-        );
+            &numbers,
+            json_style.unwrap_or_default(),
+            cli.trace,
+        )
     } else {
-        run_batch(&prog, array, cli.json, cli.debug, &config);
+        let json = if cli.json || json_style.is_some() {
+            Some(json_style.unwrap_or_default())
+        } else {
+            None
+        };
+        run_batch(&prog, array, json, cli.debug, &config, &numbers, cli.trace)
+    };
+    match result {
+        Ok(found) => {
+            let status = exit_code(found, cli.exit_status);
+            if status != 0 {
+                std::process::exit(status);
+            }
+        }
+        Err(message) => fail(&message, &cli),
     }
 }
 
-fn build_compile_config(cli: &Cli) -> CompileConfig {
+/// The exit status after a successful run: 0, or with `--exit-status` 1 if
+/// the output was empty, like grep finding nothing.
+fn exit_code(found: bool, exit_status: bool) -> i32 {
+    if exit_status && !found { 1 } else { 0 }
+}
+
+/// Whether the result counts as output for `--exit-status`. A count of 0 or
+/// `false` is output; an empty array or string is not.
+fn found(value: &Value) -> bool {
+    !value.is_empty()
+}
+
+/// Print an error to stderr and exit with status 1, or 2 with `--exit-status`
+/// so that it can be told apart from empty output.
+///
+/// `message` is the line printed normally. With `--json-errors` it is printed
+/// as `{"error": "..."}` instead, without the leading `Error: `.
+fn fail(message: &str, cli: &Cli) -> ! {
+    eprintln!("{}", error_line(message, cli.json_errors));
+    std::process::exit(if cli.exit_status { 2 } else { 1 });
+}
+
+/// The line `fail` prints for `message`.
+fn error_line(message: &str, json_errors: bool) -> String {
+    if json_errors {
+        let message = message.strip_prefix("Error: ").unwrap_or(message);
+        serde_json::json!({ "error": message.trim_end() }).to_string()
+    } else {
+        message.to_string()
+    }
+}
+
+/// Parse a CSV delimiter or quote character: one ASCII character, or `\t`.
+fn parse_csv_char(s: &str) -> Result<u8, String> {
+    match s {
+        "\\t" => Ok(b'\t'),
+        _ if s.len() == 1 && s.is_ascii() => Ok(s.as_bytes()[0]),
+        _ => Err("expected a single ASCII character".to_string()),
+    }
+}
+
+/// Bytes read from the start of the input to guess its delimiter with `--auto`.
+const AUTO_SAMPLE_BYTES: usize = 8192;
+
+/// The start of the input: the first file, or what stdin has buffered so far.
+/// Stdin is only peeked at, so the whole input is still read afterwards.
+fn input_sample(files: &[String]) -> io::Result<String> {
+    let bytes = match files.first() {
+        Some(path) => {
+            let mut bytes = Vec::new();
+            std::fs::File::open(path)?
+                .take(AUTO_SAMPLE_BYTES as u64)
+                .read_to_end(&mut bytes)?;
+            bytes
+        }
+        None => io::stdin().lock().fill_buf()?.to_vec(),
+    };
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+fn build_compile_config(cli: &Cli, files: &[String]) -> CompileConfig {
+    let default_csv = CsvFormat::default();
+    let csv_format = CsvFormat {
+        delimiter: cli.csv_delimiter.unwrap_or(default_csv.delimiter),
+        quote: cli.csv_quote.unwrap_or(default_csv.quote),
+    };
+
+    // Unreadable input is reported when it is read properly
+    let detected = if cli.auto && !cli.csv && cli.input_delim.is_none() {
+        input_sample(files)
+            .ok()
+            .and_then(|sample| operators::detect_split_mode(&sample))
+    } else {
+        None
+    };
+
     let split_mode = if cli.csv {
-        SplitMode::Csv
+        SplitMode::Csv(csv_format)
     } else if let Some(ref delim) = cli.input_delim {
         SplitMode::Delimiter(delim.clone())
+    } else if let Some(ref widths) = cli.widths {
+        SplitMode::FixedWidth(widths.clone())
     } else {
-        SplitMode::Whitespace
+        detected.clone().unwrap_or_default()
     };
 
     let join_mode = if cli.csv {
-        JoinMode::Csv
+        JoinMode::Csv(csv_format)
     } else if let Some(ref delim) = cli.output_delim {
         JoinMode::Delimiter(delim.clone())
     } else {
-        JoinMode::Semantic
+        match detected {
+            Some(SplitMode::Csv(format)) => JoinMode::Csv(format),
+            Some(SplitMode::Delimiter(delim)) => JoinMode::Delimiter(delim),
+            _ => JoinMode::Semantic,
+        }
     };
 
     CompileConfig {
         split_mode,
         join_mode,
+        strict: cli.strict,
+        strict_select: cli.strict_select,
+        // Pick the seed once so interactive mode shows a stable sample while typing
+        seed: Some(cli.seed.unwrap_or_else(operators::random_seed)),
+        header: None,
     }
 }
 
 fn run_interactive(
-    input: Array,
+    mut mode: interactive::InteractiveMode,
     files: &[String],
     print_command: bool,
-    json: bool,
-    debug: bool,
     config: &CompileConfig,
-) {
-    let mut mode =
-        interactive::InteractiveMode::new_with_config(input, json, debug, config.clone());
+    numbers: &NumberFormat,
+    json_style: JsonStyle,
+    trace: bool,
+) -> Result<bool, String> {
     match mode.run() {
         Ok(Some((prog, json, debug))) => {
             // User committed - run full programme on full input
             let input = mode.full_input();
-            run_batch(&prog, input, json, debug, config);
+            let style = json.then_some(json_style);
+            let found = run_batch(&prog, input, style, debug, config, numbers, trace)?;
 
             // Print equivalent command line
             if print_command {
-                eprint!("t");
-                if json {
-                    eprint!(" -j");
-                }
-                eprint!(" '{}'", prog);
-                for file in files {
-                    if file.contains(char::is_whitespace) || file.contains('\'') {
-                        eprint!(" '{}'", file.replace('\'', "'\\''"));
-                    } else {
-                        eprint!(" {}", file);
-                    }
-                }
-                eprintln!();
+                eprintln!("{}", equivalent_command(&prog, style, debug, files));
             }
+            Ok(found)
         }
         Ok(None) => {
             // User cancelled
+            Ok(true)
         }
-        Err(e) => {
-            eprintln!("Error: {}", e);
-            std::process::exit(1);
-        }
+        Err(e) => Err(format!("Error: {}", e)),
     }
 }
 
-fn run_batch(prog: &str, array: Array, json: bool, debug: bool, config: &CompileConfig) {
-    let programme = match parser::parse_programme(prog) {
-        Ok(p) => p,
-        Err(e) => {
-            eprintln!("{}", e);
-            std::process::exit(1);
+/// The command line that runs `prog` as the interactive session did.
+fn equivalent_command(
+    prog: &str,
+    json: Option<JsonStyle>,
+    debug: bool,
+    files: &[String],
+) -> String {
+    let mut command = "t".to_string();
+    if let Some(style) = json {
+        command.push(' ');
+        command.push_str(&style.flag());
+    }
+    if debug {
+        command.push_str(" --debug");
+    }
+    command.push_str(&format!(" '{}'", prog));
+    for file in files {
+        if file.contains(char::is_whitespace) || file.contains('\'') {
+            command.push_str(&format!(" '{}'", file.replace('\'', "'\\''")));
+        } else {
+            command.push(' ');
+            command.push_str(file);
         }
-    };
+    }
+    command
+}
 
-    let ops = match interpreter::compile_with_config(&programme, config) {
-        Ok(o) => o,
-        Err(e) => {
-            eprintln!("Error: {}", e);
-            std::process::exit(1);
-        }
-    };
+/// Run a programme over the input and print the result. Returns whether the
+/// result was non-empty, for `--exit-status`.
+fn run_batch(
+    prog: &str,
+    array: Array,
+    json: Option<JsonStyle>,
+    debug: bool,
+    config: &CompileConfig,
+    numbers: &NumberFormat,
+    trace: bool,
+) -> Result<bool, String> {
+    let programme = parser::parse_programme(prog)?;
+    let ops = interpreter::compile_with_config(&programme, config)
+        .map_err(|e| format!("Error: {}", e))?;
     let mut ctx = Context::new(Value::Array(array));
-
-    if let Err(e) = interpreter::run(&ops, &mut ctx) {
-        eprintln!("Error: {}", e);
-        std::process::exit(1);
-    }
+    let result = if trace {
+        let mut stderr = io::stderr().lock();
+        interpreter::run_with(&ops, &mut ctx, |i, ctx| {
+            let op = &programme.operators[i];
+            // Tracing is best effort, so a closed stderr doesn't stop the run
+            let _ = interactive::write_trace(&mut stderr, op, ctx.value());
+        })
+    } else {
+        interpreter::run(&ops, &mut ctx)
+    };
+    result.map_err(|e| format!("Error: {}", e))?;
 
     let value = ctx.into_value();
     let stdout = io::stdout();
     let use_color = stdout.is_terminal();
     let mut handle = stdout.lock();
     let result = if debug {
-        interactive::write_json_debug(&mut handle, &value, use_color)
+        interactive::write_json_debug(&mut handle, &value, use_color, numbers)
             .and_then(|()| writeln!(handle))
-    } else if json {
-        interactive::write_json_highlighted(&mut handle, &value, use_color)
+    } else if let Some(style) = json {
+        interactive::write_json(&mut handle, &value, use_color, numbers, style)
             .and_then(|()| writeln!(handle))
     } else {
-        write!(handle, "{}", value).and_then(|()| writeln!(handle))
+        write!(handle, "{}", value.formatted(numbers)).and_then(|()| writeln!(handle))
     };
-    if let Err(e) = result
-        && e.kind() != io::ErrorKind::BrokenPipe
-    {
-        eprintln!("write failed: {}", e);
-        std::process::exit(1);
+    match result {
+        Err(e) if e.kind() != io::ErrorKind::BrokenPipe => Err(format!("write failed: {}", e)),
+        _ => Ok(found(&value)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn equivalent_command_includes_output_flags() {
+        let files = ["log.txt".to_string(), "my file".to_string()];
+        assert_eq!(
+            equivalent_command("s@1^", None, false, &files),
+            "t 's@1^' log.txt 'my file'"
+        );
+        assert_eq!(
+            equivalent_command("s", Some(JsonStyle::Lines), true, &[]),
+            "t -j --debug 's'"
+        );
+        assert_eq!(
+            equivalent_command("s", None, true, &["it's".to_string()]),
+            "t --debug 's' 'it'\\''s'"
+        );
+    }
+
+    #[test]
+    fn error_line_as_json() {
+        let message = "Error: --zip needs input files";
+        assert_eq!(error_line(message, false), message);
+        assert_eq!(
+            error_line(message, true),
+            r#"{"error":"--zip needs input files"}"#
+        );
+        // Parse errors have no prefix, and span lines
+        let parse_error = parser::parse_programme("?/x/").unwrap_err();
+        let line = error_line(&parse_error, true);
+        assert!(!line.contains('\n'), "{}", line);
+        let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(value["error"], parse_error.trim_end());
+    }
+
+    #[test]
+    fn exit_code_for_empty_output() {
+        assert_eq!(exit_code(true, false), 0);
+        assert_eq!(exit_code(false, false), 0);
+        assert_eq!(exit_code(true, true), 0);
+        assert_eq!(exit_code(false, true), 1);
+    }
+
+    #[test]
+    fn found_means_non_empty() {
+        let lines = |lines: &[&str]| {
+            Value::Array(Array::from((
+                lines.iter().map(|s| Value::Text(s.to_string())).collect(),
+                Level::Line,
+            )))
+        };
+        assert!(found(&lines(&["a"])));
+        assert!(found(&lines(&[""])));
+        assert!(!found(&lines(&[])));
+        assert!(!found(&Value::Text(String::new())));
+        assert!(found(&Value::Number(0.0)));
+        assert!(found(&Value::Bool(false)));
     }
 }
@@ -0,0 +1,354 @@
+use std::collections::HashMap;
+
+use crate::ast::{AggFunc, Selection};
+use crate::error::{Error, Result};
+use crate::interpreter::Transform;
+use crate::value::{Array, Level, Value};
+
+use super::dedupe::value_to_key;
+use super::group::extract_key;
+
+/// Running totals for a single group.
+#[derive(Default)]
+struct Accumulator {
+    rows: usize,
+    numeric: usize,
+    sum: f64,
+    min: Option<f64>,
+    max: Option<f64>,
+}
+
+impl Accumulator {
+    fn add(&mut self, value: Option<f64>) {
+        self.rows += 1;
+        if let Some(n) = value {
+            self.numeric += 1;
+            self.sum += n;
+            self.min = Some(self.min.map_or(n, |m| m.min(n)));
+            self.max = Some(self.max.map_or(n, |m| m.max(n)));
+        }
+    }
+
+    /// Compute one aggregate. Mean, min and max of a group without any
+    /// numeric values are empty.
+    fn result(&self, func: AggFunc) -> Value {
+        let number_or_empty = |n: Option<f64>| match n {
+            Some(n) => Value::Number(n),
+            None => Value::Text(String::new()),
+        };
+        match func {
+            AggFunc::Count => Value::Number(self.rows as f64),
+            AggFunc::Sum => Value::Number(self.sum),
+            AggFunc::Mean => {
+                number_or_empty((self.numeric > 0).then(|| self.sum / self.numeric as f64))
+            }
+            AggFunc::Min => number_or_empty(self.min),
+            AggFunc::Max => number_or_empty(self.max),
+        }
+    }
+}
+
+pub struct Agg {
+    key: Selection,
+    funcs: Vec<AggFunc>,
+    value: Option<Selection>,
+}
+
+impl Agg {
+    pub fn new(key: Selection, funcs: Vec<AggFunc>, value: Option<Selection>) -> Self {
+        Self { key, funcs, value }
+    }
+}
+
+impl Transform for Agg {
+    fn apply(&self, value: Value) -> Result<Value> {
+        match value {
+            Value::Array(arr) => {
+                let mut groups: Vec<(Value, Accumulator)> = Vec::new();
+                let mut key_indices: HashMap<String, usize> = HashMap::new();
+
+                for elem in &arr.elements {
+                    let key = extract_key(elem, &self.key)?;
+                    let number = match &self.value {
+                        Some(sel) => extract_key(elem, sel)?.as_number(),
+                        None => elem.as_number(),
+                    };
+
+                    let key_str = value_to_key(&key);
+                    let idx = match key_indices.get(&key_str) {
+                        Some(&idx) => idx,
+                        None => {
+                            key_indices.insert(key_str, groups.len());
+                            groups.push((key, Accumulator::default()));
+                            groups.len() - 1
+                        }
+                    };
+                    groups[idx].1.add(number);
+                }
+
+                let elements: Vec<Value> = groups
+                    .into_iter()
+                    .map(|(key, acc)| {
+                        let mut row = Vec::with_capacity(self.funcs.len() + 1);
+                        row.push(key);
+                        row.extend(self.funcs.iter().map(|&f| acc.result(f)));
+                        Value::Array(Array::from((row, Level::Word)))
+                    })
+                    .collect();
+
+                Ok(Value::Array(Array::from((elements, arr.level))))
+            }
+            other => Ok(other),
+        }
+    }
+
+    fn requires_full_input(&self) -> bool {
+        true
+    }
+}
+
+/// Aggregates each group of the `[key, [rows]]` shape produced by `g`.
+pub struct GroupAgg {
+    funcs: Vec<AggFunc>,
+    value: Option<Selection>,
+}
+
+impl GroupAgg {
+    pub fn new(funcs: Vec<AggFunc>, value: Option<Selection>) -> Self {
+        Self { funcs, value }
+    }
+}
+
+impl Transform for GroupAgg {
+    fn apply(&self, value: Value) -> Result<Value> {
+        match value {
+            Value::Array(arr) => {
+                let mut elements = Vec::with_capacity(arr.elements.len());
+                for group in arr.elements {
+                    let mut parts = match group {
+                        Value::Array(group) if group.len() == 2 => group.elements,
+                        _ => {
+                            return Err(Error::runtime(
+                                "G expects groups of [key, [rows]] (use g first)",
+                            ));
+                        }
+                    };
+                    let Value::Array(rows) = parts.pop().unwrap() else {
+                        return Err(Error::runtime(
+                            "G expects groups of [key, [rows]] (use g first)",
+                        ));
+                    };
+                    let key = parts.pop().unwrap();
+
+                    let mut acc = Accumulator::default();
+                    for row in &rows.elements {
+                        acc.add(match &self.value {
+                            Some(sel) => extract_key(row, sel)?.as_number(),
+                            None => row.as_number(),
+                        });
+                    }
+
+                    let mut row = Vec::with_capacity(self.funcs.len() + 1);
+                    row.push(key);
+                    row.extend(self.funcs.iter().map(|&f| acc.result(f)));
+                    elements.push(Value::Array(Array::from((row, Level::Word))));
+                }
+                Ok(Value::Array(Array::from((elements, arr.level))))
+            }
+            other => Ok(other),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::SelectItem;
+    use crate::operators::GroupBy;
+
+    fn text(s: &str) -> Value {
+        Value::Text(s.to_string())
+    }
+
+    fn rows(rows: &[&[&str]]) -> Value {
+        Value::Array(Array::from((
+            rows.iter()
+                .map(|r| {
+                    Value::Array(Array::from((
+                        r.iter().map(|s| text(s)).collect(),
+                        Level::Word,
+                    )))
+                })
+                .collect(),
+            Level::Line,
+        )))
+    }
+
+    fn index(i: i64) -> Selection {
+        Selection {
+            items: vec![SelectItem::Index(i)],
+        }
+    }
+
+    fn row_values(value: &Value) -> Vec<&Value> {
+        match value {
+            Value::Array(arr) => arr.elements.iter().collect(),
+            _ => panic!("expected row array"),
+        }
+    }
+
+    #[test]
+    fn agg_count_and_sum_per_key() {
+        let input = rows(&[
+            &["GET", "/a", "10"],
+            &["POST", "/b", "5"],
+            &["GET", "/c", "30"],
+            &["GET", "/d", "2"],
+        ]);
+        let agg = Agg::new(index(0), vec![AggFunc::Count, AggFunc::Sum], Some(index(2)));
+        let result = agg.apply(input).unwrap();
+
+        match result {
+            Value::Array(arr) => {
+                assert_eq!(arr.len(), 2);
+                assert_eq!(
+                    row_values(&arr.elements[0]),
+                    vec![&text("GET"), &Value::Number(3.0), &Value::Number(42.0)]
+                );
+                assert_eq!(
+                    row_values(&arr.elements[1]),
+                    vec![&text("POST"), &Value::Number(1.0), &Value::Number(5.0)]
+                );
+            }
+            _ => panic!("expected array"),
+        }
+    }
+
+    #[test]
+    fn agg_preserves_first_seen_order() {
+        let input = rows(&[&["b", "1"], &["a", "1"], &["c", "1"], &["a", "1"]]);
+        let agg = Agg::new(index(0), vec![AggFunc::Count], None);
+        let result = agg.apply(input).unwrap();
+
+        match result {
+            Value::Array(arr) => {
+                let keys: Vec<&Value> = arr.elements.iter().map(|r| row_values(r)[0]).collect();
+                assert_eq!(keys, vec![&text("b"), &text("a"), &text("c")]);
+            }
+            _ => panic!("expected array"),
+        }
+    }
+
+    #[test]
+    fn agg_mean_min_max() {
+        let input = rows(&[&["x", "4"], &["x", "1"], &["x", "7"]]);
+        let agg = Agg::new(
+            index(0),
+            vec![AggFunc::Mean, AggFunc::Min, AggFunc::Max],
+            Some(index(1)),
+        );
+        let result = agg.apply(input).unwrap();
+
+        match result {
+            Value::Array(arr) => {
+                assert_eq!(
+                    row_values(&arr.elements[0]),
+                    vec![
+                        &text("x"),
+                        &Value::Number(4.0),
+                        &Value::Number(1.0),
+                        &Value::Number(7.0)
+                    ]
+                );
+            }
+            _ => panic!("expected array"),
+        }
+    }
+
+    #[test]
+    fn agg_skips_non_numeric_values() {
+        let input = rows(&[&["x", "n/a"], &["x", "3"], &["y", "-"]]);
+        let agg = Agg::new(
+            index(0),
+            vec![AggFunc::Count, AggFunc::Sum, AggFunc::Mean],
+            Some(index(1)),
+        );
+        let result = agg.apply(input).unwrap();
+
+        match result {
+            Value::Array(arr) => {
+                assert_eq!(
+                    row_values(&arr.elements[0]),
+                    vec![
+                        &text("x"),
+                        &Value::Number(2.0),
+                        &Value::Number(3.0),
+                        &Value::Number(3.0)
+                    ]
+                );
+                assert_eq!(
+                    row_values(&arr.elements[1]),
+                    vec![
+                        &text("y"),
+                        &Value::Number(1.0),
+                        &Value::Number(0.0),
+                        &text("")
+                    ]
+                );
+            }
+            _ => panic!("expected array"),
+        }
+    }
+
+    #[test]
+    fn group_agg_counts_each_group() {
+        let input = rows(&[&["a", "1"], &["b", "2"], &["a", "3"]]);
+        let grouped = GroupBy::new(index(0)).apply(input).unwrap();
+        let result = GroupAgg::new(vec![AggFunc::Count], None)
+            .apply(grouped)
+            .unwrap();
+
+        match result {
+            Value::Array(arr) => {
+                assert_eq!(
+                    row_values(&arr.elements[0]),
+                    vec![&text("a"), &Value::Number(2.0)]
+                );
+                assert_eq!(
+                    row_values(&arr.elements[1]),
+                    vec![&text("b"), &Value::Number(1.0)]
+                );
+            }
+            _ => panic!("expected array"),
+        }
+    }
+
+    #[test]
+    fn group_agg_sum_and_mean_of_column() {
+        let input = rows(&[&["a", "1"], &["b", "2"], &["a", "3"]]);
+        let grouped = GroupBy::new(index(0)).apply(input).unwrap();
+        let result = GroupAgg::new(vec![AggFunc::Sum, AggFunc::Mean], Some(index(1)))
+            .apply(grouped)
+            .unwrap();
+
+        match result {
+            Value::Array(arr) => {
+                assert_eq!(
+                    row_values(&arr.elements[0]),
+                    vec![&text("a"), &Value::Number(4.0), &Value::Number(2.0)]
+                );
+            }
+            _ => panic!("expected array"),
+        }
+    }
+
+    #[test]
+    fn group_agg_rejects_ungrouped_input() {
+        let input = rows(&[&["a", "1", "x"]]);
+        assert!(
+            GroupAgg::new(vec![AggFunc::Count], None)
+                .apply(input)
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,97 @@
+use regex::Regex;
+
+use crate::error::Result;
+use crate::interpreter::Transform;
+use crate::value::Value;
+
+/// Control sequences (`ESC [` ... final byte), operating system commands
+/// (`ESC ]` ... BEL or `ESC \`), and other two-character escapes.
+const ANSI_ESCAPE: &str =
+    r"\x1b\[[0-?]*[ -/]*[@-~]|\x1b\][^\x07\x1b]*(?:\x07|\x1b\\)|\x1b[@-Z\\^_]";
+
+/// Removes ANSI escape sequences, such as colours and cursor movement, from
+/// text, recursing through arrays. An escape character that doesn't start a
+/// complete sequence is left alone.
+pub struct StripAnsi {
+    pattern: Regex,
+}
+
+impl StripAnsi {
+    pub fn new() -> Self {
+        Self {
+            pattern: Regex::new(ANSI_ESCAPE).expect("ANSI escape pattern is valid"),
+        }
+    }
+}
+
+impl Default for StripAnsi {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Transform for StripAnsi {
+    fn apply(&self, value: Value) -> Result<Value> {
+        match value {
+            Value::Array(mut arr) => {
+                arr.elements = arr
+                    .elements
+                    .into_iter()
+                    .map(|v| self.apply(v))
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(Value::Array(arr))
+            }
+            Value::Text(s) if s.contains('\x1b') => {
+                Ok(Value::Text(self.pattern.replace_all(&s, "").into_owned()))
+            }
+            other => Ok(other),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value::{Array, Level};
+
+    fn text(s: &str) -> Value {
+        Value::Text(s.to_string())
+    }
+
+    fn strip(s: &str) -> Value {
+        StripAnsi::new().apply(text(s)).unwrap()
+    }
+
+    #[test]
+    fn strip_colours() {
+        assert_eq!(strip("\x1b[31merror\x1b[0m: disk"), text("error: disk"));
+        assert_eq!(strip("\x1b[1;38;5;208mwarn\x1b[m"), text("warn"));
+    }
+
+    #[test]
+    fn strip_cursor_movement_and_titles() {
+        assert_eq!(strip("\x1b[2K\x1b[1Gdone\x1b[?25h"), text("done"));
+        assert_eq!(strip("\x1b]0;title\x07shell\x1b]8;;\x1b\\"), text("shell"));
+        assert_eq!(strip("a\x1bMb"), text("ab"));
+    }
+
+    #[test]
+    fn strip_leaves_incomplete_escapes() {
+        assert_eq!(strip("a\x1bxb"), text("a\x1bxb"));
+        assert_eq!(strip("end\x1b"), text("end\x1b"));
+        assert_eq!(strip("\x1b[12"), text("\x1b[12"));
+    }
+
+    #[test]
+    fn strip_recurses() {
+        let input = Value::Array(Array::from((
+            vec![text("\x1b[32mok\x1b[0m"), Value::Number(1.0)],
+            Level::Line,
+        )));
+        let expected = Value::Array(Array::from((
+            vec![text("ok"), Value::Number(1.0)],
+            Level::Line,
+        )));
+        assert_eq!(StripAnsi::new().apply(input).unwrap(), expected);
+    }
+}
@@ -0,0 +1,331 @@
+use crate::ast::{ArithOp, Selection};
+use crate::error::{Error, Result};
+use crate::interpreter::Transform;
+use crate::value::{Array, Value};
+
+use super::group::extract_key;
+
+/// Replaces each row with the result of `a <op> b` on two of its selected fields,
+/// e.g. `=2-1` for awk's `$3-$2`.
+pub struct Arithmetic {
+    a: Selection,
+    op: ArithOp,
+    b: Selection,
+    strict: bool,
+}
+
+impl Arithmetic {
+    /// In strict mode, a missing or non-numeric field is an error instead of
+    /// giving an empty result.
+    pub fn new(a: Selection, op: ArithOp, b: Selection, strict: bool) -> Self {
+        Self { a, op, b, strict }
+    }
+
+    fn operand(&self, row: &Value, sel: &Selection) -> Result<Option<f64>> {
+        let field = match extract_key(row, sel) {
+            Ok(field) => field,
+            Err(_) if !self.strict => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        match field.as_number() {
+            Some(n) => Ok(Some(n)),
+            None if self.strict => Err(Error::runtime(format!(
+                "cannot do arithmetic on non-numeric value '{}'",
+                field
+            ))),
+            None => Ok(None),
+        }
+    }
+
+    fn evaluate(&self, row: &Value) -> Result<Value> {
+        let a = self.operand(row, &self.a)?;
+        let b = self.operand(row, &self.b)?;
+        let (Some(a), Some(b)) = (a, b) else {
+            return Ok(Value::Text(String::new()));
+        };
+        let result = match self.op {
+            ArithOp::Add => a + b,
+            ArithOp::Sub => a - b,
+            ArithOp::Mul => a * b,
+            ArithOp::Div if b == 0.0 => {
+                return Err(Error::runtime(format!("division by zero: {} / {}", a, b)));
+            }
+            ArithOp::Div => a / b,
+        };
+        Ok(Value::Number(result))
+    }
+}
+
+impl Transform for Arithmetic {
+    fn apply(&self, value: Value) -> Result<Value> {
+        match value {
+            Value::Array(arr) => {
+                let elements = arr
+                    .elements
+                    .iter()
+                    .map(|row| self.evaluate(row))
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(Value::Array(Array::from((elements, arr.level))))
+            }
+            other => Ok(other),
+        }
+    }
+}
+
+/// Apply `f` to every number and numeric text, recursing through arrays.
+/// Other text is left unchanged, or is an error in strict mode.
+fn map_numbers<F>(value: Value, strict: bool, f: &F) -> Result<Value>
+where
+    F: Fn(f64) -> f64,
+{
+    match value {
+        Value::Array(mut arr) => {
+            arr.elements = arr
+                .elements
+                .into_iter()
+                .map(|v| map_numbers(v, strict, f))
+                .collect::<Result<_>>()?;
+            Ok(Value::Array(arr))
+        }
+        Value::Number(n) => Ok(Value::Number(f(n))),
+        Value::Text(s) => match s.parse::<f64>() {
+            Ok(n) => Ok(Value::Number(f(n))),
+            Err(_) if strict => Err(Error::runtime(format!(
+                "cannot do arithmetic on non-numeric value '{}'",
+                s
+            ))),
+            Err(_) => Ok(Value::Text(s)),
+        },
+        Value::Bool(b) if strict => Err(Error::runtime(format!(
+            "cannot do arithmetic on non-numeric value '{}'",
+            b
+        ))),
+        Value::Bool(b) => Ok(Value::Bool(b)),
+    }
+}
+
+/// `+{<number>}` - adds a constant to every number, e.g. `+{-273.15}`.
+pub struct AddScalar {
+    operand: f64,
+    strict: bool,
+}
+
+impl AddScalar {
+    /// In strict mode, non-numeric text is an error instead of being left
+    /// unchanged.
+    pub fn new(operand: f64, strict: bool) -> Self {
+        Self { operand, strict }
+    }
+}
+
+impl Transform for AddScalar {
+    fn apply(&self, value: Value) -> Result<Value> {
+        map_numbers(value, self.strict, &|n| n + self.operand)
+    }
+}
+
+/// `*{<number>}` - multiplies every number by a constant, e.g. `*{0.001}`.
+pub struct MulScalar {
+    operand: f64,
+    strict: bool,
+}
+
+impl MulScalar {
+    /// In strict mode, non-numeric text is an error instead of being left
+    /// unchanged.
+    pub fn new(operand: f64, strict: bool) -> Self {
+        Self { operand, strict }
+    }
+}
+
+impl Transform for MulScalar {
+    fn apply(&self, value: Value) -> Result<Value> {
+        map_numbers(value, self.strict, &|n| n * self.operand)
+    }
+}
+
+/// `&clamp<min>:<max>` - limits every number to a range, e.g. `&clamp0:100`.
+pub struct Clamp {
+    min: f64,
+    max: f64,
+    strict: bool,
+}
+
+impl Clamp {
+    /// Fails if `min` is greater than `max`. In strict mode, non-numeric text
+    /// is an error instead of being left unchanged.
+    pub fn new(min: f64, max: f64, strict: bool) -> Result<Self> {
+        if min > max {
+            return Err(Error::runtime(format!(
+                "clamp minimum {} is greater than maximum {}",
+                min, max
+            )));
+        }
+        Ok(Self { min, max, strict })
+    }
+}
+
+impl Transform for Clamp {
+    fn apply(&self, value: Value) -> Result<Value> {
+        map_numbers(value, self.strict, &|n| n.clamp(self.min, self.max))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::SelectItem;
+    use crate::value::Level;
+
+    fn text(s: &str) -> Value {
+        Value::Text(s.to_string())
+    }
+
+    fn rows(rows: &[&[&str]]) -> Value {
+        Value::Array(Array::from((
+            rows.iter()
+                .map(|r| {
+                    Value::Array(Array::from((
+                        r.iter().map(|s| text(s)).collect(),
+                        Level::Word,
+                    )))
+                })
+                .collect(),
+            Level::Line,
+        )))
+    }
+
+    fn index(i: i64) -> Selection {
+        Selection {
+            items: vec![SelectItem::Index(i)],
+        }
+    }
+
+    fn results(value: Value) -> Vec<Value> {
+        match value {
+            Value::Array(arr) => arr.elements,
+            _ => panic!("expected array"),
+        }
+    }
+
+    #[test]
+    fn arithmetic_grid() {
+        let pairs: &[&[&str]] = &[&["6", "3"], &["-2", "4"], &["1.5", "0.5"], &["0", "7"]];
+        let expected: &[(ArithOp, [f64; 4])] = &[
+            (ArithOp::Add, [9.0, 2.0, 2.0, 7.0]),
+            (ArithOp::Sub, [3.0, -6.0, 1.0, -7.0]),
+            (ArithOp::Mul, [18.0, -8.0, 0.75, 0.0]),
+            (ArithOp::Div, [2.0, -0.5, 3.0, 0.0]),
+        ];
+        for &(op, want) in expected {
+            let result = Arithmetic::new(index(0), op, index(1), false)
+                .apply(rows(pairs))
+                .unwrap();
+            let want: Vec<Value> = want.iter().map(|&n| Value::Number(n)).collect();
+            assert_eq!(results(result), want, "{:?}", op);
+        }
+    }
+
+    #[test]
+    fn arithmetic_column_order_and_negative_index() {
+        let input = rows(&[&["a", "10", "25"], &["b", "3", "4"]]);
+        let result = Arithmetic::new(index(-1), ArithOp::Sub, index(1), false)
+            .apply(input)
+            .unwrap();
+        assert_eq!(
+            results(result),
+            vec![Value::Number(15.0), Value::Number(1.0)]
+        );
+    }
+
+    #[test]
+    fn arithmetic_non_numeric_or_missing_is_empty() {
+        let input = rows(&[&["x", "1"], &["2"], &["3", "4"]]);
+        let result = Arithmetic::new(index(0), ArithOp::Add, index(1), false)
+            .apply(input)
+            .unwrap();
+        assert_eq!(
+            results(result),
+            vec![text(""), text(""), Value::Number(7.0)]
+        );
+    }
+
+    #[test]
+    fn arithmetic_strict_rejects_non_numeric() {
+        let input = rows(&[&["x", "1"]]);
+        let err = Arithmetic::new(index(0), ArithOp::Add, index(1), true)
+            .apply(input)
+            .unwrap_err();
+        assert!(err.to_string().contains("'x'"));
+    }
+
+    #[test]
+    fn arithmetic_division_by_zero_fails() {
+        let input = rows(&[&["4", "2"], &["5", "0"]]);
+        let err = Arithmetic::new(index(0), ArithOp::Div, index(1), false)
+            .apply(input)
+            .unwrap_err();
+        assert!(err.to_string().contains("division by zero"));
+    }
+
+    #[test]
+    fn scalar_ops_recurse_and_skip_text() {
+        let input = rows(&[&["x", "2"], &["1.5", "-4"]]);
+        let result = MulScalar::new(2.0, false).apply(input).unwrap();
+        assert_eq!(
+            results(result),
+            vec![
+                Value::Array(Array::from((
+                    vec![text("x"), Value::Number(4.0)],
+                    Level::Word
+                ))),
+                Value::Array(Array::from((
+                    vec![Value::Number(3.0), Value::Number(-8.0)],
+                    Level::Word
+                ))),
+            ]
+        );
+        assert_eq!(
+            AddScalar::new(-0.5, false)
+                .apply(Value::Number(2.0))
+                .unwrap(),
+            Value::Number(1.5)
+        );
+    }
+
+    #[test]
+    fn scalar_ops_strict_rejects_non_numeric() {
+        let input = rows(&[&["1", "x"]]);
+        let err = AddScalar::new(1.0, true).apply(input).unwrap_err();
+        assert!(err.to_string().contains("'x'"));
+    }
+
+    #[test]
+    fn clamp_limits_numbers() {
+        let input = rows(&[&["-5", "50", "150"], &["x", "100"]]);
+        let result = Clamp::new(0.0, 100.0, false).unwrap().apply(input).unwrap();
+        assert_eq!(
+            results(result),
+            vec![
+                Value::Array(Array::from((
+                    vec![
+                        Value::Number(0.0),
+                        Value::Number(50.0),
+                        Value::Number(100.0)
+                    ],
+                    Level::Word
+                ))),
+                Value::Array(Array::from((
+                    vec![text("x"), Value::Number(100.0)],
+                    Level::Word
+                ))),
+            ]
+        );
+    }
+
+    #[test]
+    fn clamp_rejects_inverted_range() {
+        assert!(Clamp::new(10.0, 1.0, false).is_err());
+        assert!(Clamp::new(1.0, 1.0, false).is_ok());
+    }
+}
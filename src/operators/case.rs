@@ -1,4 +1,4 @@
-use crate::ast::Selection;
+use crate::ast::{CaseStyle, Selection};
 use crate::error::Result;
 use crate::interpreter::Transform;
 use crate::value::Value;
@@ -20,6 +20,7 @@ impl Transform for Lowercase {
             }
             Value::Text(s) => Ok(Value::Text(s.to_lowercase())),
             Value::Number(n) => Ok(Value::Number(n)),
+            Value::Bool(b) => Ok(Value::Bool(b)),
         }
     }
 }
@@ -58,6 +59,7 @@ impl Transform for Uppercase {
             }
             Value::Text(s) => Ok(Value::Text(s.to_uppercase())),
             Value::Number(n) => Ok(Value::Number(n)),
+            Value::Bool(b) => Ok(Value::Bool(b)),
         }
     }
 }
@@ -81,6 +83,137 @@ impl Transform for UppercaseSelected {
     }
 }
 
+/// Split an identifier into words. Words end at spaces, underscores and
+/// hyphens, before an uppercase letter that follows a lowercase letter or
+/// digit (`fooBar`, `utf8Decoder`), and before the last capital of a run
+/// followed by lowercase (`HTTPServer` is `HTTP` and `Server`).
+fn identifier_words(s: &str) -> Vec<String> {
+    let chars: Vec<char> = s.chars().collect();
+    let mut words = Vec::new();
+    let mut word = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_whitespace() || c == '_' || c == '-' {
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            continue;
+        }
+        if !word.is_empty() && c.is_uppercase() {
+            let prev = chars[i - 1];
+            let next_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            if prev.is_lowercase() || prev.is_numeric() || (prev.is_uppercase() && next_lower) {
+                words.push(std::mem::take(&mut word));
+            }
+        }
+        word.push(c);
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+/// Capitalise the first letter and lowercase the rest.
+fn capitalise(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first
+            .to_uppercase()
+            .chain(chars.flat_map(char::to_lowercase))
+            .collect(),
+        None => String::new(),
+    }
+}
+
+/// Rewrites identifiers as `snake_case`, `camelCase` or `kebab-case`.
+pub struct ConvertCase {
+    style: CaseStyle,
+}
+
+impl ConvertCase {
+    pub fn new(style: CaseStyle) -> Self {
+        Self { style }
+    }
+
+    fn convert(&self, s: &str) -> String {
+        let words = identifier_words(s);
+        match self.style {
+            CaseStyle::Snake => words.join("_").to_lowercase(),
+            CaseStyle::Kebab => words.join("-").to_lowercase(),
+            CaseStyle::Camel => words
+                .iter()
+                .enumerate()
+                .map(|(i, w)| {
+                    if i == 0 {
+                        w.to_lowercase()
+                    } else {
+                        capitalise(w)
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Transform for ConvertCase {
+    fn apply(&self, value: Value) -> Result<Value> {
+        match value {
+            Value::Array(mut arr) => {
+                arr.elements = arr
+                    .elements
+                    .into_iter()
+                    .map(|v| self.apply(v))
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(Value::Array(arr))
+            }
+            Value::Text(s) => Ok(Value::Text(self.convert(&s))),
+            Value::Number(n) => Ok(Value::Number(n)),
+            Value::Bool(b) => Ok(Value::Bool(b)),
+        }
+    }
+}
+
+fn slugify(s: &str) -> String {
+    let mut slug = String::with_capacity(s.len());
+    let mut pending_hyphen = false;
+    for c in s.chars() {
+        if c.is_alphanumeric() {
+            if pending_hyphen && !slug.is_empty() {
+                slug.push('-');
+            }
+            pending_hyphen = false;
+            slug.extend(c.to_lowercase());
+        } else if c != '\'' && c != '’' {
+            pending_hyphen = true;
+        }
+    }
+    slug
+}
+
+/// Rewrites text as a URL slug: lowercase letters and digits, with each run of
+/// anything else turned into a single hyphen and none at either end.
+/// Apostrophes are dropped so `don't` stays one word. Non-ASCII letters are
+/// kept as they are rather than transliterated.
+pub struct Slugify;
+
+impl Transform for Slugify {
+    fn apply(&self, value: Value) -> Result<Value> {
+        match value {
+            Value::Array(mut arr) => {
+                arr.elements = arr
+                    .elements
+                    .into_iter()
+                    .map(|v| self.apply(v))
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(Value::Array(arr))
+            }
+            Value::Text(s) => Ok(Value::Text(slugify(&s))),
+            Value::Number(n) => Ok(Value::Number(n)),
+            Value::Bool(b) => Ok(Value::Bool(b)),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -244,4 +377,75 @@ mod tests {
             _ => panic!("expected array"),
         }
     }
+
+    fn convert(style: CaseStyle, s: &str) -> String {
+        match ConvertCase::new(style).apply(text(s)).unwrap() {
+            Value::Text(s) => s,
+            _ => panic!("expected text"),
+        }
+    }
+
+    #[test]
+    fn identifier_word_boundaries() {
+        assert_eq!(
+            identifier_words("fooBar_baz-qux quux"),
+            ["foo", "Bar", "baz", "qux", "quux"]
+        );
+        assert_eq!(identifier_words("HTTPServer"), ["HTTP", "Server"]);
+        assert_eq!(identifier_words("parseURL"), ["parse", "URL"]);
+        assert_eq!(identifier_words("utf8Decoder"), ["utf8", "Decoder"]);
+        assert_eq!(identifier_words("__init__"), ["init"]);
+    }
+
+    #[test]
+    fn convert_case_round_trip() {
+        let forms = ["user_id_map", "userIdMap", "user-id-map"];
+        for form in forms {
+            assert_eq!(convert(CaseStyle::Snake, form), forms[0]);
+            assert_eq!(convert(CaseStyle::Camel, form), forms[1]);
+            assert_eq!(convert(CaseStyle::Kebab, form), forms[2]);
+        }
+    }
+
+    #[test]
+    fn convert_case_acronyms() {
+        assert_eq!(convert(CaseStyle::Snake, "HTTPServer"), "http_server");
+        assert_eq!(convert(CaseStyle::Camel, "HTTP server"), "httpServer");
+        assert_eq!(
+            convert(CaseStyle::Kebab, "getHTTPResponseCode"),
+            "get-http-response-code"
+        );
+    }
+
+    #[test]
+    fn convert_case_recurses() {
+        let result = ConvertCase::new(CaseStyle::Kebab)
+            .apply(line_array(&["Foo Bar", "bazQux"]))
+            .unwrap();
+        assert_eq!(result, line_array(&["foo-bar", "baz-qux"]));
+    }
+
+    #[test]
+    fn slugify_punctuation_and_spaces() {
+        let input = line_array(&[
+            "Hello, World!",
+            "  Rust   2024:  what's   new?  ",
+            "--already-a-slug--",
+            "C++ & Go",
+            "Crème brûlée",
+            "!!!",
+        ]);
+        let result = Slugify.apply(input).unwrap();
+        assert_eq!(
+            result,
+            line_array(&[
+                "hello-world",
+                "rust-2024-whats-new",
+                "already-a-slug",
+                "c-go",
+                "crème-brûlée",
+                "",
+            ])
+        );
+    }
 }
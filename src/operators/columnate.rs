@@ -2,17 +2,60 @@ use crate::error::Result;
 use crate::interpreter::Transform;
 use crate::value::{Array, Level, Value};
 
-pub struct Columnate;
+use super::histogram::truncate_label;
+
+/// Lays out rows as aligned columns, like `column -t`.
+///
+/// Columns of numbers are right-aligned, the rest left-aligned. A column counts
+/// as numeric when every cell below the first row is a number, so a header
+/// row doesn't stop its column aligning. The last cell of each row is never
+/// padded.
+///
+/// Rows are arrays of cells, joined by a space on output. With a separator,
+/// each row is instead the text of its cells joined by the separator. With a
+/// maximum width, longer cells are cut short with `…` so no column is wider.
+#[derive(Default)]
+pub struct Columnate {
+    max_width: Option<usize>,
+    separator: Option<String>,
+}
+
+impl Columnate {
+    pub fn new(max_width: Option<usize>, separator: Option<String>) -> Self {
+        Self {
+            max_width,
+            separator,
+        }
+    }
+}
 
 struct Cell {
     text: String,
     width: usize,
+    numeric: bool,
+}
+
+impl Cell {
+    fn new(value: Value, max_width: Option<usize>) -> Self {
+        let numeric = value.as_number().is_some();
+        let mut text = value_into_string(value);
+        if let Some(max) = max_width {
+            text = truncate_label(text, max);
+        }
+        let width = text.chars().count();
+        Cell {
+            text,
+            width,
+            numeric,
+        }
+    }
 }
 
 fn value_into_string(v: Value) -> String {
     match v {
         Value::Text(s) => s,
         Value::Number(n) => n.to_string(),
+        Value::Bool(b) => b.to_string(),
         Value::Array(arr) => arr.to_string(),
     }
 }
@@ -53,17 +96,9 @@ impl Transform for Columnate {
                         Value::Array(inner) => inner
                             .elements
                             .into_iter()
-                            .map(|v| {
-                                let text = value_into_string(v);
-                                let width = text.chars().count();
-                                Cell { text, width }
-                            })
+                            .map(|v| Cell::new(v, self.max_width))
                             .collect(),
-                        other => {
-                            let text = value_into_string(other);
-                            let width = text.chars().count();
-                            vec![Cell { text, width }]
-                        }
+                        other => vec![Cell::new(other, self.max_width)],
                     })
                     .collect();
 
@@ -78,34 +113,48 @@ impl Transform for Columnate {
                         col_widths[i] = col_widths[i].max(cell.width);
                     }
                 }
+                let right_align: Vec<bool> = (0..max_cols)
+                    .map(|i| {
+                        let mut cells = rows.iter().skip(1).filter_map(|r| r.get(i)).peekable();
+                        cells.peek().is_some() && cells.all(|c| c.numeric)
+                    })
+                    .collect();
 
                 let result_elements: Vec<Value> = rows
                     .into_iter()
                     .map(|row| {
                         let last_idx = row.len().saturating_sub(1);
-                        let cells: Vec<Value> = row
-                            .into_iter()
-                            .enumerate()
-                            .map(|(i, cell)| {
-                                if i == last_idx {
-                                    Value::Text(cell.text)
+                        let cells = row.into_iter().enumerate().map(|(i, cell)| {
+                            if i == last_idx {
+                                cell.text
+                            } else {
+                                let target_width = col_widths.get(i).copied().unwrap_or(0);
+                                let padding = target_width.saturating_sub(cell.width);
+                                if padding == 0 {
+                                    cell.text
+                                } else if right_align[i] {
+                                    let mut padded = " ".repeat(padding);
+                                    padded.push_str(&cell.text);
+                                    padded
                                 } else {
-                                    let target_width = col_widths.get(i).copied().unwrap_or(0);
-                                    let padding = target_width.saturating_sub(cell.width);
-                                    if padding == 0 {
-                                        Value::Text(cell.text)
-                                    } else {
-                                        let mut padded = cell.text;
-                                        padded.reserve(padding);
-                                        for _ in 0..padding {
-                                            padded.push(' ');
-                                        }
-                                        Value::Text(padded)
+                                    let mut padded = cell.text;
+                                    padded.reserve(padding);
+                                    for _ in 0..padding {
+                                        padded.push(' ');
                                     }
+                                    padded
                                 }
-                            })
-                            .collect();
-                        Value::Array(Array::from((cells, Level::Word)))
+                            }
+                        });
+                        match &self.separator {
+                            Some(separator) => {
+                                Value::Text(cells.collect::<Vec<_>>().join(separator))
+                            }
+                            None => Value::Array(Array::from((
+                                cells.map(Value::Text).collect(),
+                                Level::Word,
+                            ))),
+                        }
                     })
                     .collect();
 
@@ -145,7 +194,7 @@ mod tests {
             ],
             Level::Line,
         )));
-        let result = Columnate.apply(input).unwrap();
+        let result = Columnate::default().apply(input).unwrap();
         let expected = Value::Array(Array::from((
             vec![
                 row(vec!["name ", "age"]),
@@ -172,7 +221,7 @@ mod tests {
             ],
             Level::Line,
         )));
-        let result = Columnate.apply(input).unwrap();
+        let result = Columnate::default().apply(input).unwrap();
         let expected = Value::Array(Array::from((
             vec![
                 row(vec!["a   ", "bb", "ccc"]),
@@ -192,7 +241,7 @@ mod tests {
             )))],
             Level::Line,
         )));
-        let result = Columnate.apply(input).unwrap();
+        let result = Columnate::default().apply(input).unwrap();
         let expected = Value::Array(Array::from((
             vec![row(vec!["one", "two", "three"])],
             Level::Line,
@@ -210,7 +259,7 @@ mod tests {
             ],
             Level::Line,
         )));
-        let result = Columnate.apply(input).unwrap();
+        let result = Columnate::default().apply(input).unwrap();
         let expected = Value::Array(Array::from((
             vec![row(vec!["first"]), row(vec!["second"]), row(vec!["third"])],
             Level::Line,
@@ -221,7 +270,7 @@ mod tests {
     #[test]
     fn columnate_empty_array() {
         let input = Value::Array(Array::from((vec![], Level::Line)));
-        let result = Columnate.apply(input).unwrap();
+        let result = Columnate::default().apply(input).unwrap();
         let expected = Value::Array(Array::from((vec![], Level::Line)));
         assert_eq!(result, expected);
     }
@@ -245,12 +294,111 @@ mod tests {
             ],
             Level::Line,
         )));
-        let result = Columnate.apply(input).unwrap();
+        let result = Columnate::default().apply(input).unwrap();
         let expected = Value::Array(Array::from((
             vec![
                 row(vec!["count", "value"]),
-                row(vec!["42   ", "foo"]),
-                row(vec!["7    ", "bar"]),
+                row(vec!["   42", "foo"]),
+                row(vec!["    7", "bar"]),
+            ],
+            Level::Line,
+        )));
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn columnate_with_separator() {
+        let input = Value::Array(Array::from((
+            vec![
+                row(vec!["name", "qty", "note"]),
+                row(vec!["apple", "3"]),
+                row(vec!["fig", "12", "ripe"]),
+                row(vec!["kiwi"]),
+            ],
+            Level::Line,
+        )));
+        let result = Columnate::new(None, Some(" | ".to_string()))
+            .apply(input)
+            .unwrap();
+        // Ragged rows get only the separators between the cells they have
+        let expected = Value::Array(Array::from((
+            vec![
+                text("name  | qty | note"),
+                text("apple | 3"),
+                text("fig   |  12 | ripe"),
+                text("kiwi"),
+            ],
+            Level::Line,
+        )));
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn columnate_caps_column_width() {
+        let input = Value::Array(Array::from((
+            vec![
+                row(vec!["id", "description", "qty"]),
+                row(vec!["1", "a very long description", "3"]),
+                row(vec!["2", "short", "12"]),
+            ],
+            Level::Line,
+        )));
+        let result = Columnate::new(Some(8), None).apply(input).unwrap();
+        let expected = Value::Array(Array::from((
+            vec![
+                Value::Array(Array::from((
+                    vec![text("id"), text("descrip…"), text("qty")],
+                    Level::Word,
+                ))),
+                Value::Array(Array::from((
+                    vec![text(" 1"), text("a very …"), text("3")],
+                    Level::Word,
+                ))),
+                Value::Array(Array::from((
+                    vec![text(" 2"), text("short   "), text("12")],
+                    Level::Word,
+                ))),
+            ],
+            Level::Line,
+        )));
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn columnate_truncates_multibyte_cells() {
+        let input = Value::Array(Array::from((
+            vec![row(vec!["café…😀 crème", "x"]), row(vec!["née", "y"])],
+            Level::Line,
+        )));
+        let result = Columnate::new(Some(6), Some("|".to_string()))
+            .apply(input)
+            .unwrap();
+        // Widths are counted in chars, so the cut lands between characters
+        let expected = Value::Array(Array::from((
+            vec![text("café……|x"), text("née   |y")],
+            Level::Line,
+        )));
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn columnate_right_aligns_numeric_columns() {
+        let input = Value::Array(Array::from((
+            vec![
+                row(vec!["1.5", "apple", "3"]),
+                row(vec!["120", "fig", "10"]),
+                row(vec!["-7", "12", "x"]),
+            ],
+            Level::Line,
+        )));
+        let result = Columnate::default().apply(input).unwrap();
+        // A text cell keeps the second column left-aligned, and the last
+        // column is never padded
+        let expected = Value::Array(Array::from((
+            vec![
+                row(vec!["1.5", "apple", "3"]),
+                row(vec!["120", "fig  ", "10"]),
+                row(vec![" -7", "12   ", "x"]),
             ],
             Level::Line,
         )));
@@ -270,7 +418,7 @@ mod tests {
             ],
             Level::Line,
         )));
-        let result = Columnate.apply(input).unwrap();
+        let result = Columnate::default().apply(input).unwrap();
         let expected = Value::Array(Array::from((
             vec![
                 row(vec!["a", "b", "c"]),
@@ -288,7 +436,7 @@ mod tests {
             vec![text("hello"), text("world")],
             Level::Line,
         )));
-        let result = Columnate.apply(input).unwrap();
+        let result = Columnate::default().apply(input).unwrap();
         let expected = Value::Array(Array::from((
             vec![row(vec!["hello"]), row(vec!["world"])],
             Level::Line,
@@ -299,7 +447,7 @@ mod tests {
     #[test]
     fn columnate_non_array_is_identity() {
         let input = text("hello");
-        let result = Columnate.apply(input).unwrap();
+        let result = Columnate::default().apply(input).unwrap();
         assert_eq!(result, text("hello"));
     }
 
@@ -325,7 +473,7 @@ mod tests {
             ],
             Level::File,
         )));
-        let result = Columnate.apply(input).unwrap();
+        let result = Columnate::default().apply(input).unwrap();
         let expected = Value::Array(Array::from((
             vec![
                 row(vec!["a  ", "b"]),
@@ -0,0 +1,215 @@
+use crate::error::Result;
+use crate::interpreter::Transform;
+use crate::value::{Array, Level, Value};
+
+pub struct StripComments {
+    marker: String,
+    /// Keep lines that held only a comment, as blank lines
+    keep_empty: bool,
+}
+
+impl StripComments {
+    pub fn new(marker: String, keep_empty: bool) -> Self {
+        Self { marker, keep_empty }
+    }
+
+    /// Strip the comment from a line that has been split into words or
+    /// chars: the element with the marker is cut at it and every element
+    /// after it is dropped, so the whole comment goes, not just its first
+    /// word.
+    fn strip_row(&self, mut arr: Array) -> Result<Array> {
+        let mut elements = Vec::with_capacity(arr.elements.len());
+        for v in arr.elements {
+            let Value::Text(s) = v else {
+                elements.push(self.apply(v)?);
+                continue;
+            };
+            match self.find_marker(&s) {
+                Some(pos) => {
+                    let kept = s[..pos].trim_end();
+                    if !kept.is_empty() {
+                        elements.push(Value::Text(kept.to_string()));
+                    }
+                    break;
+                }
+                None => elements.push(Value::Text(s)),
+            }
+        }
+        arr.elements = elements;
+        Ok(arr)
+    }
+
+    /// Returns the byte offset of the first marker that is not inside a
+    /// double-quoted string. Single quotes are not treated as quoting, since
+    /// they show up unbalanced in prose (e.g. "don't").
+    fn find_marker(&self, s: &str) -> Option<usize> {
+        let mut in_quote = false;
+        let mut escaped = false;
+        for (i, c) in s.char_indices() {
+            if in_quote {
+                match c {
+                    _ if escaped => escaped = false,
+                    '\\' => escaped = true,
+                    '"' => in_quote = false,
+                    _ => {}
+                }
+            } else if s[i..].starts_with(&self.marker) {
+                return Some(i);
+            } else if c == '"' {
+                in_quote = true;
+            }
+        }
+        None
+    }
+}
+
+impl Transform for StripComments {
+    fn apply(&self, value: Value) -> Result<Value> {
+        match value {
+            Value::Array(arr) if matches!(arr.level, Level::Word | Level::Char) => {
+                Ok(Value::Array(self.strip_row(arr)?))
+            }
+            Value::Array(mut arr) => {
+                let mut elements = Vec::with_capacity(arr.elements.len());
+                for v in arr.elements {
+                    // Drop lines that held nothing but a comment, keep blank ones
+                    let was_empty = v.is_empty();
+                    let stripped = self.apply(v)?;
+                    if self.keep_empty || was_empty || !stripped.is_empty() {
+                        elements.push(stripped);
+                    }
+                }
+                arr.elements = elements;
+                Ok(Value::Array(arr))
+            }
+            Value::Text(s) => match self.find_marker(&s) {
+                Some(pos) => Ok(Value::Text(s[..pos].trim_end().to_string())),
+                None => Ok(Value::Text(s)),
+            },
+            Value::Number(n) => Ok(Value::Number(n)),
+            Value::Bool(b) => Ok(Value::Bool(b)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(s: &str) -> Value {
+        Value::Text(s.to_string())
+    }
+
+    fn line_array(lines: &[&str]) -> Value {
+        Value::Array(Array::from((
+            lines.iter().map(|s| text(s)).collect(),
+            Level::Line,
+        )))
+    }
+
+    #[test]
+    fn strip_hash_comments_from_config() {
+        let input = line_array(&[
+            "# server settings",
+            "host = example.com  # primary",
+            "port = 8080",
+            "",
+            "   # indented comment",
+            "debug = false#trailing",
+        ]);
+        let result = StripComments::new("#".to_string(), false)
+            .apply(input)
+            .unwrap();
+        assert_eq!(
+            result,
+            line_array(&["host = example.com", "port = 8080", "", "debug = false"])
+        );
+    }
+
+    #[test]
+    fn strip_multi_char_marker() {
+        let input = line_array(&["let x = 1; // one", "let y = a / b;"]);
+        let result = StripComments::new("//".to_string(), false)
+            .apply(input)
+            .unwrap();
+        assert_eq!(result, line_array(&["let x = 1;", "let y = a / b;"]));
+    }
+
+    #[test]
+    fn strip_ignores_markers_in_double_quotes() {
+        let input = line_array(&[r##"color = "#ff0000" # red"##, r##"msg = "say \"#hi\"""##]);
+        let result = StripComments::new("#".to_string(), false)
+            .apply(input)
+            .unwrap();
+        assert_eq!(
+            result,
+            line_array(&[r##"color = "#ff0000""##, r##"msg = "say \"#hi\"""##])
+        );
+    }
+
+    #[test]
+    fn strip_text_directly() {
+        let result = StripComments::new("#".to_string(), false)
+            .apply(text("value # note"))
+            .unwrap();
+        assert_eq!(result, text("value"));
+    }
+
+    #[test]
+    fn strip_numbers_unchanged() {
+        let result = StripComments::new("#".to_string(), false)
+            .apply(Value::Number(42.0))
+            .unwrap();
+        assert_eq!(result, Value::Number(42.0));
+    }
+
+    fn word_row(words: &[&str]) -> Value {
+        Value::Array(Array::from((
+            words.iter().map(|s| text(s)).collect(),
+            Level::Word,
+        )))
+    }
+
+    #[test]
+    fn strip_drops_rest_of_split_line() {
+        let input = Value::Array(Array::from((
+            vec![
+                word_row(&["a", "#", "b", "c"]),
+                word_row(&["x", "y#z", "w"]),
+                word_row(&["#", "only"]),
+                word_row(&[]),
+            ],
+            Level::Line,
+        )));
+        let result = StripComments::new("#".to_string(), false)
+            .apply(input)
+            .unwrap();
+        let expected = Value::Array(Array::from((
+            vec![word_row(&["a"]), word_row(&["x", "y"]), word_row(&[])],
+            Level::Line,
+        )));
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn strip_keeping_comment_only_lines() {
+        let input = line_array(&["# header", "a = 1 # one", "", "b = 2"]);
+        let result = StripComments::new("#".to_string(), true)
+            .apply(input)
+            .unwrap();
+        assert_eq!(result, line_array(&["", "a = 1", "", "b = 2"]));
+
+        let input = Value::Array(Array::from((
+            vec![word_row(&["#", "only"]), word_row(&["a", "#", "b"])],
+            Level::Line,
+        )));
+        let result = StripComments::new("#".to_string(), true)
+            .apply(input)
+            .unwrap();
+        let expected = Value::Array(Array::from((
+            vec![word_row(&[]), word_row(&["a"])],
+            Level::Line,
+        )));
+        assert_eq!(result, expected);
+    }
+}
@@ -1,6 +1,6 @@
 use crate::error::Result;
 use crate::interpreter::Transform;
-use crate::value::Value;
+use crate::value::{Array, Level, Value};
 
 pub struct Count;
 
@@ -9,7 +9,7 @@ impl Transform for Count {
         match value {
             Value::Array(arr) => Ok(Value::Number(arr.len() as f64)),
             Value::Text(s) => Ok(Value::Number(s.chars().count() as f64)),
-            Value::Number(_) => Ok(Value::Number(0.0)),
+            Value::Number(_) | Value::Bool(_) => Ok(Value::Number(0.0)),
         }
     }
 
@@ -18,10 +18,70 @@ impl Transform for Count {
     }
 }
 
+/// Counts the leaves of a value at any depth, where `Count` counts only the
+/// top-level elements. Text and numbers are one leaf each.
+pub struct CountLeaves;
+
+fn count_leaves(value: &Value) -> usize {
+    match value {
+        Value::Array(arr) => arr.elements.iter().map(count_leaves).sum(),
+        Value::Text(_) | Value::Number(_) | Value::Bool(_) => 1,
+    }
+}
+
+impl Transform for CountLeaves {
+    fn apply(&self, value: Value) -> Result<Value> {
+        Ok(Value::Number(count_leaves(&value) as f64))
+    }
+
+    fn requires_full_input(&self) -> bool {
+        true
+    }
+}
+
+/// Counts `[lines, words, chars]`, where lines are the top-level elements and
+/// words and chars are summed over all text inside them. Unlike `wc`, line
+/// breaks are not chars: they were removed when the input was split.
+pub struct Stats;
+
+/// Word and char counts of a value, recursing through arrays.
+fn words_and_chars(value: &Value) -> (usize, usize) {
+    match value {
+        Value::Array(arr) => arr.elements.iter().fold((0, 0), |(w, c), v| {
+            let (vw, vc) = words_and_chars(v);
+            (w + vw, c + vc)
+        }),
+        Value::Text(s) => (s.split_whitespace().count(), s.chars().count()),
+        Value::Number(n) => (1, n.to_string().chars().count()),
+        Value::Bool(b) => (1, b.to_string().chars().count()),
+    }
+}
+
+impl Transform for Stats {
+    fn apply(&self, value: Value) -> Result<Value> {
+        let lines = match &value {
+            Value::Array(arr) => arr.len(),
+            _ => 1,
+        };
+        let (words, chars) = words_and_chars(&value);
+        Ok(Value::Array(Array::from((
+            vec![
+                Value::Number(lines as f64),
+                Value::Number(words as f64),
+                Value::Number(chars as f64),
+            ],
+            Level::Word,
+        ))))
+    }
+
+    fn requires_full_input(&self) -> bool {
+        true
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::value::{Array, Level};
 
     fn text(s: &str) -> Value {
         Value::Text(s.to_string())
@@ -66,4 +126,80 @@ mod tests {
         let result = Count.apply(input).unwrap();
         assert_eq!(result, Value::Number(0.0));
     }
+
+    #[test]
+    fn count_leaves_nested_arrays() {
+        let inner1 = Value::Array(Array::from((vec![text("a"), text("b")], Level::Word)));
+        let inner2 = Value::Array(Array::from((vec![text("c")], Level::Word)));
+        let empty = Value::Array(Array::from((vec![], Level::Word)));
+        let grid = Value::Array(Array::from((vec![inner1, inner2, empty], Level::Line)));
+        let input = Value::Array(Array::from((vec![grid, Value::Number(1.0)], Level::File)));
+        let result = CountLeaves.apply(input).unwrap();
+        assert_eq!(result, Value::Number(4.0));
+    }
+
+    #[test]
+    fn count_leaves_scalar_is_one() {
+        assert_eq!(
+            CountLeaves.apply(text("hello")).unwrap(),
+            Value::Number(1.0)
+        );
+        assert_eq!(
+            CountLeaves.apply(Value::Number(42.0)).unwrap(),
+            Value::Number(1.0)
+        );
+    }
+
+    fn stats_values(value: Value) -> Vec<Value> {
+        match value {
+            Value::Array(arr) => arr.elements,
+            _ => panic!("expected array"),
+        }
+    }
+
+    #[test]
+    fn stats_lines_words_chars() {
+        let input = Value::Array(Array::from((
+            vec![text("the cat  sat"), text(""), text("on the mat")],
+            Level::Line,
+        )));
+        let result = Stats.apply(input).unwrap();
+        assert_eq!(
+            stats_values(result),
+            vec![Value::Number(3.0), Value::Number(6.0), Value::Number(22.0)]
+        );
+    }
+
+    #[test]
+    fn stats_after_split() {
+        let line = |words: &[&str]| {
+            Value::Array(Array::from((
+                words.iter().map(|w| text(w)).collect(),
+                Level::Word,
+            )))
+        };
+        let input = Value::Array(Array::from((
+            vec![line(&["ab", "c"]), line(&["d"])],
+            Level::Line,
+        )));
+        let result = Stats.apply(input).unwrap();
+        assert_eq!(
+            stats_values(result),
+            vec![Value::Number(2.0), Value::Number(3.0), Value::Number(4.0)]
+        );
+    }
+
+    #[test]
+    fn stats_chars_exclude_newlines() {
+        // As read from "3\n1\n2\n": `wc` would count 6 chars
+        let input = Value::Array(Array::from((
+            vec![text("3"), text("1"), text("2")],
+            Level::Line,
+        )));
+        let result = Stats.apply(input).unwrap();
+        assert_eq!(
+            stats_values(result),
+            vec![Value::Number(3.0), Value::Number(3.0), Value::Number(3.0)]
+        );
+    }
 }
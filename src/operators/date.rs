@@ -0,0 +1,309 @@
+use std::fmt::Write;
+
+use chrono::format::{Item, Parsed, StrftimeItems, parse};
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime};
+
+use crate::error::{Error, Result};
+use crate::interpreter::Transform;
+use crate::value::Value;
+
+/// A date read from text: with a UTC offset, without one, or without a time.
+enum Moment {
+    Zoned(DateTime<FixedOffset>),
+    Naive(NaiveDateTime),
+    Date(NaiveDate),
+}
+
+impl Moment {
+    /// Parse text with a strftime-style format.
+    fn parse_with(s: &str, format: &str) -> Option<Self> {
+        let mut parsed = Parsed::new();
+        parse(&mut parsed, s, StrftimeItems::new(format)).ok()?;
+        if let Ok(dt) = parsed.to_datetime() {
+            return Some(Moment::Zoned(dt));
+        }
+        let date = parsed.to_naive_date().ok()?;
+        Some(match parsed.to_naive_time() {
+            Ok(time) => Moment::Naive(date.and_time(time)),
+            Err(_) => Moment::Date(date),
+        })
+    }
+
+    /// Parse the ISO 8601 forms written by `to_iso`, also allowing a space
+    /// between the date and the time.
+    fn parse_iso(s: &str) -> Option<Self> {
+        if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+            return Some(Moment::Zoned(dt));
+        }
+        for format in ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"] {
+            if let Ok(dt) = NaiveDateTime::parse_from_str(s, format) {
+                return Some(Moment::Naive(dt));
+            }
+        }
+        NaiveDate::parse_from_str(s, "%Y-%m-%d")
+            .ok()
+            .map(Moment::Date)
+    }
+
+    fn to_iso(&self) -> String {
+        match self {
+            Moment::Zoned(dt) => dt.format("%Y-%m-%dT%H:%M:%S%.f%:z").to_string(),
+            Moment::Naive(dt) => dt.format("%Y-%m-%dT%H:%M:%S%.f").to_string(),
+            Moment::Date(date) => date.format("%Y-%m-%d").to_string(),
+        }
+    }
+
+    /// Format with strftime-style items, or None if the format asks for
+    /// something the date doesn't have (such as an offset on a naive time).
+    fn format(&self, items: &[Item<'_>]) -> Option<String> {
+        let mut out = String::new();
+        let result = match self {
+            Moment::Zoned(dt) => write!(out, "{}", dt.format_with_items(items.iter())),
+            Moment::Naive(dt) => write!(out, "{}", dt.format_with_items(items.iter())),
+            Moment::Date(date) => write!(
+                out,
+                "{}",
+                date.and_time(Default::default())
+                    .format_with_items(items.iter())
+            ),
+        };
+        result.ok().map(|()| out)
+    }
+
+    /// Seconds since the Unix epoch. Times without an offset are taken as UTC.
+    fn epoch(&self) -> f64 {
+        let micros = match self {
+            Moment::Zoned(dt) => dt.timestamp_micros(),
+            Moment::Naive(dt) => dt.and_utc().timestamp_micros(),
+            Moment::Date(date) => date
+                .and_time(Default::default())
+                .and_utc()
+                .timestamp_micros(),
+        };
+        micros as f64 / 1_000_000.0
+    }
+}
+
+/// Parse a strftime-style format, rejecting unknown specifiers.
+fn date_format(format: &str) -> Result<Vec<Item<'static>>> {
+    let items: Vec<Item<'static>> = StrftimeItems::new(format)
+        .map(|item| item.to_owned())
+        .collect();
+    if items.iter().any(|item| matches!(item, Item::Error)) {
+        return Err(Error::runtime(format!(
+            "invalid date format \"{}\"",
+            format
+        )));
+    }
+    Ok(items)
+}
+
+/// Apply `convert` to every text element, recursing through arrays. Text it
+/// can't convert is left unchanged, or is an error in strict mode.
+fn convert_text<F>(value: Value, strict: bool, convert: &F) -> Result<Value>
+where
+    F: Fn(&str) -> Option<Value>,
+{
+    match value {
+        Value::Array(mut arr) => {
+            arr.elements = arr
+                .elements
+                .into_iter()
+                .map(|v| convert_text(v, strict, convert))
+                .collect::<Result<_>>()?;
+            Ok(Value::Array(arr))
+        }
+        Value::Text(s) => match convert(&s) {
+            Some(v) => Ok(v),
+            None if strict => Err(Error::runtime(format!("cannot read '{}' as a date", s))),
+            None => Ok(Value::Text(s)),
+        },
+        Value::Number(n) => Ok(Value::Number(n)),
+        Value::Bool(b) => Ok(Value::Bool(b)),
+    }
+}
+
+/// Reads dates in a given format and rewrites them as ISO 8601.
+pub struct ParseDate {
+    format: String,
+    strict: bool,
+}
+
+impl ParseDate {
+    pub fn new(format: &str, strict: bool) -> Result<Self> {
+        date_format(format)?;
+        Ok(Self {
+            format: format.to_string(),
+            strict,
+        })
+    }
+}
+
+impl Transform for ParseDate {
+    fn apply(&self, value: Value) -> Result<Value> {
+        convert_text(value, self.strict, &|s| {
+            Moment::parse_with(s, &self.format).map(|m| Value::Text(m.to_iso()))
+        })
+    }
+}
+
+/// Rewrites ISO 8601 dates in a given format.
+pub struct FormatDate {
+    items: Vec<Item<'static>>,
+    strict: bool,
+}
+
+impl FormatDate {
+    pub fn new(format: &str, strict: bool) -> Result<Self> {
+        Ok(Self {
+            items: date_format(format)?,
+            strict,
+        })
+    }
+}
+
+impl Transform for FormatDate {
+    fn apply(&self, value: Value) -> Result<Value> {
+        convert_text(value, self.strict, &|s| {
+            Moment::parse_iso(s)
+                .and_then(|m| m.format(&self.items))
+                .map(Value::Text)
+        })
+    }
+}
+
+/// Converts dates to seconds since the Unix epoch, reading ISO 8601 or the
+/// given format.
+pub struct ToEpoch {
+    format: Option<String>,
+    strict: bool,
+}
+
+impl ToEpoch {
+    pub fn new(format: Option<&str>, strict: bool) -> Result<Self> {
+        if let Some(format) = format {
+            date_format(format)?;
+        }
+        Ok(Self {
+            format: format.map(str::to_string),
+            strict,
+        })
+    }
+}
+
+impl Transform for ToEpoch {
+    fn apply(&self, value: Value) -> Result<Value> {
+        convert_text(value, self.strict, &|s| {
+            let moment = match &self.format {
+                Some(format) => Moment::parse_with(s, format),
+                None => Moment::parse_iso(s),
+            };
+            moment.map(|m| Value::Number(m.epoch()))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value::{Array, Level};
+
+    fn text(s: &str) -> Value {
+        Value::Text(s.to_string())
+    }
+
+    fn line_array(values: Vec<Value>) -> Value {
+        Value::Array(Array::from((values, Level::Line)))
+    }
+
+    #[test]
+    fn parse_date_to_iso() {
+        let input = line_array(vec![text("10/Oct/2025:13:55:36 +0200"), text("not a date")]);
+        let result = ParseDate::new("%d/%b/%Y:%H:%M:%S %z", false)
+            .unwrap()
+            .apply(input)
+            .unwrap();
+        assert_eq!(
+            result,
+            line_array(vec![text("2025-10-10T13:55:36+02:00"), text("not a date")])
+        );
+    }
+
+    #[test]
+    fn parse_date_without_time_or_offset() {
+        let parse = |format, s| {
+            ParseDate::new(format, false)
+                .unwrap()
+                .apply(text(s))
+                .unwrap()
+        };
+        assert_eq!(parse("%d.%m.%Y", "16.10.2026"), text("2026-10-16"));
+        assert_eq!(
+            parse("%m/%d/%Y %H:%M", "10/16/2026 09:05"),
+            text("2026-10-16T09:05:00")
+        );
+    }
+
+    #[test]
+    fn parse_date_strict_rejects_unparseable() {
+        let result = ParseDate::new("%Y-%m-%d", true)
+            .unwrap()
+            .apply(text("yesterday"));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn format_date_from_iso() {
+        let format = FormatDate::new("%d %b %Y %H:%M", false).unwrap();
+        let input = line_array(vec![
+            text("2026-10-16T09:05:00"),
+            text("2026-10-16 09:05:00"),
+            text("2026-10-16T09:05:00+02:00"),
+            text("2026-10-16"),
+            text("-"),
+        ]);
+        assert_eq!(
+            format.apply(input).unwrap(),
+            line_array(vec![
+                text("16 Oct 2026 09:05"),
+                text("16 Oct 2026 09:05"),
+                text("16 Oct 2026 09:05"),
+                text("16 Oct 2026 00:00"),
+                text("-"),
+            ])
+        );
+    }
+
+    #[test]
+    fn format_date_needs_offset_for_z() {
+        let format = FormatDate::new("%z", false).unwrap();
+        assert_eq!(
+            format.apply(text("2026-10-16T09:05:00")).unwrap(),
+            text("2026-10-16T09:05:00")
+        );
+    }
+
+    #[test]
+    fn to_epoch() {
+        let epoch = ToEpoch::new(None, false).unwrap();
+        assert_eq!(
+            epoch.apply(text("1970-01-02T00:00:00+01:00")).unwrap(),
+            Value::Number(82800.0)
+        );
+        assert_eq!(
+            epoch.apply(text("1970-01-01T00:00:01.5")).unwrap(),
+            Value::Number(1.5)
+        );
+        let epoch = ToEpoch::new(Some("%d/%m/%Y"), false).unwrap();
+        assert_eq!(
+            epoch.apply(text("02/01/1970")).unwrap(),
+            Value::Number(86400.0)
+        );
+    }
+
+    #[test]
+    fn invalid_format_is_rejected() {
+        assert!(ParseDate::new("%Q", false).is_err());
+        assert!(FormatDate::new("%Y-%", false).is_err());
+    }
+}
@@ -1,7 +1,7 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use crate::ast::Selection;
-use crate::error::Result;
+use crate::error::{Error, Result};
 use crate::interpreter::Transform;
 use crate::value::{Array, Level, Value};
 
@@ -70,10 +70,102 @@ impl Transform for DedupeWithCounts {
     }
 }
 
+/// Like `DedupeWithCounts`, but each count is a percentage of the total.
+pub struct DedupeWithPercentages;
+
+impl Transform for DedupeWithPercentages {
+    fn apply(&self, value: Value) -> Result<Value> {
+        match value {
+            Value::Array(arr) => {
+                let total = arr.len() as f64;
+                let counted = dedupe_with_counts_by(
+                    arr,
+                    |elem| Ok(value_to_key(elem)),
+                    |elem| Ok(elem.deep_copy()),
+                )?;
+                let Value::Array(mut rows) = counted else {
+                    unreachable!("dedupe_with_counts_by returns an array");
+                };
+                for row in &mut rows.elements {
+                    if let Value::Array(row) = row
+                        && let Some(Value::Number(count)) = row.elements.first_mut()
+                    {
+                        *count = *count / total * 100.0;
+                    }
+                }
+                Ok(Value::Array(rows))
+            }
+            other => Ok(other),
+        }
+    }
+
+    fn requires_full_input(&self) -> bool {
+        true
+    }
+}
+
+/// The most common element, the first seen winning a tie, as `d` orders them.
+pub struct Mode;
+
+impl Transform for Mode {
+    fn apply(&self, value: Value) -> Result<Value> {
+        match value {
+            Value::Array(arr) if arr.is_empty() => {
+                Err(Error::runtime("cannot take the mode of an empty array"))
+            }
+            Value::Array(arr) => {
+                let counted = dedupe_with_counts_by(
+                    arr,
+                    |elem| Ok(value_to_key(elem)),
+                    |elem| Ok(elem.deep_copy()),
+                )?;
+                let Value::Array(rows) = counted else {
+                    unreachable!("dedupe_with_counts_by returns an array");
+                };
+                match rows.elements.into_iter().next() {
+                    Some(Value::Array(row)) => Ok(row
+                        .elements
+                        .into_iter()
+                        .nth(1)
+                        .expect("counted rows are [count, value]")),
+                    _ => unreachable!("a non-empty array has a most common element"),
+                }
+            }
+            other => Ok(other),
+        }
+    }
+
+    fn requires_full_input(&self) -> bool {
+        true
+    }
+}
+
+/// Like `DedupeWithCounts`, but text that differs only in case is the same.
+/// The first spelling seen is kept.
+pub struct DedupeWithCountsIgnoreCase;
+
+impl Transform for DedupeWithCountsIgnoreCase {
+    fn apply(&self, value: Value) -> Result<Value> {
+        match value {
+            Value::Array(arr) => dedupe_with_counts_by(
+                arr,
+                |elem| Ok(value_to_folded_key(elem)),
+                |elem| Ok(elem.deep_copy()),
+            ),
+            other => Ok(other),
+        }
+    }
+
+    fn requires_full_input(&self) -> bool {
+        true
+    }
+}
+
 pub fn value_to_key(value: &Value) -> String {
     match value {
         Value::Text(s) => format!("T:{}", s),
         Value::Number(n) => format!("N:{}", n),
+        Value::Bool(b) => format!("B:{}", b),
         Value::Array(arr) => {
             let inner: Vec<String> = arr.elements.iter().map(value_to_key).collect();
             format!("A:[{}]", inner.join(","))
@@ -81,13 +173,30 @@ pub fn value_to_key(value: &Value) -> String {
     }
 }
 
+/// Like `value_to_key`, but lowercases text so keys ignore case.
+pub fn value_to_folded_key(value: &Value) -> String {
+    match value {
+        Value::Text(s) => format!("T:{}", s.to_lowercase()),
+        Value::Number(n) => format!("N:{}", n),
+        Value::Bool(b) => format!("B:{}", b),
+        Value::Array(arr) => {
+            let inner: Vec<String> = arr.elements.iter().map(value_to_folded_key).collect();
+            format!("A:[{}]", inner.join(","))
+        }
+    }
+}
+
 pub struct DedupeSelectionWithCounts {
     selection: Selection,
+    ignore_case: bool,
 }
 
 impl DedupeSelectionWithCounts {
-    pub fn new(selection: Selection) -> Self {
-        Self { selection }
+    pub fn new(selection: Selection, ignore_case: bool) -> Self {
+        Self {
+            selection,
+            ignore_case,
+        }
     }
 }
 
@@ -98,7 +207,11 @@ impl Transform for DedupeSelectionWithCounts {
                 arr,
                 |elem| {
                     let extracted = extract_key(elem, &self.selection)?;
-                    Ok(value_to_key(&extracted))
+                    Ok(if self.ignore_case {
+                        value_to_folded_key(&extracted)
+                    } else {
+                        value_to_key(&extracted)
+                    })
                 },
                 |elem| extract_key(elem, &self.selection),
             ),
@@ -111,6 +224,91 @@ impl Transform for DedupeSelectionWithCounts {
     }
 }
 
+/// Keeps the first whole element for each distinct key at the selection,
+/// dropping later duplicates.
+pub struct DedupeSelection {
+    selection: Selection,
+}
+
+impl DedupeSelection {
+    pub fn new(selection: Selection) -> Self {
+        Self { selection }
+    }
+}
+
+impl Transform for DedupeSelection {
+    fn apply(&self, value: Value) -> Result<Value> {
+        match value {
+            Value::Array(arr) => {
+                let mut seen: HashSet<String> = HashSet::new();
+                let mut elements = Vec::new();
+                for elem in arr.elements {
+                    let key = extract_key(&elem, &self.selection)?;
+                    if seen.insert(value_to_key(&key)) {
+                        elements.push(elem);
+                    }
+                }
+                Ok(Value::Array(Array::from((elements, arr.level))))
+            }
+            other => Ok(other),
+        }
+    }
+}
+
+/// Collapses runs of equal adjacent elements, like `uniq`. With counts, each
+/// run becomes `[count, value]`, like `uniq -c`.
+pub struct AdjacentDedupe {
+    counts: bool,
+}
+
+impl AdjacentDedupe {
+    pub fn new(counts: bool) -> Self {
+        Self { counts }
+    }
+}
+
+impl Transform for AdjacentDedupe {
+    fn apply(&self, value: Value) -> Result<Value> {
+        match value {
+            Value::Array(arr) => {
+                let mut runs: Vec<(usize, Value)> = Vec::new();
+                let mut last_key: Option<String> = None;
+                for elem in arr.elements {
+                    let key = value_to_key(&elem);
+                    match runs.last_mut() {
+                        Some((count, _)) if last_key.as_ref() == Some(&key) => *count += 1,
+                        _ => {
+                            runs.push((1, elem));
+                            last_key = Some(key);
+                        }
+                    }
+                }
+
+                if !self.counts {
+                    let elements = runs.into_iter().map(|(_, v)| v).collect();
+                    return Ok(Value::Array(Array::from((elements, arr.level))));
+                }
+                let elements = runs
+                    .into_iter()
+                    .map(|(count, v)| {
+                        Value::Array(Array::from((
+                            vec![Value::Number(count as f64), v],
+                            Level::Word,
+                        )))
+                    })
+                    .collect();
+                Ok(Value::Array(Array::from((elements, Level::Line))))
+            }
+            other => Ok(other),
+        }
+    }
+
+    // The last run's count can grow with more input; the runs themselves can't
+    fn requires_full_input(&self) -> bool {
+        self.counts
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -249,9 +447,12 @@ mod tests {
             ],
             Level::Line,
         )));
-        let dedupe = DedupeSelectionWithCounts::new(Selection {
-            items: vec![SelectItem::Index(0)],
-        });
+        let dedupe = DedupeSelectionWithCounts::new(
+            Selection {
+                items: vec![SelectItem::Index(0)],
+            },
+            false,
+        );
         let result = dedupe.apply(input).unwrap();
 
         match result {
@@ -288,9 +489,12 @@ mod tests {
             ],
             Level::Line,
         )));
-        let dedupe = DedupeSelectionWithCounts::new(Selection {
-            items: vec![SelectItem::Index(0)],
-        });
+        let dedupe = DedupeSelectionWithCounts::new(
+            Selection {
+                items: vec![SelectItem::Index(0)],
+            },
+            false,
+        );
         let result = dedupe.apply(input).unwrap();
 
         match result {
@@ -323,9 +527,12 @@ mod tests {
     #[test]
     fn dedupe_selection_with_counts_empty_array() {
         let input = Value::Array(Array::from((vec![], Level::Line)));
-        let dedupe = DedupeSelectionWithCounts::new(Selection {
-            items: vec![SelectItem::Index(0)],
-        });
+        let dedupe = DedupeSelectionWithCounts::new(
+            Selection {
+                items: vec![SelectItem::Index(0)],
+            },
+            false,
+        );
         let result = dedupe.apply(input).unwrap();
 
         match result {
@@ -339,10 +546,180 @@ mod tests {
     #[test]
     fn dedupe_selection_with_counts_non_array_is_identity() {
         let input = text("hello");
-        let dedupe = DedupeSelectionWithCounts::new(Selection {
-            items: vec![SelectItem::Index(0)],
-        });
+        let dedupe = DedupeSelectionWithCounts::new(
+            Selection {
+                items: vec![SelectItem::Index(0)],
+            },
+            false,
+        );
         let result = dedupe.apply(input).unwrap();
         assert_eq!(result, text("hello"));
     }
+
+    #[test]
+    fn dedupe_with_percentages() {
+        let input = Value::Array(Array::from((
+            vec![text("a"), text("b"), text("a"), text("c")],
+            Level::Line,
+        )));
+        let result = DedupeWithPercentages.apply(input).unwrap();
+        let rows: Vec<(f64, String)> = match result {
+            Value::Array(arr) => arr
+                .elements
+                .iter()
+                .map(|row| match row {
+                    Value::Array(row) => (
+                        row.elements[0].as_number().unwrap(),
+                        row.elements[1].to_string(),
+                    ),
+                    _ => panic!("expected row"),
+                })
+                .collect(),
+            _ => panic!("expected array"),
+        };
+        assert_eq!(
+            rows,
+            vec![
+                (50.0, "a".to_string()),
+                (25.0, "b".to_string()),
+                (25.0, "c".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn dedupe_ignore_case_keeps_first_spelling() {
+        let input = Value::Array(Array::from((
+            vec![text("Foo"), text("foo"), text("FOO"), text("bar")],
+            Level::Line,
+        )));
+        let result = DedupeWithCountsIgnoreCase.apply(input).unwrap();
+        let expected = Value::Array(Array::from((
+            vec![
+                Value::Array(Array::from((
+                    vec![Value::Number(3.0), text("Foo")],
+                    Level::Word,
+                ))),
+                Value::Array(Array::from((
+                    vec![Value::Number(1.0), text("bar")],
+                    Level::Word,
+                ))),
+            ],
+            Level::Line,
+        )));
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn dedupe_selection_ignore_case() {
+        let row =
+            |a: &str, b: &str| Value::Array(Array::from((vec![text(a), text(b)], Level::Word)));
+        let input = Value::Array(Array::from((
+            vec![row("1", "Foo"), row("2", "foo"), row("3", "FOO")],
+            Level::Line,
+        )));
+        let sel = Selection {
+            items: vec![SelectItem::Index(1)],
+        };
+        let result = DedupeSelectionWithCounts::new(sel, true)
+            .apply(input)
+            .unwrap();
+        match result {
+            Value::Array(arr) => {
+                assert_eq!(arr.len(), 1);
+                assert_eq!(
+                    arr.elements[0],
+                    Value::Array(Array::from((
+                        vec![Value::Number(3.0), text("Foo")],
+                        Level::Word
+                    )))
+                );
+            }
+            _ => panic!("expected array"),
+        }
+    }
+
+    #[test]
+    fn dedupe_selection_keeps_first_row() {
+        let row =
+            |a: &str, b: &str| Value::Array(Array::from((vec![text(a), text(b)], Level::Word)));
+        let input = Value::Array(Array::from((
+            vec![row("x", "1"), row("y", "2"), row("x", "3")],
+            Level::Line,
+        )));
+        let sel = Selection {
+            items: vec![SelectItem::Index(0)],
+        };
+        let result = DedupeSelection::new(sel).apply(input).unwrap();
+        let expected = Value::Array(Array::from((
+            vec![row("x", "1"), row("y", "2")],
+            Level::Line,
+        )));
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn adjacent_dedupe_keeps_separate_runs() {
+        let input = Value::Array(Array::from((
+            vec![text("a"), text("a"), text("b"), text("a"), text("a")],
+            Level::Line,
+        )));
+        let result = AdjacentDedupe::new(false).apply(input).unwrap();
+        assert_eq!(
+            result,
+            Value::Array(Array::from((
+                vec![text("a"), text("b"), text("a")],
+                Level::Line
+            )))
+        );
+    }
+
+    #[test]
+    fn adjacent_dedupe_with_counts() {
+        let input = Value::Array(Array::from((
+            vec![
+                text("x"),
+                text("x"),
+                text("x"),
+                Value::Number(1.0),
+                text("1"),
+            ],
+            Level::Line,
+        )));
+        let result = AdjacentDedupe::new(true).apply(input).unwrap();
+        let row =
+            |n: f64, v: Value| Value::Array(Array::from((vec![Value::Number(n), v], Level::Word)));
+        assert_eq!(
+            result,
+            Value::Array(Array::from((
+                vec![
+                    row(3.0, text("x")),
+                    row(1.0, Value::Number(1.0)),
+                    row(1.0, text("1"))
+                ],
+                Level::Line
+            )))
+        );
+    }
+
+    #[test]
+    fn mode_most_common_first_seen_on_tie() {
+        let input = Value::Array(Array::from((
+            vec![text("b"), text("a"), text("a"), text("b"), text("c")],
+            Level::Line,
+        )));
+        assert_eq!(Mode.apply(input).unwrap(), text("b"));
+
+        let input = Value::Array(Array::from((
+            vec![text("x"), text("y"), text("y")],
+            Level::Line,
+        )));
+        assert_eq!(Mode.apply(input).unwrap(), text("y"));
+    }
+
+    #[test]
+    fn mode_of_empty_array_fails() {
+        let input = Value::Array(Array::from((vec![], Level::Line)));
+        assert!(Mode.apply(input).is_err());
+    }
 }
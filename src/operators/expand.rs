@@ -0,0 +1,100 @@
+use crate::error::Result;
+use crate::interpreter::Transform;
+use crate::value::Value;
+
+/// Replaces each tab in text with spaces up to the next tab stop, every
+/// `tab_width` columns, like `expand`. Columns are counted in chars from the
+/// start of the text or the last newline.
+pub struct ExpandTabs {
+    tab_width: usize,
+}
+
+impl ExpandTabs {
+    pub fn new(tab_width: usize) -> Self {
+        Self { tab_width }
+    }
+
+    fn expand(&self, s: &str) -> String {
+        let mut result = String::with_capacity(s.len());
+        let mut column = 0;
+        for c in s.chars() {
+            match c {
+                '\t' => {
+                    let spaces = self.tab_width - column % self.tab_width;
+                    result.extend(std::iter::repeat_n(' ', spaces));
+                    column += spaces;
+                }
+                '\n' => {
+                    result.push(c);
+                    column = 0;
+                }
+                _ => {
+                    result.push(c);
+                    column += 1;
+                }
+            }
+        }
+        result
+    }
+}
+
+impl Transform for ExpandTabs {
+    fn apply(&self, value: Value) -> Result<Value> {
+        match value {
+            Value::Array(mut arr) => {
+                arr.elements = arr
+                    .elements
+                    .into_iter()
+                    .map(|v| self.apply(v))
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(Value::Array(arr))
+            }
+            Value::Text(s) if s.contains('\t') => Ok(Value::Text(self.expand(&s))),
+            other => Ok(other),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value::{Array, Level};
+
+    fn text(s: &str) -> Value {
+        Value::Text(s.to_string())
+    }
+
+    fn expand(s: &str, tab_width: usize) -> String {
+        ExpandTabs::new(tab_width).expand(s)
+    }
+
+    #[test]
+    fn expand_to_next_tab_stop() {
+        assert_eq!(expand("\tx", 8), "        x");
+        assert_eq!(expand("ab\tc", 8), "ab      c");
+        assert_eq!(expand("abcdefg\th", 8), "abcdefg h");
+        // A tab right on a stop moves a whole stop on
+        assert_eq!(expand("abcdefgh\ti", 8), "abcdefgh        i");
+        assert_eq!(expand("a\t\tb", 4), "a       b");
+        assert_eq!(expand("héllo\tx", 4), "héllo   x");
+    }
+
+    #[test]
+    fn expand_restarts_columns_after_newline() {
+        assert_eq!(expand("abc\tx\n\ty", 4), "abc x\n    y");
+    }
+
+    #[test]
+    fn expand_recurses() {
+        let input = Value::Array(Array::from((
+            vec![text("a\tb"), Value::Number(1.0), text("none")],
+            Level::Line,
+        )));
+        let result = ExpandTabs::new(2).apply(input).unwrap();
+        let expected = Value::Array(Array::from((
+            vec![text("a b"), Value::Number(1.0), text("none")],
+            Level::Line,
+        )));
+        assert_eq!(result, expected);
+    }
+}
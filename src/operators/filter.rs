@@ -1,9 +1,13 @@
 use regex::Regex;
 
+use crate::ast::{Comparison, Selection};
 use crate::error::Result;
 use crate::interpreter::Transform;
 use crate::value::{Array, Value};
 
+use super::group::extract_key;
+use super::select::selection_indices;
+
 pub struct Filter {
     pattern: Regex,
     negate: bool,
@@ -23,12 +27,7 @@ impl Transform for Filter {
                     .elements
                     .into_iter()
                     .filter(|elem| {
-                        let text = match elem {
-                            Value::Text(s) => s.clone(),
-                            Value::Number(n) => n.to_string(),
-                            Value::Array(inner) => inner.to_string(),
-                        };
-                        let matches = self.pattern.is_match(&text);
+                        let matches = self.pattern.is_match(&match_text(elem));
                         if self.negate { !matches } else { matches }
                     })
                     .collect();
@@ -39,15 +38,367 @@ impl Transform for Filter {
     }
 }
 
+/// Maps each element to whether it matches the regex (or does not, when
+/// negated), keeping the array's shape. Bare text becomes a single boolean.
+pub struct Matches {
+    pattern: Regex,
+    negate: bool,
+}
+
+impl Matches {
+    pub fn new(pattern: Regex, negate: bool) -> Self {
+        Self { pattern, negate }
+    }
+
+    fn test(&self, elem: &Value) -> bool {
+        self.pattern.is_match(&match_text(elem)) != self.negate
+    }
+}
+
+impl Transform for Matches {
+    fn apply(&self, value: Value) -> Result<Value> {
+        match value {
+            Value::Array(arr) => {
+                let results: Vec<Value> = arr
+                    .elements
+                    .iter()
+                    .map(|elem| Value::Bool(self.test(elem)))
+                    .collect();
+                Ok(Value::Array(Array::from((results, arr.level))))
+            }
+            other => Ok(Value::Bool(self.test(&other))),
+        }
+    }
+}
+
+/// The text a regex filter matches an element against.
+fn match_text(elem: &Value) -> String {
+    match elem {
+        Value::Text(s) => s.clone(),
+        Value::Number(n) => n.to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Array(inner) => inner.to_string(),
+    }
+}
+
+/// Keeps the elements that match the regex (or do not, when negated) along
+/// with up to `before` elements before each and `after` after, like `grep -C`.
+/// Overlapping windows are merged, so each element appears at most once, and
+/// no separator is put between blocks.
+pub struct FilterContext {
+    pattern: Regex,
+    negate: bool,
+    before: usize,
+    after: usize,
+}
+
+impl FilterContext {
+    pub fn new(pattern: Regex, negate: bool, before: usize, after: usize) -> Self {
+        Self {
+            pattern,
+            negate,
+            before,
+            after,
+        }
+    }
+}
+
+impl Transform for FilterContext {
+    fn apply(&self, value: Value) -> Result<Value> {
+        match value {
+            Value::Array(arr) => {
+                let len = arr.len();
+                let mut keep = vec![false; len];
+                for (i, elem) in arr.elements.iter().enumerate() {
+                    if self.pattern.is_match(&match_text(elem)) != self.negate {
+                        let end = (i + self.after).min(len - 1);
+                        keep[i.saturating_sub(self.before)..=end].fill(true);
+                    }
+                }
+                let kept: Vec<Value> = arr
+                    .elements
+                    .into_iter()
+                    .zip(keep)
+                    .filter_map(|(elem, keep)| keep.then_some(elem))
+                    .collect();
+                Ok(Value::Array(Array::from((kept, arr.level))))
+            }
+            other => Ok(other),
+        }
+    }
+
+    fn requires_full_input(&self) -> bool {
+        true
+    }
+}
+
+/// Applies an operator to the elements that match the regex (or do not, when
+/// negated), passing the rest through unchanged.
+pub struct ApplyIf {
+    pattern: Regex,
+    negate: bool,
+    inner: Box<dyn Transform>,
+}
+
+impl ApplyIf {
+    pub fn new(pattern: Regex, negate: bool, inner: Box<dyn Transform>) -> Self {
+        Self {
+            pattern,
+            negate,
+            inner,
+        }
+    }
+
+    fn apply_to_element(&self, elem: Value) -> Result<Value> {
+        if self.pattern.is_match(&match_text(&elem)) != self.negate {
+            self.inner.apply(elem)
+        } else {
+            Ok(elem)
+        }
+    }
+}
+
+impl Transform for ApplyIf {
+    fn apply(&self, value: Value) -> Result<Value> {
+        match value {
+            Value::Array(arr) => {
+                let elements = arr
+                    .elements
+                    .into_iter()
+                    .map(|elem| self.apply_to_element(elem))
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(Value::Array(Array::from((elements, arr.level))))
+            }
+            other => self.apply_to_element(other),
+        }
+    }
+}
+
+/// Keeps array elements whose selected value matches the regex (or does not,
+/// when negated). Elements that are not arrays or lack the selected index are
+/// dropped either way.
+pub struct FilterSelected {
+    selection: Selection,
+    pattern: Regex,
+    negate: bool,
+}
+
+impl FilterSelected {
+    pub fn new(selection: Selection, pattern: Regex, negate: bool) -> Self {
+        Self {
+            selection,
+            pattern,
+            negate,
+        }
+    }
+
+    fn keep(&self, elem: &Value) -> bool {
+        let Value::Array(arr) = elem else {
+            return false;
+        };
+        if selection_indices(&self.selection, &arr.elements).is_empty() {
+            return false;
+        }
+        match extract_key(elem, &self.selection) {
+            Ok(key) => self.pattern.is_match(&match_text(&key)) != self.negate,
+            Err(_) => false,
+        }
+    }
+}
+
+impl Transform for FilterSelected {
+    fn apply(&self, value: Value) -> Result<Value> {
+        match value {
+            Value::Array(arr) => {
+                let filtered: Vec<Value> = arr
+                    .elements
+                    .into_iter()
+                    .filter(|elem| self.keep(elem))
+                    .collect();
+                Ok(Value::Array(Array::from((filtered, arr.level))))
+            }
+            other => Ok(other),
+        }
+    }
+}
+
+/// Keeps rows where any field (or, with `all`, every field) matches the regex,
+/// testing each field on its own rather than the row as one string. Negation
+/// applies per field: with `all`, `!` keeps rows where no field matches.
+/// Elements that are not arrays are tested as a single field.
+pub struct FilterFields {
+    pattern: Regex,
+    negate: bool,
+    all: bool,
+}
+
+impl FilterFields {
+    pub fn new(pattern: Regex, negate: bool, all: bool) -> Self {
+        Self {
+            pattern,
+            negate,
+            all,
+        }
+    }
+
+    fn field_matches(&self, field: &Value) -> bool {
+        self.pattern.is_match(&match_text(field)) != self.negate
+    }
+
+    fn keep(&self, elem: &Value) -> bool {
+        match elem {
+            Value::Array(arr) if self.all => arr.elements.iter().all(|f| self.field_matches(f)),
+            Value::Array(arr) => arr.elements.iter().any(|f| self.field_matches(f)),
+            other => self.field_matches(other),
+        }
+    }
+}
+
+impl Transform for FilterFields {
+    fn apply(&self, value: Value) -> Result<Value> {
+        match value {
+            Value::Array(arr) => {
+                let filtered: Vec<Value> = arr
+                    .elements
+                    .into_iter()
+                    .filter(|elem| self.keep(elem))
+                    .collect();
+                Ok(Value::Array(Array::from((filtered, arr.level))))
+            }
+            other => Ok(other),
+        }
+    }
+}
+
+/// Keeps elements whose selected value is a number satisfying the comparison.
+/// Elements where the value is missing or non-numeric are dropped.
+pub struct Compare {
+    selection: Selection,
+    cmp: Comparison,
+    value: f64,
+}
+
+impl Compare {
+    pub fn new(selection: Selection, cmp: Comparison, value: f64) -> Self {
+        Self {
+            selection,
+            cmp,
+            value,
+        }
+    }
+
+    fn keep(&self, elem: &Value) -> bool {
+        extract_key(elem, &self.selection)
+            .ok()
+            .and_then(|key| key.as_number())
+            .is_some_and(|n| self.cmp.holds(n, self.value))
+    }
+}
+
+impl Transform for Compare {
+    fn apply(&self, value: Value) -> Result<Value> {
+        match value {
+            Value::Array(arr) => {
+                let filtered: Vec<Value> = arr
+                    .elements
+                    .into_iter()
+                    .filter(|elem| self.keep(elem))
+                    .collect();
+                Ok(Value::Array(Array::from((filtered, arr.level))))
+            }
+            other => Ok(other),
+        }
+    }
+}
+
+/// Keeps elements whose length satisfies the comparison: chars for text and
+/// numbers, elements for arrays.
+pub struct CompareLength {
+    cmp: Comparison,
+    value: f64,
+}
+
+impl CompareLength {
+    pub fn new(cmp: Comparison, value: f64) -> Self {
+        Self { cmp, value }
+    }
+
+    fn keep(&self, elem: &Value) -> bool {
+        let len = match elem {
+            Value::Text(s) => s.chars().count(),
+            Value::Number(n) => n.to_string().chars().count(),
+            Value::Bool(b) => b.to_string().chars().count(),
+            Value::Array(inner) => inner.len(),
+        };
+        self.cmp.holds(len as f64, self.value)
+    }
+}
+
+impl Transform for CompareLength {
+    fn apply(&self, value: Value) -> Result<Value> {
+        match value {
+            Value::Array(arr) => {
+                let filtered: Vec<Value> = arr
+                    .elements
+                    .into_iter()
+                    .filter(|elem| self.keep(elem))
+                    .collect();
+                Ok(Value::Array(Array::from((filtered, arr.level))))
+            }
+            other => Ok(other),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ast::SelectItem;
     use crate::value::Level;
 
+    fn rows(rows: &[&[&str]]) -> Value {
+        Value::Array(Array::from((
+            rows.iter()
+                .map(|r| {
+                    Value::Array(Array::from((
+                        r.iter().map(|s| text(s)).collect(),
+                        Level::Word,
+                    )))
+                })
+                .collect(),
+            Level::Line,
+        )))
+    }
+
+    fn index(i: i64) -> Selection {
+        Selection {
+            items: vec![SelectItem::Index(i)],
+        }
+    }
+
     fn text(s: &str) -> Value {
         Value::Text(s.to_string())
     }
 
+    #[test]
+    fn matches_maps_to_booleans() {
+        let input = Value::Array(Array::from((
+            vec![text("apple"), text("banana"), Value::Number(42.0)],
+            Level::Line,
+        )));
+        let matches = Matches::new(Regex::new("^a|2").unwrap(), false);
+        assert_eq!(
+            matches.apply(input).unwrap(),
+            Value::Array(Array::from((
+                vec![Value::Bool(true), Value::Bool(false), Value::Bool(true)],
+                Level::Line,
+            )))
+        );
+
+        let negated = Matches::new(Regex::new("^a").unwrap(), true);
+        assert_eq!(negated.apply(text("apple")).unwrap(), Value::Bool(false));
+    }
+
     #[test]
     fn filter_keep_matching() {
         let input = Value::Array(Array::from((
@@ -199,4 +550,235 @@ mod tests {
             _ => panic!("expected array"),
         }
     }
+
+    #[test]
+    fn compare_keeps_rows_above_threshold() {
+        let input = rows(&[&["a", "50"], &["b", "150"], &["c", "100"], &["d", "101"]]);
+        let compare = Compare::new(index(1), Comparison::Gt, 100.0);
+        let result = compare.apply(input).unwrap();
+        assert_eq!(result, rows(&[&["b", "150"], &["d", "101"]]));
+    }
+
+    #[test]
+    fn compare_each_operator() {
+        let input = || rows(&[&["1"], &["2"], &["3"]]);
+        let kept = |cmp| match Compare::new(index(0), cmp, 2.0).apply(input()).unwrap() {
+            Value::Array(arr) => arr.len(),
+            _ => panic!("expected array"),
+        };
+        assert_eq!(kept(Comparison::Lt), 1);
+        assert_eq!(kept(Comparison::Le), 2);
+        assert_eq!(kept(Comparison::Gt), 1);
+        assert_eq!(kept(Comparison::Ge), 2);
+        assert_eq!(kept(Comparison::Eq), 1);
+        assert_eq!(kept(Comparison::Ne), 2);
+    }
+
+    #[test]
+    fn compare_drops_non_numeric_and_short_rows() {
+        let input = rows(&[&["a", "n/a"], &["b"], &["c", "-3"]]);
+        let compare = Compare::new(index(1), Comparison::Lt, 0.0);
+        let result = compare.apply(input).unwrap();
+        assert_eq!(result, rows(&[&["c", "-3"]]));
+    }
+
+    #[test]
+    fn compare_plain_elements() {
+        let input = Value::Array(Array::from((
+            vec![Value::Number(5.0), text("12"), text("x")],
+            Level::Line,
+        )));
+        let compare = Compare::new(index(0), Comparison::Ge, 10.0);
+        let result = compare.apply(input).unwrap();
+        assert_eq!(
+            result,
+            Value::Array(Array::from((vec![text("12")], Level::Line)))
+        );
+    }
+
+    #[test]
+    fn compare_length_counts_chars() {
+        let input = Value::Array(Array::from((
+            vec![text("short"), text("a longer line"), text("héllo!")],
+            Level::Line,
+        )));
+        let compare = CompareLength::new(Comparison::Gt, 5.0);
+        let result = compare.apply(input).unwrap();
+        assert_eq!(
+            result,
+            Value::Array(Array::from((
+                vec![text("a longer line"), text("héllo!")],
+                Level::Line
+            )))
+        );
+    }
+
+    #[test]
+    fn compare_length_of_arrays() {
+        let input = rows(&[&["a"], &["a", "b", "c"], &[]]);
+        let compare = CompareLength::new(Comparison::Ge, 2.0);
+        let result = compare.apply(input).unwrap();
+        assert_eq!(result, rows(&[&["a", "b", "c"]]));
+    }
+
+    fn lines(lines: &[&str]) -> Value {
+        Value::Array(Array::from((
+            lines.iter().map(|s| text(s)).collect(),
+            Level::Line,
+        )))
+    }
+
+    fn context(pattern: &str, before: usize, after: usize) -> FilterContext {
+        FilterContext::new(Regex::new(pattern).unwrap(), false, before, after)
+    }
+
+    #[test]
+    fn filter_context_before_and_after() {
+        let input = lines(&["a", "b", "ERR", "c", "d", "e"]);
+        let result = context("ERR", 1, 2).apply(input).unwrap();
+        assert_eq!(result, lines(&["b", "ERR", "c", "d"]));
+    }
+
+    #[test]
+    fn filter_context_overlapping_windows_merge() {
+        let input = lines(&["a", "ERR1", "b", "c", "ERR2", "d", "e", "f", "g", "ERR3"]);
+        let result = context("ERR", 2, 1).apply(input).unwrap();
+        assert_eq!(
+            result,
+            lines(&["a", "ERR1", "b", "c", "ERR2", "d", "f", "g", "ERR3"])
+        );
+    }
+
+    #[test]
+    fn filter_context_adjacent_matches() {
+        let input = lines(&["x", "ERR", "ERR", "y", "z"]);
+        let result = context("ERR", 0, 1).apply(input.deep_copy()).unwrap();
+        assert_eq!(result, lines(&["ERR", "ERR", "y"]));
+        // Without context, this is a plain filter
+        let result = context("ERR", 0, 0).apply(input).unwrap();
+        assert_eq!(result, lines(&["ERR", "ERR"]));
+    }
+
+    #[test]
+    fn filter_context_negated() {
+        let input = lines(&["ok", "ok", "bad", "ok"]);
+        let filter = FilterContext::new(Regex::new("ok").unwrap(), true, 1, 0);
+        assert_eq!(filter.apply(input).unwrap(), lines(&["ok", "bad"]));
+    }
+
+    #[test]
+    fn filter_selected_matches_column() {
+        let input = rows(&[&["web", "ok"], &["db", "failed"], &["cache", "ok"]]);
+        let filter = FilterSelected::new(index(1), Regex::new("^ok$").unwrap(), false);
+        let result = filter.apply(input).unwrap();
+        assert_eq!(result, rows(&[&["web", "ok"], &["cache", "ok"]]));
+    }
+
+    #[test]
+    fn filter_selected_negated() {
+        let input = rows(&[&["web", "ok"], &["db", "failed"], &["okay"]]);
+        let filter = FilterSelected::new(index(1), Regex::new("ok").unwrap(), true);
+        let result = filter.apply(input).unwrap();
+        assert_eq!(result, rows(&[&["db", "failed"]]));
+    }
+
+    #[test]
+    fn filter_fields_any() {
+        let input = rows(&[&["a", "b"], &["ab", "c"], &["c"]]);
+        // Matching the joined row would keep `["a", "b"]` too
+        let filter = FilterFields::new(Regex::new("a b|ab").unwrap(), false, false);
+        let result = filter.apply(input).unwrap();
+        assert_eq!(result, rows(&[&["ab", "c"]]));
+    }
+
+    #[test]
+    fn filter_fields_all() {
+        let input = rows(&[&["1", "2"], &["3", "x"], &[]]);
+        let filter = FilterFields::new(Regex::new(r"^\d+$").unwrap(), false, true);
+        let result = filter.apply(input).unwrap();
+        assert_eq!(result, rows(&[&["1", "2"], &[]]));
+    }
+
+    #[test]
+    fn filter_fields_negated() {
+        let input = rows(&[&["web", "ok"], &["db", "failed"]]);
+        // No field matches
+        let none = FilterFields::new(Regex::new("^ok$").unwrap(), true, true);
+        assert_eq!(
+            none.apply(input.deep_copy()).unwrap(),
+            rows(&[&["db", "failed"]])
+        );
+        // Some field doesn't match
+        let some = FilterFields::new(Regex::new("^ok$").unwrap(), true, false);
+        assert_eq!(
+            some.apply(input).unwrap(),
+            rows(&[&["web", "ok"], &["db", "failed"]])
+        );
+    }
+
+    #[test]
+    fn filter_selected_drops_non_arrays() {
+        let input = Value::Array(Array::from((
+            vec![
+                text("ok"),
+                Value::Array(Array::from((vec![text("ok")], Level::Word))),
+            ],
+            Level::Line,
+        )));
+        let filter = FilterSelected::new(index(0), Regex::new("ok").unwrap(), false);
+        let result = filter.apply(input).unwrap();
+        match result {
+            Value::Array(arr) => assert_eq!(arr.len(), 1),
+            _ => panic!("expected array"),
+        }
+    }
+
+    fn line_array(lines: &[&str]) -> Value {
+        Value::Array(Array::from((
+            lines.iter().map(|s| text(s)).collect(),
+            Level::Line,
+        )))
+    }
+
+    #[test]
+    fn apply_if_transforms_only_matching() {
+        let input = line_array(&["error one", "ok", "ERROR two"]);
+        let apply_if = ApplyIf::new(
+            Regex::new("ERROR").unwrap(),
+            false,
+            Box::new(crate::operators::Uppercase),
+        );
+        assert_eq!(
+            apply_if.apply(input).unwrap(),
+            line_array(&["error one", "ok", "ERROR TWO"])
+        );
+    }
+
+    #[test]
+    fn apply_if_negated_transforms_non_matching() {
+        let input = line_array(&["keep", "shout", "keep too"]);
+        let apply_if = ApplyIf::new(
+            Regex::new("keep").unwrap(),
+            true,
+            Box::new(crate::operators::Uppercase),
+        );
+        assert_eq!(
+            apply_if.apply(input).unwrap(),
+            line_array(&["keep", "SHOUT", "keep too"])
+        );
+    }
+
+    #[test]
+    fn apply_if_matches_rows_by_joined_text() {
+        let input = rows(&[&["a", "1"], &["b", "2"]]);
+        let apply_if = ApplyIf::new(
+            Regex::new("^b 2$").unwrap(),
+            false,
+            Box::new(crate::operators::Uppercase),
+        );
+        assert_eq!(
+            apply_if.apply(input).unwrap(),
+            rows(&[&["a", "1"], &["B", "2"]])
+        );
+    }
 }
@@ -0,0 +1,166 @@
+use crate::error::{Error, Result};
+use crate::interpreter::Transform;
+use crate::value::{Array, Value};
+
+/// One part of a parsed template.
+#[derive(Debug, PartialEq)]
+enum Piece {
+    Literal(String),
+    /// `{}` - the whole element
+    Whole,
+    /// `{<n>}` - an inner element by index (negative counts from the end)
+    Index(i64),
+}
+
+/// Renders each element through a template such as `"{1}: {0}"`.
+pub struct Format {
+    pieces: Vec<Piece>,
+}
+
+impl Format {
+    /// Fails if the template has an unclosed `{` or a placeholder that isn't
+    /// empty or an index. `{{` and `}}` are literal braces.
+    pub fn new(template: &str) -> Result<Self> {
+        let mut pieces = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => {
+                                return Err(Error::runtime(format!(
+                                    "unclosed '{{' in template \"{}\"",
+                                    template
+                                )));
+                            }
+                        }
+                    }
+                    let piece = if name.is_empty() {
+                        Piece::Whole
+                    } else {
+                        Piece::Index(name.parse().map_err(|_| {
+                            Error::runtime(format!(
+                                "invalid placeholder '{{{}}}' in template \"{}\"",
+                                name, template
+                            ))
+                        })?)
+                    };
+                    if !literal.is_empty() {
+                        pieces.push(Piece::Literal(std::mem::take(&mut literal)));
+                    }
+                    pieces.push(piece);
+                }
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            pieces.push(Piece::Literal(literal));
+        }
+        Ok(Self { pieces })
+    }
+
+    fn render(&self, value: &Value) -> String {
+        let mut out = String::new();
+        for piece in &self.pieces {
+            match piece {
+                Piece::Literal(s) => out.push_str(s),
+                Piece::Whole => out.push_str(&value.to_string()),
+                Piece::Index(i) => {
+                    let field = match value {
+                        Value::Array(arr) => arr.get(*i),
+                        // A scalar is a row of one
+                        scalar if *i == 0 || *i == -1 => Some(scalar),
+                        _ => None,
+                    };
+                    if let Some(field) = field {
+                        out.push_str(&field.to_string());
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+impl Transform for Format {
+    fn apply(&self, value: Value) -> Result<Value> {
+        match value {
+            Value::Array(arr) => {
+                let elements = arr
+                    .elements
+                    .iter()
+                    .map(|v| Value::Text(self.render(v)))
+                    .collect();
+                Ok(Value::Array(Array::from((elements, arr.level))))
+            }
+            other => Ok(Value::Text(self.render(&other))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value::Level;
+
+    fn text(s: &str) -> Value {
+        Value::Text(s.to_string())
+    }
+
+    fn row(values: Vec<Value>) -> Value {
+        Value::Array(Array::from((values, Level::Word)))
+    }
+
+    fn line_array(values: Vec<Value>) -> Value {
+        Value::Array(Array::from((values, Level::Line)))
+    }
+
+    #[test]
+    fn format_rows_by_index() {
+        let input = line_array(vec![
+            row(vec![Value::Number(3.0), text("apple")]),
+            row(vec![Value::Number(1.0), text("pear")]),
+        ]);
+        let result = Format::new("{1}: {0}").unwrap().apply(input).unwrap();
+        assert_eq!(result, line_array(vec![text("apple: 3"), text("pear: 1")]));
+    }
+
+    #[test]
+    fn format_out_of_range_is_empty() {
+        let input = line_array(vec![row(vec![text("a"), text("b")])]);
+        let result = Format::new("[{0}|{5}|{-1}]").unwrap().apply(input).unwrap();
+        assert_eq!(result, line_array(vec![text("[a||b]")]));
+    }
+
+    #[test]
+    fn format_scalars_and_whole_element() {
+        let input = line_array(vec![text("x"), Value::Number(2.5)]);
+        let result = Format::new("<{}> {0}{1}").unwrap().apply(input).unwrap();
+        assert_eq!(result, line_array(vec![text("<x> x"), text("<2.5> 2.5")]));
+    }
+
+    #[test]
+    fn format_literal_braces() {
+        let result = Format::new("{{{0}}}").unwrap().apply(text("k")).unwrap();
+        assert_eq!(result, text("{k}"));
+    }
+
+    #[test]
+    fn format_rejects_bad_templates() {
+        assert!(Format::new("{0").is_err());
+        assert!(Format::new("{name}").is_err());
+    }
+}
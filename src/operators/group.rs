@@ -3,7 +3,7 @@ use std::collections::HashMap;
 use crate::ast::Selection;
 use crate::error::{Error, Result};
 use crate::interpreter::Transform;
-use crate::value::{Array, Value};
+use crate::value::{Array, Level, Value};
 
 use super::dedupe::value_to_key;
 use super::select::selection_indices;
@@ -59,11 +59,86 @@ impl Transform for GroupBy {
     }
 }
 
+/// Splits elements into runs with equal keys at the selection, starting a new
+/// chunk whenever the key changes. Unlike `GroupBy`, equal keys that are apart
+/// stay in separate chunks.
+pub struct ChunkBy {
+    selection: Selection,
+}
+
+impl ChunkBy {
+    pub fn new(selection: Selection) -> Self {
+        Self { selection }
+    }
+}
+
+impl Transform for ChunkBy {
+    fn apply(&self, value: Value) -> Result<Value> {
+        match value {
+            Value::Array(arr) => {
+                let mut chunks: Vec<Vec<Value>> = Vec::new();
+                let mut last_key: Option<String> = None;
+                for elem in arr.elements {
+                    let key = value_to_key(&extract_key(&elem, &self.selection)?);
+                    match chunks.last_mut() {
+                        Some(chunk) if last_key.as_ref() == Some(&key) => chunk.push(elem),
+                        _ => {
+                            chunks.push(vec![elem]);
+                            last_key = Some(key);
+                        }
+                    }
+                }
+
+                let elements = chunks
+                    .into_iter()
+                    .map(|chunk| Value::Array(Array::from((chunk, arr.level))))
+                    .collect();
+                Ok(Value::Array(Array::from((elements, Level::Line))))
+            }
+            other => Ok(other),
+        }
+    }
+}
+
+/// Overlapping runs of `n` consecutive elements, e.g. the bigrams of
+/// `[a, b, c]` are `[[a, b], [b, c]]`. Fewer than `n` elements give none.
+pub struct Ngram {
+    n: usize,
+}
+
+impl Ngram {
+    /// Fails if `n` is 0.
+    pub fn new(n: usize) -> Result<Self> {
+        if n == 0 {
+            return Err(Error::runtime("n-gram size must be at least 1"));
+        }
+        Ok(Self { n })
+    }
+}
+
+impl Transform for Ngram {
+    fn apply(&self, value: Value) -> Result<Value> {
+        match value {
+            Value::Array(arr) => {
+                let elements = arr
+                    .elements
+                    .windows(self.n)
+                    .map(|window| {
+                        let tuple = window.iter().map(Value::deep_copy).collect();
+                        Value::Array(Array::from((tuple, arr.level)))
+                    })
+                    .collect();
+                Ok(Value::Array(Array::from((elements, Level::Line))))
+            }
+            other => Ok(other),
+        }
+    }
+}
+
 pub fn extract_key(elem: &Value, selection: &Selection) -> Result<Value> {
     match elem {
         Value::Array(arr) => {
-            let len = arr.len() as i64;
-            let indices = selection_indices(selection, len);
+            let indices = selection_indices(selection, &arr.elements);
 
             if indices.len() == 1 {
                 return arr
@@ -307,4 +382,98 @@ mod tests {
             _ => panic!("expected array"),
         }
     }
+
+    fn row(fields: &[&str]) -> Value {
+        Value::Array(Array::from((
+            fields.iter().map(|s| text(s)).collect(),
+            Level::Word,
+        )))
+    }
+
+    #[test]
+    fn chunk_by_splits_on_key_change() {
+        let input = Value::Array(Array::from((
+            vec![
+                row(&["up", "1"]),
+                row(&["up", "2"]),
+                row(&["down", "3"]),
+                row(&["up", "4"]),
+            ],
+            Level::Line,
+        )));
+        let result = ChunkBy::new(Selection {
+            items: vec![SelectItem::Index(0)],
+        })
+        .apply(input)
+        .unwrap();
+
+        let chunk = |rows: Vec<Value>| Value::Array(Array::from((rows, Level::Line)));
+        assert_eq!(
+            result,
+            Value::Array(Array::from((
+                vec![
+                    chunk(vec![row(&["up", "1"]), row(&["up", "2"])]),
+                    chunk(vec![row(&["down", "3"])]),
+                    chunk(vec![row(&["up", "4"])]),
+                ],
+                Level::Line,
+            )))
+        );
+    }
+
+    #[test]
+    fn chunk_by_keeps_input_level() {
+        let input = Value::Array(Array::from((
+            vec![text("a"), text("a"), text("b")],
+            Level::Word,
+        )));
+        let result = ChunkBy::new(Selection {
+            items: vec![SelectItem::Index(0)],
+        })
+        .apply(input)
+        .unwrap();
+        match result {
+            Value::Array(arr) => {
+                assert_eq!(arr.level, Level::Line);
+                assert_eq!(
+                    arr.elements[0],
+                    Value::Array(Array::from((vec![text("a"), text("a")], Level::Word)))
+                );
+            }
+            _ => panic!("expected array"),
+        }
+    }
+
+    fn words(words: &[&str]) -> Value {
+        Value::Array(Array::from((
+            words.iter().map(|w| text(w)).collect(),
+            Level::Word,
+        )))
+    }
+
+    fn ngrams(tuples: &[&[&str]]) -> Value {
+        Value::Array(Array::from((
+            tuples.iter().map(|t| words(t)).collect(),
+            Level::Line,
+        )))
+    }
+
+    #[test]
+    fn ngram_unigrams_and_bigrams() {
+        let input = words(&["a", "b", "c"]);
+        let unigrams = Ngram::new(1).unwrap().apply(input.deep_copy()).unwrap();
+        assert_eq!(unigrams, ngrams(&[&["a"], &["b"], &["c"]]));
+        let bigrams = Ngram::new(2).unwrap().apply(input).unwrap();
+        assert_eq!(bigrams, ngrams(&[&["a", "b"], &["b", "c"]]));
+    }
+
+    #[test]
+    fn ngram_longer_than_input_is_empty() {
+        let result = Ngram::new(4)
+            .unwrap()
+            .apply(words(&["a", "b", "c"]))
+            .unwrap();
+        assert_eq!(result, ngrams(&[]));
+        assert!(Ngram::new(0).is_err());
+    }
 }
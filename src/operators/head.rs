@@ -0,0 +1,165 @@
+use crate::error::Result;
+use crate::interpreter::Transform;
+use crate::value::{Array, Value};
+
+/// Keeps the first n elements of an array, or the first n chars of text.
+pub struct Head {
+    n: usize,
+}
+
+impl Head {
+    pub fn new(n: usize) -> Self {
+        Self { n }
+    }
+}
+
+impl Transform for Head {
+    fn apply(&self, value: Value) -> Result<Value> {
+        match value {
+            Value::Array(mut arr) => {
+                arr.elements.truncate(self.n);
+                Ok(Value::Array(Array::from((arr.elements, arr.level))))
+            }
+            Value::Text(s) => Ok(Value::Text(s.chars().take(self.n).collect())),
+            Value::Number(n) => Ok(Value::Number(n)),
+            Value::Bool(b) => Ok(Value::Bool(b)),
+        }
+    }
+}
+
+/// Keeps the last n elements of an array, or the last n chars of text.
+pub struct Tail {
+    n: usize,
+}
+
+impl Tail {
+    pub fn new(n: usize) -> Self {
+        Self { n }
+    }
+}
+
+impl Transform for Tail {
+    fn apply(&self, value: Value) -> Result<Value> {
+        match value {
+            Value::Array(mut arr) => {
+                let skip = arr.elements.len().saturating_sub(self.n);
+                arr.elements.drain(..skip);
+                Ok(Value::Array(Array::from((arr.elements, arr.level))))
+            }
+            Value::Text(s) => {
+                let skip = s.chars().count().saturating_sub(self.n);
+                Ok(Value::Text(s.chars().skip(skip).collect()))
+            }
+            Value::Number(n) => Ok(Value::Number(n)),
+            Value::Bool(b) => Ok(Value::Bool(b)),
+        }
+    }
+
+    fn requires_full_input(&self) -> bool {
+        true
+    }
+}
+
+/// Keeps the elements numbered `first` to `last` counting from 1, both
+/// included, like `sed -n 'first,last p'`. Without `last`, keeps everything
+/// from `first` on. Values other than arrays pass through unchanged.
+pub struct LineRange {
+    first: usize,
+    last: Option<usize>,
+}
+
+impl LineRange {
+    pub fn new(first: usize, last: Option<usize>) -> Self {
+        Self { first, last }
+    }
+}
+
+impl Transform for LineRange {
+    fn apply(&self, value: Value) -> Result<Value> {
+        match value {
+            Value::Array(mut arr) => {
+                if let Some(last) = self.last {
+                    arr.elements.truncate(last);
+                }
+                let skip = self.first.saturating_sub(1).min(arr.elements.len());
+                arr.elements.drain(..skip);
+                Ok(Value::Array(arr))
+            }
+            other => Ok(other),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value::Level;
+
+    fn text(s: &str) -> Value {
+        Value::Text(s.to_string())
+    }
+
+    fn line_array(lines: &[&str]) -> Value {
+        Value::Array(Array::from((
+            lines.iter().map(|s| text(s)).collect(),
+            Level::Line,
+        )))
+    }
+
+    #[test]
+    fn head_keeps_first_n() {
+        let result = Head::new(2).apply(line_array(&["a", "b", "c"])).unwrap();
+        assert_eq!(result, line_array(&["a", "b"]));
+    }
+
+    #[test]
+    fn head_of_one_stays_an_array() {
+        let result = Head::new(1).apply(line_array(&["a", "b"])).unwrap();
+        assert_eq!(result, line_array(&["a"]));
+    }
+
+    #[test]
+    fn head_longer_than_input() {
+        let result = Head::new(10).apply(line_array(&["a", "b"])).unwrap();
+        assert_eq!(result, line_array(&["a", "b"]));
+    }
+
+    #[test]
+    fn tail_keeps_last_n() {
+        let result = Tail::new(2).apply(line_array(&["a", "b", "c"])).unwrap();
+        assert_eq!(result, line_array(&["b", "c"]));
+
+        let result = Tail::new(5).apply(line_array(&["a"])).unwrap();
+        assert_eq!(result, line_array(&["a"]));
+    }
+
+    #[test]
+    fn head_and_tail_on_text_use_chars() {
+        assert_eq!(Head::new(3).apply(text("héllo")).unwrap(), text("hél"));
+        assert_eq!(Tail::new(3).apply(text("héllo")).unwrap(), text("llo"));
+    }
+
+    #[test]
+    fn line_range_is_one_based_and_inclusive() {
+        let input = || line_array(&["a", "b", "c", "d", "e"]);
+        let range = |first, last| LineRange::new(first, last).apply(input()).unwrap();
+        assert_eq!(range(2, Some(4)), line_array(&["b", "c", "d"]));
+        assert_eq!(range(1, Some(1)), line_array(&["a"]));
+        assert_eq!(range(5, Some(5)), line_array(&["e"]));
+        assert_eq!(range(1, Some(5)), input());
+    }
+
+    #[test]
+    fn line_range_past_the_end() {
+        let input = || line_array(&["a", "b", "c"]);
+        let range = |first, last| LineRange::new(first, last).apply(input()).unwrap();
+        assert_eq!(range(2, Some(10)), line_array(&["b", "c"]));
+        assert_eq!(range(2, None), line_array(&["b", "c"]));
+        assert_eq!(range(4, None), line_array(&[]));
+        assert_eq!(range(3, Some(2)), line_array(&[]));
+        assert_eq!(
+            LineRange::new(1, None).apply(text("ab")).unwrap(),
+            text("ab")
+        );
+    }
+}
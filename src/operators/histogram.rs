@@ -0,0 +1,175 @@
+use std::io::{self, IsTerminal};
+
+use crate::error::{Error, Result};
+use crate::interpreter::Transform;
+use crate::value::{Array, Value};
+
+/// Width to draw histograms at: the terminal's width, or 80 when not on a terminal.
+pub fn output_width() -> usize {
+    if io::stdout().is_terminal() {
+        crossterm::terminal::size()
+            .map(|(w, _)| w as usize)
+            .unwrap_or(80)
+    } else {
+        80
+    }
+}
+
+/// Renders `[count, label]` rows (as produced by `d`) as a bar chart.
+pub struct Histogram {
+    width: usize,
+}
+
+impl Histogram {
+    pub fn new(width: usize) -> Self {
+        Self { width }
+    }
+}
+
+/// Shorten a label to at most `max` chars, marking the cut with `…`.
+pub(crate) fn truncate_label(label: String, max: usize) -> String {
+    if label.chars().count() <= max {
+        return label;
+    }
+    let mut short: String = label.chars().take(max.saturating_sub(1)).collect();
+    short.push('…');
+    short
+}
+
+impl Transform for Histogram {
+    fn apply(&self, value: Value) -> Result<Value> {
+        let Value::Array(arr) = value else {
+            return Ok(value);
+        };
+
+        let mut rows = Vec::with_capacity(arr.len());
+        for row in &arr.elements {
+            let (count, label) = match row {
+                Value::Array(row) if row.len() == 2 => (&row.elements[0], &row.elements[1]),
+                _ => {
+                    return Err(Error::runtime(
+                        "b expects rows of [count, label] (use d first)",
+                    ));
+                }
+            };
+            let Some(n) = count.as_number() else {
+                return Err(Error::runtime(format!(
+                    "b expects numeric counts, got '{}'",
+                    count
+                )));
+            };
+            rows.push((n.max(0.0), count.to_string(), label.to_string()));
+        }
+
+        // Labels get at most a third of the line; counts are never cut
+        let label_width = rows
+            .iter()
+            .map(|(_, _, label)| label.chars().count())
+            .max()
+            .unwrap_or(0)
+            .min((self.width / 3).max(1));
+        let count_width = rows
+            .iter()
+            .map(|(_, count, _)| count.chars().count())
+            .max()
+            .unwrap_or(0);
+        let bar_width = self
+            .width
+            .saturating_sub(label_width + count_width + 2)
+            .max(1);
+        let max = rows.iter().map(|(n, _, _)| *n).fold(0.0, f64::max);
+
+        let elements = rows
+            .into_iter()
+            .map(|(n, count, label)| {
+                let bar_len = if n > 0.0 {
+                    ((n / max * bar_width as f64).round() as usize).max(1)
+                } else {
+                    0
+                };
+                Value::Text(format!(
+                    "{:<label_width$} {} {}",
+                    truncate_label(label, label_width),
+                    "█".repeat(bar_len),
+                    count
+                ))
+            })
+            .collect();
+        Ok(Value::Array(Array::from((elements, arr.level))))
+    }
+
+    fn requires_full_input(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value::Level;
+
+    fn text(s: &str) -> Value {
+        Value::Text(s.to_string())
+    }
+
+    fn counts(rows: &[(f64, &str)]) -> Value {
+        Value::Array(Array::from((
+            rows.iter()
+                .map(|&(n, label)| {
+                    Value::Array(Array::from((
+                        vec![Value::Number(n), text(label)],
+                        Level::Word,
+                    )))
+                })
+                .collect(),
+            Level::Line,
+        )))
+    }
+
+    fn lines(value: Value) -> Vec<String> {
+        match value {
+            Value::Array(arr) => arr.elements.iter().map(|v| v.to_string()).collect(),
+            _ => panic!("expected array"),
+        }
+    }
+
+    #[test]
+    fn histogram_scales_bars_to_width() {
+        // 30 columns: label 3 + count 2 + 2 spaces leaves 23 for the bar
+        let result = Histogram::new(30)
+            .apply(counts(&[(10.0, "the"), (5.0, "cat"), (1.0, "a")]))
+            .unwrap();
+        assert_eq!(
+            lines(result),
+            vec![
+                format!("the {} 10", "█".repeat(23)),
+                format!("cat {} 5", "█".repeat(12)),
+                format!("a   {} 1", "█".repeat(2)),
+            ]
+        );
+    }
+
+    #[test]
+    fn histogram_zero_count_has_no_bar() {
+        let result = Histogram::new(20)
+            .apply(counts(&[(2.0, "x"), (0.0, "y")]))
+            .unwrap();
+        assert_eq!(lines(result)[1], "y  0");
+    }
+
+    #[test]
+    fn histogram_truncates_long_labels() {
+        let result = Histogram::new(30)
+            .apply(counts(&[(1.0, "a-very-long-label-indeed")]))
+            .unwrap();
+        let line = &lines(result)[0];
+        assert!(line.starts_with("a-very-lo… █"));
+        assert_eq!(line.chars().count(), 30);
+    }
+
+    #[test]
+    fn histogram_rejects_other_shapes() {
+        let input = Value::Array(Array::from((vec![text("a b")], Level::Line)));
+        assert!(Histogram::new(80).apply(input).is_err());
+    }
+}
@@ -2,6 +2,8 @@ use crate::error::Result;
 use crate::interpreter::Transform;
 use crate::value::{Array, Value};
 
+use super::split::CsvFormat;
+
 /// Join mode determines how `j` joins strings.
 #[derive(Debug, Clone, Default)]
 pub enum JoinMode {
@@ -11,7 +13,7 @@ pub enum JoinMode {
     /// Join with a specific delimiter
     Delimiter(String),
     /// Join as CSV fields
-    Csv,
+    Csv(CsvFormat),
 }
 
 pub struct Join {
@@ -62,7 +64,7 @@ fn join_array(arr: Array, mode: &JoinMode) -> Value {
     let delimiter = match mode {
         JoinMode::Semantic => arr.level.join_delimiter(),
         JoinMode::Delimiter(delim) => delim.as_str(),
-        JoinMode::Csv => ",", // CSV handled specially below
+        JoinMode::Csv(_) => ",", // CSV handled specially below
     };
 
     let parts: Vec<String> = arr
@@ -71,6 +73,7 @@ fn join_array(arr: Array, mode: &JoinMode) -> Value {
         .map(|v| match v {
             Value::Text(s) => s,
             Value::Number(n) => n.to_string(),
+            Value::Bool(b) => b.to_string(),
             Value::Array(inner) => match join_array(inner, mode) {
                 Value::Text(s) => s,
                 Value::Number(n) => n.to_string(),
@@ -80,11 +83,14 @@ fn join_array(arr: Array, mode: &JoinMode) -> Value {
         .collect();
 
     let joined = match mode {
-        JoinMode::Csv => {
+        JoinMode::Csv(format) => {
             if parts.is_empty() {
                 String::new()
             } else {
-                let mut writer = csv::Writer::from_writer(vec![]);
+                let mut writer = csv::WriterBuilder::new()
+                    .delimiter(format.delimiter)
+                    .quote(format.quote)
+                    .from_writer(vec![]);
                 writer.write_record(&parts).ok();
                 writer.flush().ok();
                 let data = writer.into_inner().unwrap_or_default();
@@ -117,6 +123,7 @@ impl Transform for JoinDelim {
                     .map(|v| match v {
                         Value::Text(s) => s,
                         Value::Number(n) => n.to_string(),
+                        Value::Bool(b) => b.to_string(),
                         Value::Array(inner) => inner.to_string(),
                     })
                     .collect();
@@ -308,7 +315,9 @@ mod tests {
     fn join_csv_on_inner_arrays() {
         let inner1 = word_array(&["a", "b,c", "d"]);
         let outer = Value::Array(Array::from((vec![inner1], Level::Line)));
-        let result = Join::new(JoinMode::Csv).apply(outer).unwrap();
+        let result = Join::new(JoinMode::Csv(CsvFormat::default()))
+            .apply(outer)
+            .unwrap();
         match result {
             Value::Array(arr) => {
                 assert_eq!(arr.len(), 1);
@@ -318,6 +327,25 @@ mod tests {
         }
     }
 
+    #[test]
+    fn join_csv_custom_delimiter_and_quote() {
+        let format = CsvFormat {
+            delimiter: b';',
+            quote: b'\'',
+        };
+        let inner1 = word_array(&["a", "b;c", "it's", "1,5"]);
+        let outer = Value::Array(Array::from((vec![inner1], Level::Line)));
+        let result = Join::new(JoinMode::Csv(format)).apply(outer).unwrap();
+        // The custom quote is doubled inside a quoted field; commas need no quoting
+        assert_eq!(
+            result,
+            Value::Array(Array::from((
+                vec![text("a;'b;c';'it''s';1,5")],
+                Level::Line
+            )))
+        );
+    }
+
     // JoinDelim tests
 
     #[test]
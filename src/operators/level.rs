@@ -0,0 +1,69 @@
+use crate::error::Result;
+use crate::interpreter::Transform;
+use crate::value::{Level, Value};
+
+/// Relabels an array's level without touching its elements, which changes
+/// how `j` joins it and what `s` splits it into.
+pub struct SetLevel {
+    level: Level,
+}
+
+impl SetLevel {
+    pub fn new(level: Level) -> Self {
+        Self { level }
+    }
+}
+
+impl Transform for SetLevel {
+    fn apply(&self, value: Value) -> Result<Value> {
+        match value {
+            Value::Array(mut arr) => {
+                arr.level = self.level;
+                Ok(Value::Array(arr))
+            }
+            other => Ok(other),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operators::Join;
+    use crate::value::Array;
+
+    fn text(s: &str) -> Value {
+        Value::Text(s.to_string())
+    }
+
+    fn row(level: Level) -> Value {
+        Value::Array(Array::from((vec![text("a"), text("b")], level)))
+    }
+
+    /// Join a single row, as `j` does for each element.
+    fn join(row: Value) -> Value {
+        let input = Value::Array(Array::from((vec![row], Level::Line)));
+        match Join::default().apply(input).unwrap() {
+            Value::Array(mut arr) => arr.elements.remove(0),
+            _ => panic!("expected array"),
+        }
+    }
+
+    #[test]
+    fn set_level_changes_join_delimiter() {
+        assert_eq!(join(row(Level::Word)), text("a b"));
+
+        let as_lines = SetLevel::new(Level::Line).apply(row(Level::Word)).unwrap();
+        assert_eq!(as_lines, row(Level::Line));
+        assert_eq!(join(as_lines), text("a\nb"));
+
+        let as_chars = SetLevel::new(Level::Char).apply(row(Level::Word)).unwrap();
+        assert_eq!(join(as_chars), text("ab"));
+    }
+
+    #[test]
+    fn set_level_non_array_is_identity() {
+        let result = SetLevel::new(Level::File).apply(text("a b")).unwrap();
+        assert_eq!(result, text("a b"));
+    }
+}
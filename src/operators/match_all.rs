@@ -23,32 +23,66 @@ impl MatchAll {
 
 impl Transform for MatchAll {
     fn apply(&self, value: Value) -> Result<Value> {
-        match value {
-            Value::Array(arr) => {
-                let results: Vec<Value> = arr
-                    .elements
-                    .into_iter()
-                    .map(|elem| {
-                        let text = match &elem {
-                            Value::Text(s) => s.clone(),
-                            Value::Number(n) => n.to_string(),
-                            Value::Array(inner) => inner.to_string(),
-                        };
-                        let matches = self.extract_matches(&text);
-                        Value::Array(Array::from((matches, Level::Word)))
-                    })
-                    .collect();
-                Ok(Value::Array(Array::from((results, arr.level))))
-            }
-            Value::Text(s) => {
-                let matches = self.extract_matches(&s);
-                Ok(Value::Array(Array::from((matches, Level::Word))))
-            }
-            Value::Number(n) => {
-                let matches = self.extract_matches(&n.to_string());
-                Ok(Value::Array(Array::from((matches, Level::Word))))
-            }
+        Ok(map_to_matches(value, |s| self.extract_matches(s)))
+    }
+}
+
+/// Like `MatchAll`, but extracts capture group 1 when the pattern has one.
+pub struct Extract {
+    pattern: Regex,
+}
+
+impl Extract {
+    pub fn new(pattern: Regex) -> Self {
+        Self { pattern }
+    }
+
+    fn extract_matches(&self, text: &str) -> Vec<Value> {
+        let group = if self.pattern.captures_len() > 1 {
+            1
+        } else {
+            0
+        };
+        self.pattern
+            .captures_iter(text)
+            .filter_map(|caps| caps.get(group))
+            .map(|m| Value::Text(m.as_str().to_string()))
+            .collect()
+    }
+}
+
+impl Transform for Extract {
+    fn apply(&self, value: Value) -> Result<Value> {
+        Ok(map_to_matches(value, |s| self.extract_matches(s)))
+    }
+}
+
+/// Replace each element of an array (or a single value) with a word-level
+/// array of its matches. The outer array keeps its level.
+fn map_to_matches<F>(value: Value, matches: F) -> Value
+where
+    F: Fn(&str) -> Vec<Value>,
+{
+    match value {
+        Value::Array(arr) => {
+            let results: Vec<Value> = arr
+                .elements
+                .into_iter()
+                .map(|elem| {
+                    let text = match &elem {
+                        Value::Text(s) => s.clone(),
+                        Value::Number(n) => n.to_string(),
+                        Value::Bool(b) => b.to_string(),
+                        Value::Array(inner) => inner.to_string(),
+                    };
+                    Value::Array(Array::from((matches(&text), Level::Word)))
+                })
+                .collect();
+            Value::Array(Array::from((results, arr.level)))
         }
+        Value::Text(s) => Value::Array(Array::from((matches(&s), Level::Word))),
+        Value::Number(n) => Value::Array(Array::from((matches(&n.to_string()), Level::Word))),
+        Value::Bool(b) => Value::Array(Array::from((matches(&b.to_string()), Level::Word))),
     }
 }
 
@@ -225,4 +259,52 @@ mod tests {
             _ => panic!("expected array"),
         }
     }
+
+    #[test]
+    fn extract_whole_match_without_groups() {
+        let input = arr(
+            vec![text("from 10.0.0.1 to 10.0.0.2"), text("no address")],
+            Level::Line,
+        );
+        let extract = Extract::new(Regex::new(r"\d+\.\d+\.\d+\.\d+").unwrap());
+        let result = extract.apply(input).unwrap();
+        assert_eq!(
+            result,
+            arr(
+                vec![
+                    arr(vec![text("10.0.0.1"), text("10.0.0.2")], Level::Word),
+                    arr(vec![], Level::Word),
+                ],
+                Level::Line
+            )
+        );
+    }
+
+    #[test]
+    fn extract_first_capture_group() {
+        let input = arr(
+            vec![text("user=alice id=1 user=bob"), text("user=carol")],
+            Level::File,
+        );
+        let extract = Extract::new(Regex::new(r"user=(\w+)").unwrap());
+        let result = extract.apply(input).unwrap();
+        assert_eq!(
+            result,
+            arr(
+                vec![
+                    arr(vec![text("alice"), text("bob")], Level::Word),
+                    arr(vec![text("carol")], Level::Word),
+                ],
+                Level::File
+            )
+        );
+    }
+
+    #[test]
+    fn extract_skips_unmatched_optional_group() {
+        let input = text("a=1 b a=2");
+        let extract = Extract::new(Regex::new(r"\w(?:=(\d))?").unwrap());
+        let result = extract.apply(input).unwrap();
+        assert_eq!(result, arr(vec![text("1"), text("2")], Level::Word));
+    }
 }
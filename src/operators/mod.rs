@@ -1,41 +1,83 @@
+mod agg;
+mod ansi;
+mod arith;
 mod case;
 mod columnate;
+mod comment;
 mod count;
+#[cfg(feature = "dates")]
+mod date;
 mod dedupe;
 mod delete_empty;
+mod expand;
 mod filter;
 mod flatten;
+mod format;
 mod group;
+mod head;
+mod histogram;
 mod join;
+mod level;
 mod match_all;
 mod navigate;
 mod noop;
 mod number;
 mod partition;
+mod random;
+mod repeat;
 mod replace;
+mod reverse;
+mod rot;
 mod select;
 mod sort;
 mod split;
+mod squeeze;
 mod sum;
 mod trim;
+mod wrap;
 
-pub use case::{Lowercase, LowercaseSelected, Uppercase, UppercaseSelected};
+pub use agg::{Agg, GroupAgg};
+pub use ansi::StripAnsi;
+pub use arith::{AddScalar, Arithmetic, Clamp, MulScalar};
+pub use case::{ConvertCase, Lowercase, LowercaseSelected, Slugify, Uppercase, UppercaseSelected};
 pub use columnate::Columnate;
-pub use count::Count;
-pub use dedupe::{DedupeSelectionWithCounts, DedupeWithCounts};
+pub use comment::StripComments;
+pub use count::{Count, CountLeaves, Stats};
+#[cfg(feature = "dates")]
+pub use date::{FormatDate, ParseDate, ToEpoch};
+pub use dedupe::{
+    AdjacentDedupe, DedupeSelection, DedupeSelectionWithCounts, DedupeWithCounts,
+    DedupeWithCountsIgnoreCase, DedupeWithPercentages, Mode, value_to_key,
+};
 pub use delete_empty::DeleteEmpty;
-pub use filter::Filter;
+pub use expand::ExpandTabs;
+pub use filter::{
+    ApplyIf, Compare, CompareLength, Filter, FilterContext, FilterFields, FilterSelected, Matches,
+};
 pub use flatten::Flatten;
-pub use group::GroupBy;
+pub use format::Format;
+pub use group::{ChunkBy, GroupBy, Ngram};
+pub use head::{Head, LineRange, Tail};
+pub use histogram::{Histogram, output_width};
 pub use join::{Join, JoinDelim, JoinMode};
-pub use match_all::MatchAll;
-pub use navigate::{Ascend, Descend};
+pub use level::SetLevel;
+pub use match_all::{Extract, MatchAll};
+pub use navigate::{Ascend, DeepMap, Descend};
 pub use noop::NoOp;
 pub use number::{ToNumber, ToNumberSelected};
 pub use partition::Partition;
+pub use random::{Sample, Shuffle, random_seed};
+pub use repeat::Repeat;
 pub use replace::Replace;
+pub use reverse::ReverseChars;
+pub use rot::Rot;
 pub use select::Select;
-pub use sort::{SortAscending, SortDescending};
-pub use split::{Split, SplitDelim, SplitMode};
-pub use sum::Sum;
-pub use trim::{Trim, TrimSelected};
+pub use sort::{Rank, SortAscending, SortBy, SortChars, SortDescending, SortUnique};
+pub use split::{
+    CsvFormat, ParseKv, Split, SplitAny, SplitDelim, SplitMode, SplitSentences, detect_split_mode,
+    header_names, split_rows,
+};
+pub use squeeze::Squeeze;
+pub use sum::{MovingAverage, Quantile, StdDev, Sum, Variance, WeightedSum};
+pub use trim::{Trim, TrimChars, TrimCharsSelected, TrimSelected};
+pub use wrap::Wrap;
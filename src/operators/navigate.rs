@@ -1,4 +1,6 @@
-use crate::interpreter::{Context, Navigate};
+use crate::error::Result;
+use crate::interpreter::{Context, Navigate, Transform};
+use crate::value::Value;
 
 pub struct Descend;
 
@@ -15,3 +17,115 @@ impl Navigate for Ascend {
         ctx.ascend();
     }
 }
+
+/// `*<op>` - applies a transform to every leaf (text or number), however
+/// deeply it is nested.
+pub struct DeepMap {
+    inner: Box<dyn Transform>,
+}
+
+impl DeepMap {
+    pub fn new(inner: Box<dyn Transform>) -> Self {
+        Self { inner }
+    }
+}
+
+impl Transform for DeepMap {
+    fn apply(&self, value: Value) -> Result<Value> {
+        match value {
+            Value::Array(mut arr) => {
+                arr.elements = arr
+                    .elements
+                    .into_iter()
+                    .map(|v| self.apply(v))
+                    .collect::<Result<_>>()?;
+                Ok(Value::Array(arr))
+            }
+            leaf => self.inner.apply(leaf),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operators::{Lowercase, ToNumber};
+    use crate::value::{Array, Level};
+
+    fn text(s: &str) -> Value {
+        Value::Text(s.to_string())
+    }
+
+    fn array(values: Vec<Value>, level: Level) -> Value {
+        Value::Array(Array::from((values, level)))
+    }
+
+    #[test]
+    fn deep_map_reaches_leaves_at_any_depth() {
+        // [[["A", "B"], ["C"]], "D", [[["E"]]]]
+        let input = array(
+            vec![
+                array(
+                    vec![
+                        array(vec![text("A"), text("B")], Level::Char),
+                        array(vec![text("C")], Level::Char),
+                    ],
+                    Level::Word,
+                ),
+                text("D"),
+                array(
+                    vec![array(
+                        vec![array(vec![text("E")], Level::Char)],
+                        Level::Char,
+                    )],
+                    Level::Word,
+                ),
+            ],
+            Level::Line,
+        );
+        let result = DeepMap::new(Box::new(Lowercase)).apply(input).unwrap();
+        let expected = array(
+            vec![
+                array(
+                    vec![
+                        array(vec![text("a"), text("b")], Level::Char),
+                        array(vec![text("c")], Level::Char),
+                    ],
+                    Level::Word,
+                ),
+                text("d"),
+                array(
+                    vec![array(
+                        vec![array(vec![text("e")], Level::Char)],
+                        Level::Char,
+                    )],
+                    Level::Word,
+                ),
+            ],
+            Level::Line,
+        );
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn deep_map_keeps_empty_arrays_and_numbers() {
+        let input = array(
+            vec![
+                array(vec![], Level::Word),
+                array(vec![text("1"), Value::Number(2.0)], Level::Word),
+            ],
+            Level::Line,
+        );
+        let result = DeepMap::new(Box::new(ToNumber::new(false)))
+            .apply(input)
+            .unwrap();
+        let expected = array(
+            vec![
+                array(vec![], Level::Word),
+                array(vec![Value::Number(1.0), Value::Number(2.0)], Level::Word),
+            ],
+            Level::Line,
+        );
+        assert_eq!(result, expected);
+    }
+}
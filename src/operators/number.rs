@@ -1,11 +1,21 @@
 use crate::ast::Selection;
-use crate::error::Result;
+use crate::error::{Error, Result};
 use crate::interpreter::Transform;
 use crate::value::Value;
 
 use super::select::apply_to_selected;
 
-pub struct ToNumber;
+pub struct ToNumber {
+    strict: bool,
+}
+
+impl ToNumber {
+    /// In strict mode, text that doesn't parse as a number is an error
+    /// instead of being left unchanged.
+    pub fn new(strict: bool) -> Self {
+        Self { strict }
+    }
+}
 
 impl Transform for ToNumber {
     fn apply(&self, value: Value) -> Result<Value> {
@@ -18,29 +28,40 @@ impl Transform for ToNumber {
                     .collect::<Result<_>>()?;
                 Ok(Value::Array(arr))
             }
-            Value::Text(s) => Ok(s
-                .parse::<f64>()
-                .map(Value::Number)
-                .unwrap_or(Value::Text(s))),
+            Value::Text(s) => match s.parse::<f64>() {
+                Ok(n) => Ok(Value::Number(n)),
+                Err(_) if self.strict => Err(Error::runtime(format!(
+                    "cannot convert '{}' to a number",
+                    s
+                ))),
+                Err(_) => Ok(Value::Text(s)),
+            },
             Value::Number(n) => Ok(Value::Number(n)),
+            Value::Bool(b) => Ok(Value::Number(if b { 1.0 } else { 0.0 })),
         }
     }
 }
 
 pub struct ToNumberSelected {
     selection: Selection,
+    to_number: ToNumber,
 }
 
 impl ToNumberSelected {
-    pub fn new(selection: Selection) -> Self {
-        Self { selection }
+    pub fn new(selection: Selection, strict: bool) -> Self {
+        Self {
+            selection,
+            to_number: ToNumber::new(strict),
+        }
     }
 }
 
 impl Transform for ToNumberSelected {
     fn apply(&self, value: Value) -> Result<Value> {
         match value {
-            Value::Array(arr) => apply_to_selected(arr, &self.selection, |v| ToNumber.apply(v)),
+            Value::Array(arr) => {
+                apply_to_selected(arr, &self.selection, |v| self.to_number.apply(v))
+            }
             other => Ok(other),
         }
     }
@@ -66,49 +87,57 @@ mod tests {
     #[test]
     fn to_number_integer() {
         let input = text("42");
-        let result = ToNumber.apply(input).unwrap();
+        let result = ToNumber::new(false).apply(input).unwrap();
         assert_eq!(result, Value::Number(42.0));
     }
 
     #[test]
+    #[allow(clippy::approx_constant)]
     fn to_number_float() {
         let input = text("3.14");
-        let result = ToNumber.apply(input).unwrap();
+        let result = ToNumber::new(false).apply(input).unwrap();
         assert_eq!(result, Value::Number(3.14));
     }
 
+    #[test]
+    fn to_number_exact_fraction() {
+        let input = text("2.5");
+        let result = ToNumber::new(false).apply(input).unwrap();
+        assert_eq!(result, Value::Number(2.5));
+    }
+
     #[test]
     fn to_number_negative() {
         let input = text("-123");
-        let result = ToNumber.apply(input).unwrap();
+        let result = ToNumber::new(false).apply(input).unwrap();
         assert_eq!(result, Value::Number(-123.0));
     }
 
     #[test]
     fn to_number_non_numeric() {
         let input = text("hello");
-        let result = ToNumber.apply(input).unwrap();
+        let result = ToNumber::new(false).apply(input).unwrap();
         assert_eq!(result, text("hello"));
     }
 
     #[test]
     fn to_number_empty_string() {
         let input = text("");
-        let result = ToNumber.apply(input).unwrap();
+        let result = ToNumber::new(false).apply(input).unwrap();
         assert_eq!(result, text(""));
     }
 
     #[test]
     fn to_number_preserves_number() {
         let input = Value::Number(42.0);
-        let result = ToNumber.apply(input).unwrap();
+        let result = ToNumber::new(false).apply(input).unwrap();
         assert_eq!(result, Value::Number(42.0));
     }
 
     #[test]
     fn to_number_array() {
         let input = line_array(&["1", "2", "3"]);
-        let result = ToNumber.apply(input).unwrap();
+        let result = ToNumber::new(false).apply(input).unwrap();
         match result {
             Value::Array(arr) => {
                 assert_eq!(arr.elements[0], Value::Number(1.0));
@@ -122,7 +151,7 @@ mod tests {
     #[test]
     fn to_number_array_mixed() {
         let input = line_array(&["1", "hello", "3"]);
-        let result = ToNumber.apply(input).unwrap();
+        let result = ToNumber::new(false).apply(input).unwrap();
         match result {
             Value::Array(arr) => {
                 assert_eq!(arr.elements.len(), 3);
@@ -140,7 +169,7 @@ mod tests {
         let sel = Selection {
             items: vec![SelectItem::Index(0)],
         };
-        let result = ToNumberSelected::new(sel).apply(input).unwrap();
+        let result = ToNumberSelected::new(sel, false).apply(input).unwrap();
         match result {
             Value::Array(arr) => {
                 assert_eq!(arr.elements[0], Value::Number(1.0));
@@ -161,7 +190,7 @@ mod tests {
                 step: None,
             })],
         };
-        let result = ToNumberSelected::new(sel).apply(input).unwrap();
+        let result = ToNumberSelected::new(sel, false).apply(input).unwrap();
         match result {
             Value::Array(arr) => {
                 assert_eq!(arr.elements[0], text("1"));
@@ -178,7 +207,7 @@ mod tests {
         let sel = Selection {
             items: vec![SelectItem::Index(0), SelectItem::Index(2)],
         };
-        let result = ToNumberSelected::new(sel).apply(input).unwrap();
+        let result = ToNumberSelected::new(sel, false).apply(input).unwrap();
         match result {
             Value::Array(arr) => {
                 assert_eq!(arr.elements[0], Value::Number(1.0));
@@ -196,7 +225,7 @@ mod tests {
         let sel = Selection {
             items: vec![SelectItem::Index(-1)],
         };
-        let result = ToNumberSelected::new(sel).apply(input).unwrap();
+        let result = ToNumberSelected::new(sel, false).apply(input).unwrap();
         match result {
             Value::Array(arr) => {
                 assert_eq!(arr.elements[0], text("1"));
@@ -213,7 +242,42 @@ mod tests {
         let sel = Selection {
             items: vec![SelectItem::Index(0)],
         };
-        let result = ToNumberSelected::new(sel).apply(input).unwrap();
+        let result = ToNumberSelected::new(sel, false).apply(input).unwrap();
         assert_eq!(result, text("hello"));
     }
+
+    #[test]
+    fn to_number_strict_parses_numbers() {
+        let input = line_array(&["1", "2.5", "-3"]);
+        let result = ToNumber::new(true).apply(input).unwrap();
+        match result {
+            Value::Array(arr) => {
+                assert_eq!(arr.elements[1], Value::Number(2.5));
+                assert_eq!(arr.elements[2], Value::Number(-3.0));
+            }
+            _ => panic!("expected array"),
+        }
+    }
+
+    #[test]
+    fn to_number_strict_rejects_non_numeric() {
+        let input = line_array(&["1", "hello", "3"]);
+        let err = ToNumber::new(true).apply(input).unwrap_err();
+        assert_eq!(err.message, "cannot convert 'hello' to a number");
+    }
+
+    #[test]
+    fn to_number_selected_strict_ignores_unselected() {
+        let input = line_array(&["1", "hello"]);
+        let sel = Selection {
+            items: vec![SelectItem::Index(0)],
+        };
+        assert!(ToNumberSelected::new(sel, true).apply(input).is_ok());
+
+        let input = line_array(&["1", "hello"]);
+        let sel = Selection {
+            items: vec![SelectItem::Index(1)],
+        };
+        assert!(ToNumberSelected::new(sel, true).apply(input).is_err());
+    }
 }
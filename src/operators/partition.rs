@@ -3,8 +3,10 @@ use crate::error::Result;
 use crate::interpreter::Transform;
 use crate::value::{Array, Level, Value};
 
-use super::select::selection_indices;
+use super::select::{selection_indices, text_selection_indices};
 
+/// Splits an array or string before each selected index. Negative indices
+/// are resolved from the end first, so `p-1` splits off the last element.
 pub struct Partition {
     selection: Selection,
 }
@@ -19,8 +21,7 @@ impl Transform for Partition {
     fn apply(&self, value: Value) -> Result<Value> {
         match value {
             Value::Array(arr) => {
-                let len = arr.len() as i64;
-                let mut split_points = selection_indices(&self.selection, len);
+                let mut split_points = selection_indices(&self.selection, &arr.elements);
                 split_points.sort();
                 split_points.dedup();
 
@@ -56,8 +57,7 @@ impl Transform for Partition {
             }
             Value::Text(s) => {
                 let chars: Vec<char> = s.chars().collect();
-                let len = chars.len() as i64;
-                let mut split_points = selection_indices(&self.selection, len);
+                let mut split_points = text_selection_indices(&self.selection, chars.len() as i64);
                 split_points.sort();
                 split_points.dedup();
 
@@ -86,6 +86,7 @@ impl Transform for Partition {
                 Ok(Value::Array(Array::from((result, Level::Word))))
             }
             Value::Number(n) => Ok(Value::Number(n)),
+            Value::Bool(b) => Ok(Value::Bool(b)),
         }
     }
 }
@@ -330,4 +331,48 @@ mod tests {
             _ => panic!("expected array"),
         }
     }
+
+    fn chunk_lens(value: Value) -> Vec<usize> {
+        match value {
+            Value::Array(arr) => arr
+                .elements
+                .iter()
+                .map(|chunk| match chunk {
+                    Value::Array(chunk) => chunk.len(),
+                    _ => panic!("expected array"),
+                })
+                .collect(),
+            _ => panic!("expected array"),
+        }
+    }
+
+    #[test]
+    fn partition_negative_indices_count_from_end() {
+        let partition = |items| {
+            let input = line_array(&["a", "b", "c", "d", "e"]);
+            chunk_lens(Partition::new(Selection { items }).apply(input).unwrap())
+        };
+        assert_eq!(partition(vec![SelectItem::Index(-1)]), vec![4, 1]);
+        assert_eq!(partition(vec![SelectItem::Index(-2)]), vec![3, 2]);
+        assert_eq!(
+            partition(vec![SelectItem::Index(-1), SelectItem::Index(-2)]),
+            vec![3, 1, 1]
+        );
+        // -5 is the first element, so doesn't split; -6 is out of range
+        assert_eq!(partition(vec![SelectItem::Index(-5)]), vec![5]);
+        assert_eq!(partition(vec![SelectItem::Index(-6)]), vec![5]);
+    }
+
+    #[test]
+    fn partition_string_negative_index() {
+        let result = Partition::new(Selection {
+            items: vec![SelectItem::Index(-1)],
+        })
+        .apply(text("hello"))
+        .unwrap();
+        assert_eq!(
+            result,
+            Value::Array(Array::from((vec![text("hell"), text("o")], Level::Word)))
+        );
+    }
 }
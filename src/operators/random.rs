@@ -0,0 +1,204 @@
+use std::cell::Cell;
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::error::Result;
+use crate::interpreter::Transform;
+use crate::value::{Array, Value};
+
+/// Returns a seed for runs without `--seed`.
+pub fn random_seed() -> u64 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos() as u64);
+    RandomState::new().hash_one(nanos)
+}
+
+/// A small SplitMix64 generator. Good enough for sampling, and reproducible
+/// for a given seed across platforms.
+struct Rng {
+    state: Cell<u64>,
+}
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        Self {
+            state: Cell::new(seed),
+        }
+    }
+
+    fn next_u64(&self) -> u64 {
+        let state = self.state.get().wrapping_add(0x9e37_79b9_7f4a_7c15);
+        self.state.set(state);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number in `0..bound`.
+    fn below(&self, bound: usize) -> usize {
+        ((self.next_u64() as u128 * bound as u128) >> 64) as usize
+    }
+}
+
+/// Keeps n elements chosen uniformly at random, in their original order.
+pub struct Sample {
+    n: usize,
+    rng: Rng,
+}
+
+impl Sample {
+    pub fn new(n: usize, seed: u64) -> Self {
+        Self {
+            n,
+            rng: Rng::new(seed),
+        }
+    }
+}
+
+impl Transform for Sample {
+    fn apply(&self, value: Value) -> Result<Value> {
+        match value {
+            Value::Array(arr) => {
+                // Reservoir sampling, tagging each pick with its position
+                let mut reservoir: Vec<(usize, Value)> = Vec::with_capacity(self.n);
+                for (i, elem) in arr.elements.into_iter().enumerate() {
+                    if i < self.n {
+                        reservoir.push((i, elem));
+                    } else {
+                        let j = self.rng.below(i + 1);
+                        if j < self.n {
+                            reservoir[j] = (i, elem);
+                        }
+                    }
+                }
+                reservoir.sort_by_key(|(i, _)| *i);
+                let elements = reservoir.into_iter().map(|(_, v)| v).collect();
+                Ok(Value::Array(Array::from((elements, arr.level))))
+            }
+            other => Ok(other),
+        }
+    }
+
+    fn requires_full_input(&self) -> bool {
+        true
+    }
+}
+
+/// Randomly permutes the elements of an array.
+pub struct Shuffle {
+    rng: Rng,
+}
+
+impl Shuffle {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: Rng::new(seed),
+        }
+    }
+}
+
+impl Transform for Shuffle {
+    fn apply(&self, value: Value) -> Result<Value> {
+        match value {
+            Value::Array(mut arr) => {
+                // Fisher-Yates
+                for i in (1..arr.elements.len()).rev() {
+                    let j = self.rng.below(i + 1);
+                    arr.elements.swap(i, j);
+                }
+                Ok(Value::Array(arr))
+            }
+            other => Ok(other),
+        }
+    }
+
+    fn requires_full_input(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value::Level;
+
+    fn numbers(range: std::ops::Range<usize>) -> Value {
+        Value::Array(Array::from((
+            range.map(|n| Value::Number(n as f64)).collect(),
+            Level::Line,
+        )))
+    }
+
+    fn elements(value: Value) -> Vec<Value> {
+        match value {
+            Value::Array(arr) => arr.elements,
+            _ => panic!("expected array"),
+        }
+    }
+
+    #[test]
+    fn rng_below_stays_in_bounds() {
+        let rng = Rng::new(7);
+        assert!((0..1000).all(|_| rng.below(10) < 10));
+    }
+
+    #[test]
+    fn sample_picks_n_distinct_in_order() {
+        let result = elements(Sample::new(5, 42).apply(numbers(0..100)).unwrap());
+        assert_eq!(result.len(), 5);
+        let picked: Vec<f64> = result.iter().filter_map(|v| v.as_number()).collect();
+        assert!(picked.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn sample_is_reproducible_with_seed() {
+        let a = elements(Sample::new(5, 42).apply(numbers(0..100)).unwrap());
+        let b = elements(Sample::new(5, 42).apply(numbers(0..100)).unwrap());
+        let c = elements(Sample::new(5, 43).apply(numbers(0..100)).unwrap());
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+    }
+
+    #[test]
+    fn sample_larger_than_input_keeps_everything() {
+        let result = Sample::new(10, 1).apply(numbers(0..3)).unwrap();
+        assert_eq!(result, numbers(0..3));
+    }
+
+    #[test]
+    fn sample_preserves_level() {
+        let input = Value::Array(Array::from((
+            vec![Value::Text("a".to_string()), Value::Text("b".to_string())],
+            Level::Word,
+        )));
+        match Sample::new(1, 3).apply(input).unwrap() {
+            Value::Array(arr) => {
+                assert_eq!(arr.level, Level::Word);
+                assert_eq!(arr.len(), 1);
+            }
+            _ => panic!("expected array"),
+        }
+    }
+
+    #[test]
+    fn shuffle_is_a_reproducible_permutation() {
+        let a = elements(Shuffle::new(9).apply(numbers(0..50)).unwrap());
+        let b = elements(Shuffle::new(9).apply(numbers(0..50)).unwrap());
+        assert_eq!(a, b);
+        assert_ne!(a, elements(numbers(0..50)));
+
+        let mut sorted: Vec<f64> = a.iter().filter_map(|v| v.as_number()).collect();
+        sorted.sort_by(f64::total_cmp);
+        let expected: Vec<f64> = (0..50).map(|n| n as f64).collect();
+        assert_eq!(sorted, expected);
+    }
+
+    #[test]
+    fn shuffle_non_array_is_identity() {
+        let result = Shuffle::new(1).apply(Value::Number(3.0)).unwrap();
+        assert_eq!(result, Value::Number(3.0));
+    }
+}
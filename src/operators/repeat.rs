@@ -0,0 +1,76 @@
+use crate::error::Result;
+use crate::interpreter::Transform;
+use crate::value::{Array, Value};
+
+/// Repeats elements `n` times: each element in place (`[a, a, b, b]`), or
+/// the whole array (`[a, b, a, b]`). A count of 0 gives an empty array.
+pub struct Repeat {
+    n: usize,
+    whole: bool,
+}
+
+impl Repeat {
+    pub fn new(n: usize, whole: bool) -> Self {
+        Self { n, whole }
+    }
+}
+
+impl Transform for Repeat {
+    fn apply(&self, value: Value) -> Result<Value> {
+        let Value::Array(arr) = value else {
+            return Ok(value);
+        };
+
+        let mut elements = Vec::with_capacity(arr.len() * self.n);
+        if self.whole {
+            for _ in 0..self.n {
+                elements.extend(arr.elements.iter().map(Value::deep_copy));
+            }
+        } else {
+            for elem in &arr.elements {
+                elements.extend((0..self.n).map(|_| elem.deep_copy()));
+            }
+        }
+        Ok(Value::Array(Array::from((elements, arr.level))))
+    }
+
+    fn requires_full_input(&self) -> bool {
+        // Each copy of the array has to be complete before the next starts
+        self.whole
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value::Level;
+
+    fn words(words: &[&str]) -> Value {
+        Value::Array(Array::from((
+            words.iter().map(|w| Value::Text(w.to_string())).collect(),
+            Level::Word,
+        )))
+    }
+
+    #[test]
+    fn repeat_each_element() {
+        let result = Repeat::new(2, false).apply(words(&["a", "b"])).unwrap();
+        assert_eq!(result, words(&["a", "a", "b", "b"]));
+    }
+
+    #[test]
+    fn repeat_whole_array() {
+        let result = Repeat::new(3, true).apply(words(&["a", "b"])).unwrap();
+        assert_eq!(result, words(&["a", "b", "a", "b", "a", "b"]));
+    }
+
+    #[test]
+    fn repeat_zero_and_one() {
+        for whole in [false, true] {
+            let empty = Repeat::new(0, whole).apply(words(&["a", "b"])).unwrap();
+            assert_eq!(empty, words(&[]));
+            let same = Repeat::new(1, whole).apply(words(&["a", "b"])).unwrap();
+            assert_eq!(same, words(&["a", "b"]));
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use regex::Regex;
+use regex::{Captures, Regex};
 
 use crate::ast::Selection;
 use crate::error::Result;
@@ -7,18 +7,131 @@ use crate::value::Value;
 
 use super::select::apply_to_selected;
 
+/// A piece of a replacement template: literal text with `$` references, or a
+/// sed/Perl-style case escape.
+#[derive(Debug, PartialEq)]
+enum Piece {
+    Template(String),
+    /// `\U` - uppercase until `\E`
+    Upper,
+    /// `\L` - lowercase until `\E`
+    Lower,
+    /// `\E` - end `\U` or `\L`
+    End,
+    /// `\u` - uppercase the next char
+    UpperNext,
+    /// `\l` - lowercase the next char
+    LowerNext,
+}
+
+/// Split a replacement into pieces. Returns None if it has no case escapes,
+/// so plain replacements keep using the regex crate's expansion directly.
+fn parse_replacement(replacement: &str) -> Option<Vec<Piece>> {
+    let mut pieces = Vec::new();
+    let mut current = String::new();
+    let mut chars = replacement.chars().peekable();
+    let mut has_escapes = false;
+
+    while let Some(c) = chars.next() {
+        let piece = match (c, chars.peek()) {
+            ('\\', Some('U')) => Piece::Upper,
+            ('\\', Some('L')) => Piece::Lower,
+            ('\\', Some('E')) => Piece::End,
+            ('\\', Some('u')) => Piece::UpperNext,
+            ('\\', Some('l')) => Piece::LowerNext,
+            ('\\', Some(&next)) => {
+                // Other escapes are kept as written
+                current.push(c);
+                current.push(next);
+                chars.next();
+                continue;
+            }
+            _ => {
+                current.push(c);
+                continue;
+            }
+        };
+        chars.next();
+        has_escapes = true;
+        if !current.is_empty() {
+            pieces.push(Piece::Template(std::mem::take(&mut current)));
+        }
+        pieces.push(piece);
+    }
+    if !current.is_empty() {
+        pieces.push(Piece::Template(current));
+    }
+    has_escapes.then_some(pieces)
+}
+
+/// Expand a parsed replacement for one match, applying case escapes.
+fn expand_with_case(pieces: &[Piece], caps: &Captures, dst: &mut String) {
+    let mut mode: Option<bool> = None; // Some(true) = upper, Some(false) = lower
+    let mut next: Option<bool> = None;
+    let mut expanded = String::new();
+
+    for piece in pieces {
+        match piece {
+            Piece::Upper => mode = Some(true),
+            Piece::Lower => mode = Some(false),
+            Piece::End => mode = None,
+            Piece::UpperNext => next = Some(true),
+            Piece::LowerNext => next = Some(false),
+            Piece::Template(template) => {
+                expanded.clear();
+                caps.expand(template, &mut expanded);
+                for c in expanded.chars() {
+                    match next.take().or(mode) {
+                        Some(true) => dst.extend(c.to_uppercase()),
+                        Some(false) => dst.extend(c.to_lowercase()),
+                        None => dst.push(c),
+                    }
+                }
+            }
+        }
+    }
+}
+
 pub struct Replace {
     pattern: Regex,
     replacement: String,
+    pieces: Option<Vec<Piece>>,
     selection: Option<Selection>,
+    first_only: bool,
 }
 
 impl Replace {
-    pub fn new(pattern: Regex, replacement: String, selection: Option<Selection>) -> Self {
+    pub fn new(
+        pattern: Regex,
+        replacement: String,
+        selection: Option<Selection>,
+        first_only: bool,
+    ) -> Self {
         Self {
             pattern,
+            pieces: parse_replacement(&replacement),
             replacement,
             selection,
+            first_only,
+        }
+    }
+
+    fn replace_text(&self, s: &str) -> String {
+        // A limit of 0 replaces every match
+        let limit = if self.first_only { 1 } else { 0 };
+        match &self.pieces {
+            Some(pieces) => self
+                .pattern
+                .replacen(s, limit, |caps: &Captures| {
+                    let mut dst = String::new();
+                    expand_with_case(pieces, caps, &mut dst);
+                    dst
+                })
+                .into_owned(),
+            None => self
+                .pattern
+                .replacen(s, limit, &self.replacement)
+                .into_owned(),
         }
     }
 
@@ -32,10 +145,9 @@ impl Replace {
                     .collect::<Result<Vec<_>>>()?;
                 Ok(Value::Array(arr))
             }
-            Value::Text(s) => Ok(Value::Text(
-                self.pattern.replace_all(&s, &self.replacement).into_owned(),
-            )),
+            Value::Text(s) => Ok(Value::Text(self.replace_text(&s))),
             Value::Number(n) => Ok(Value::Number(n)),
+            Value::Bool(b) => Ok(Value::Bool(b)),
         }
     }
 }
@@ -72,7 +184,7 @@ mod tests {
     #[test]
     fn replace_basic() {
         let input = line_array(&["foo bar", "foo baz"]);
-        let replace = Replace::new(Regex::new("foo").unwrap(), "qux".to_string(), None);
+        let replace = Replace::new(Regex::new("foo").unwrap(), "qux".to_string(), None, false);
         let result = replace.apply(input).unwrap();
         match result {
             Value::Array(arr) => {
@@ -86,7 +198,7 @@ mod tests {
     #[test]
     fn replace_all_occurrences() {
         let input = text("foo foo foo");
-        let replace = Replace::new(Regex::new("foo").unwrap(), "bar".to_string(), None);
+        let replace = Replace::new(Regex::new("foo").unwrap(), "bar".to_string(), None, false);
         let result = replace.apply(input).unwrap();
         assert_eq!(result, text("bar bar bar"));
     }
@@ -94,7 +206,7 @@ mod tests {
     #[test]
     fn replace_empty_replacement() {
         let input = text("ERROR: something");
-        let replace = Replace::new(Regex::new("ERROR: ").unwrap(), "".to_string(), None);
+        let replace = Replace::new(Regex::new("ERROR: ").unwrap(), "".to_string(), None, false);
         let result = replace.apply(input).unwrap();
         assert_eq!(result, text("something"));
     }
@@ -108,6 +220,7 @@ mod tests {
             Some(Selection {
                 items: vec![SelectItem::Index(0)],
             }),
+            false,
         );
         let result = replace.apply(input).unwrap();
         match result {
@@ -133,6 +246,7 @@ mod tests {
                     step: None,
                 })],
             }),
+            false,
         );
         let result = replace.apply(input).unwrap();
         match result {
@@ -152,11 +266,106 @@ mod tests {
             Regex::new("(\\w+) (\\w+)").unwrap(),
             "$2 $1".to_string(),
             None,
+            false,
         );
         let result = replace.apply(input).unwrap();
         assert_eq!(result, text("world hello"));
     }
 
+    #[test]
+    fn replace_regex_capture_groups_uppercase() {
+        let input = text("hello world");
+        let replace = Replace::new(
+            Regex::new("(\\w+) (\\w+)").unwrap(),
+            "\\U$2\\E $1".to_string(),
+            None,
+            false,
+        );
+        let result = replace.apply(input).unwrap();
+        assert_eq!(result, text("WORLD hello"));
+    }
+
+    #[test]
+    fn replace_regex_capture_groups_title_case() {
+        let input = text("hello WORLD");
+        let replace = Replace::new(
+            Regex::new("(\\w+)").unwrap(),
+            "\\u\\L$1".to_string(),
+            None,
+            false,
+        );
+        let result = replace.apply(input).unwrap();
+        assert_eq!(result, text("Hello World"));
+    }
+
+    #[test]
+    fn replace_lowercase_until_end() {
+        let input = text("ABC DEF");
+        let replace = Replace::new(
+            Regex::new("(\\w+) (\\w+)").unwrap(),
+            "\\L$1-\\E$2 \\lX".to_string(),
+            None,
+            false,
+        );
+        let result = replace.apply(input).unwrap();
+        assert_eq!(result, text("abc-DEF x"));
+    }
+
+    #[test]
+    fn replace_other_escapes_unchanged() {
+        assert_eq!(parse_replacement("a\\tb$1"), None);
+        assert_eq!(
+            parse_replacement("\\U$1\\t"),
+            Some(vec![Piece::Upper, Piece::Template("$1\\t".to_string())])
+        );
+    }
+
+    #[test]
+    fn replace_named_capture_group() {
+        let input = text("hello world");
+        let replace = Replace::new(
+            Regex::new("(?P<word>\\w+)").unwrap(),
+            "${word}!".to_string(),
+            None,
+            false,
+        );
+        let result = replace.apply(input).unwrap();
+        assert_eq!(result, text("hello! world!"));
+    }
+
+    #[test]
+    fn replace_named_capture_group_with_case() {
+        let input = text("hello world");
+        let replace = Replace::new(
+            Regex::new("(?P<first>\\w+) (?P<second>\\w+)").unwrap(),
+            "\\U${second}\\E ${first}".to_string(),
+            None,
+            false,
+        );
+        let result = replace.apply(input).unwrap();
+        assert_eq!(result, text("WORLD hello"));
+    }
+
+    #[test]
+    fn replace_first_only() {
+        let input = text("a-b-c");
+        let replace = Replace::new(Regex::new("-").unwrap(), "+".to_string(), None, true);
+        let result = replace.apply(input).unwrap();
+        assert_eq!(result, text("a+b-c"));
+    }
+
+    #[test]
+    fn replace_case_insensitive() {
+        let input = text("Foo foo FOO");
+        let regex = regex::RegexBuilder::new("foo")
+            .case_insensitive(true)
+            .build()
+            .unwrap();
+        let replace = Replace::new(regex, "bar".to_string(), None, false);
+        let result = replace.apply(input).unwrap();
+        assert_eq!(result, text("bar bar bar"));
+    }
+
     #[test]
     fn replace_non_array_with_selection_is_identity() {
         let input = text("foo");
@@ -166,6 +375,7 @@ mod tests {
             Some(Selection {
                 items: vec![SelectItem::Index(0)],
             }),
+            false,
         );
         let result = replace.apply(input).unwrap();
         assert_eq!(result, text("foo"));
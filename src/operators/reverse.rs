@@ -0,0 +1,63 @@
+use crate::error::Result;
+use crate::interpreter::Transform;
+use crate::value::Value;
+
+/// Reverses the characters of each string, recursing through nested arrays.
+///
+/// `["abc", "racecar"]` → `["cba", "racecar"]`
+///
+/// Works on chars, so a character built from several code points (such as a
+/// letter with a combining accent) is split apart. Numbers are kept as-is.
+pub struct ReverseChars;
+
+impl Transform for ReverseChars {
+    fn apply(&self, value: Value) -> Result<Value> {
+        match value {
+            Value::Array(mut arr) => {
+                arr.elements = arr
+                    .elements
+                    .into_iter()
+                    .map(|v| self.apply(v))
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(Value::Array(arr))
+            }
+            Value::Text(s) => Ok(Value::Text(s.chars().rev().collect())),
+            Value::Number(n) => Ok(Value::Number(n)),
+            Value::Bool(b) => Ok(Value::Bool(b)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value::{Array, Level};
+
+    fn text(s: &str) -> Value {
+        Value::Text(s.to_string())
+    }
+
+    #[test]
+    fn reverse_chars_recurses() {
+        let input = Value::Array(Array::from((
+            vec![
+                text("hello"),
+                Value::Number(12.0),
+                Value::Array(Array::from((vec![text("añb"), text("")], Level::Word))),
+            ],
+            Level::Line,
+        )));
+        let result = ReverseChars.apply(input).unwrap();
+        assert_eq!(
+            result,
+            Value::Array(Array::from((
+                vec![
+                    text("olleh"),
+                    Value::Number(12.0),
+                    Value::Array(Array::from((vec![text("bña"), text("")], Level::Word))),
+                ],
+                Level::Line,
+            )))
+        );
+    }
+}
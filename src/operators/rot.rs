@@ -0,0 +1,113 @@
+use crate::error::Result;
+use crate::interpreter::Transform;
+use crate::value::Value;
+
+/// Rotates ASCII letters through the alphabet by a fixed shift, recursing
+/// through nested arrays. `Rot::new(13)` is ROT13, which undoes itself.
+///
+/// `["Hello, World!"]` → `["Uryyb, Jbeyq!"]`
+///
+/// Case is kept, and everything that isn't an ASCII letter (digits,
+/// punctuation, accented letters) is left alone. Numbers are kept as-is.
+pub struct Rot {
+    shift: u8,
+}
+
+impl Rot {
+    /// A negative shift rotates backwards, so `-3` undoes `3`.
+    pub fn new(shift: i64) -> Self {
+        Self {
+            shift: shift.rem_euclid(26) as u8,
+        }
+    }
+
+    fn rotate(&self, c: char) -> char {
+        let base = match c {
+            'a'..='z' => b'a',
+            'A'..='Z' => b'A',
+            _ => return c,
+        };
+        char::from(base + (c as u8 - base + self.shift) % 26)
+    }
+}
+
+impl Transform for Rot {
+    fn apply(&self, value: Value) -> Result<Value> {
+        match value {
+            Value::Array(mut arr) => {
+                arr.elements = arr
+                    .elements
+                    .into_iter()
+                    .map(|v| self.apply(v))
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(Value::Array(arr))
+            }
+            Value::Text(s) => Ok(Value::Text(s.chars().map(|c| self.rotate(c)).collect())),
+            Value::Number(n) => Ok(Value::Number(n)),
+            Value::Bool(b) => Ok(Value::Bool(b)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value::{Array, Level};
+
+    fn text(s: &str) -> Value {
+        Value::Text(s.to_string())
+    }
+
+    #[test]
+    fn rot13_text() {
+        let result = Rot::new(13).apply(text("Hello, World!")).unwrap();
+        assert_eq!(result, text("Uryyb, Jbeyq!"));
+    }
+
+    #[test]
+    fn rot13_twice_is_identity() {
+        let input = "The quick brown fox jumps over the lazy dog. 0123456789 ça_va?";
+        let once = Rot::new(13).apply(text(input)).unwrap();
+        assert_ne!(once, text(input));
+        assert_eq!(Rot::new(13).apply(once).unwrap(), text(input));
+    }
+
+    #[test]
+    fn rot_leaves_non_letters_unchanged() {
+        let input = "0123456789 !\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~ é ß 日本";
+        assert_eq!(Rot::new(13).apply(text(input)).unwrap(), text(input));
+    }
+
+    #[test]
+    fn rot_n_wraps_around() {
+        assert_eq!(Rot::new(3).apply(text("xyz XYZ")).unwrap(), text("abc ABC"));
+        assert_eq!(Rot::new(-3).apply(text("abc")).unwrap(), text("xyz"));
+        assert_eq!(Rot::new(29).apply(text("abc")).unwrap(), text("def"));
+        assert_eq!(Rot::new(26).apply(text("abc")).unwrap(), text("abc"));
+    }
+
+    #[test]
+    fn rot_recurses_and_keeps_numbers() {
+        let input = Value::Array(Array::from((
+            vec![
+                Value::Array(Array::from((
+                    vec![text("ab"), Value::Number(7.0)],
+                    Level::Word,
+                ))),
+                text("z"),
+            ],
+            Level::Line,
+        )));
+        let expected = Value::Array(Array::from((
+            vec![
+                Value::Array(Array::from((
+                    vec![text("bc"), Value::Number(7.0)],
+                    Level::Word,
+                ))),
+                text("a"),
+            ],
+            Level::Line,
+        )));
+        assert_eq!(Rot::new(1).apply(input).unwrap(), expected);
+    }
+}
@@ -1,31 +1,59 @@
-use crate::ast::{SelectItem, Selection, Slice};
+use crate::ast::{Literal, Predicate, SelectItem, Selection, Slice};
 use crate::error::{Error, Result};
 use crate::interpreter::Transform;
 use crate::value::{Array, Value};
 
+use super::group::extract_key;
+
 pub struct Select {
     selection: Selection,
+    strict: bool,
 }
 
 impl Select {
-    pub fn new(selection: Selection) -> Self {
-        Self { selection }
+    /// In strict mode, an index past either end is an error instead of being
+    /// skipped. Slices are never strict.
+    pub fn new(selection: Selection, strict: bool) -> Self {
+        Self { selection, strict }
+    }
+
+    fn check_indices(&self, len: usize, what: &str) -> Result<()> {
+        if !self.strict {
+            return Ok(());
+        }
+        for item in &self.selection.items {
+            if let SelectItem::Index(idx) = item
+                && !(0..len as i64).contains(&normalize_index(*idx, len as i64))
+            {
+                return Err(Error::runtime(format!(
+                    "index {} out of range for {} of length {}",
+                    idx, what, len
+                )));
+            }
+        }
+        Ok(())
     }
 }
 
 impl Transform for Select {
     fn apply(&self, value: Value) -> Result<Value> {
         match value {
-            Value::Array(arr) => select_from_array(arr, &self.selection),
-            Value::Text(s) => select_from_string(&s, &self.selection),
+            Value::Array(arr) => {
+                self.check_indices(arr.len(), "array")?;
+                select_from_array(arr, &self.selection)
+            }
+            Value::Text(s) => {
+                self.check_indices(s.chars().count(), "string")?;
+                select_from_string(&s, &self.selection)
+            }
             Value::Number(_) => Err(Error::runtime("cannot select from number")),
+            Value::Bool(_) => Err(Error::runtime("cannot select from boolean")),
         }
     }
 }
 
 fn select_from_array(arr: Array, selection: &Selection) -> Result<Value> {
-    let len = arr.len() as i64;
-    let indices = selection_indices(selection, len);
+    let indices = selection_indices(selection, &arr.elements);
 
     if indices.len() == 1 {
         let idx = indices[0];
@@ -48,8 +76,7 @@ fn select_from_array(arr: Array, selection: &Selection) -> Result<Value> {
 
 fn select_from_string(s: &str, selection: &Selection) -> Result<Value> {
     let chars: Vec<char> = s.chars().collect();
-    let len = chars.len() as i64;
-    let indices = selection_indices(selection, len);
+    let indices = text_selection_indices(selection, chars.len() as i64);
 
     if indices.len() == 1 {
         return chars
@@ -104,7 +131,18 @@ pub fn compute_slice_indices(slice: &Slice, len: i64) -> Vec<usize> {
     indices
 }
 
-pub fn selection_indices(selection: &Selection, len: i64) -> Vec<usize> {
+/// The indices of `elements` picked by `selection`, in selection order.
+pub fn selection_indices(selection: &Selection, elements: &[Value]) -> Vec<usize> {
+    indices_of(selection, elements.len() as i64, Some(elements))
+}
+
+/// The indices of the chars of a text of `len` chars picked by `selection`.
+/// Predicates test elements of arrays, so they pick no chars.
+pub fn text_selection_indices(selection: &Selection, len: i64) -> Vec<usize> {
+    indices_of(selection, len, None)
+}
+
+fn indices_of(selection: &Selection, len: i64, elements: Option<&[Value]>) -> Vec<usize> {
     let mut indices = Vec::new();
     for item in &selection.items {
         match item {
@@ -117,18 +155,48 @@ pub fn selection_indices(selection: &Selection, len: i64) -> Vec<usize> {
             SelectItem::Slice(slice) => {
                 indices.extend(compute_slice_indices(slice, len));
             }
+            // Names are replaced by indices before compiling
+            SelectItem::Name(_) => {}
+            SelectItem::Predicate(predicate) => {
+                let elements = elements.unwrap_or_default();
+                indices.extend(
+                    elements
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, elem)| predicate_holds(predicate, elem))
+                        .map(|(i, _)| i),
+                );
+            }
         }
     }
     indices
 }
 
+/// Whether the value selected from `elem` by the predicate's column compares
+/// true against its literal.
+fn predicate_holds(predicate: &Predicate, elem: &Value) -> bool {
+    let Ok(key) = extract_key(elem, &predicate.column) else {
+        return false;
+    };
+    match &predicate.value {
+        Literal::Number(n) => key.as_number().is_some_and(|k| predicate.cmp.holds(k, *n)),
+        Literal::Text(s) => {
+            let key = match key {
+                Value::Text(k) => k,
+                other => other.to_string(),
+            };
+            predicate.cmp.holds_for(key.as_str().cmp(s.as_str()))
+        }
+    }
+}
+
 pub fn apply_to_selected<F>(arr: Array, selection: &Selection, transform: F) -> Result<Value>
 where
     F: Fn(Value) -> Result<Value>,
 {
-    let len = arr.len() as i64;
-    let selected: std::collections::HashSet<usize> =
-        selection_indices(selection, len).into_iter().collect();
+    let selected: std::collections::HashSet<usize> = selection_indices(selection, &arr.elements)
+        .into_iter()
+        .collect();
 
     let elements: Result<Vec<Value>> = arr
         .elements
@@ -149,6 +217,7 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ast::Comparison;
     use crate::value::Level;
 
     fn text(s: &str) -> Value {
@@ -168,7 +237,7 @@ mod tests {
         let sel = Selection {
             items: vec![SelectItem::Index(1)],
         };
-        let result = Select::new(sel).apply(input).unwrap();
+        let result = Select::new(sel, false).apply(input).unwrap();
         assert_eq!(result, text("b"));
     }
 
@@ -178,7 +247,7 @@ mod tests {
         let sel = Selection {
             items: vec![SelectItem::Index(-1)],
         };
-        let result = Select::new(sel).apply(input).unwrap();
+        let result = Select::new(sel, false).apply(input).unwrap();
         assert_eq!(result, text("c"));
     }
 
@@ -192,7 +261,7 @@ mod tests {
                 step: None,
             })],
         };
-        let result = Select::new(sel).apply(input).unwrap();
+        let result = Select::new(sel, false).apply(input).unwrap();
 
         match result {
             Value::Array(arr) => {
@@ -214,7 +283,7 @@ mod tests {
                 step: None,
             })],
         };
-        let result = Select::new(sel).apply(input).unwrap();
+        let result = Select::new(sel, false).apply(input).unwrap();
 
         match result {
             Value::Array(arr) => {
@@ -236,7 +305,7 @@ mod tests {
                 step: None,
             })],
         };
-        let result = Select::new(sel).apply(input).unwrap();
+        let result = Select::new(sel, false).apply(input).unwrap();
 
         match result {
             Value::Array(arr) => {
@@ -258,7 +327,7 @@ mod tests {
                 step: Some(-1),
             })],
         };
-        let result = Select::new(sel).apply(input).unwrap();
+        let result = Select::new(sel, false).apply(input).unwrap();
 
         match result {
             Value::Array(arr) => {
@@ -277,7 +346,7 @@ mod tests {
         let sel = Selection {
             items: vec![SelectItem::Index(0), SelectItem::Index(2)],
         };
-        let result = Select::new(sel).apply(input).unwrap();
+        let result = Select::new(sel, false).apply(input).unwrap();
 
         match result {
             Value::Array(arr) => {
@@ -299,7 +368,7 @@ mod tests {
                 step: Some(2),
             })],
         };
-        let result = Select::new(sel).apply(input).unwrap();
+        let result = Select::new(sel, false).apply(input).unwrap();
 
         match result {
             Value::Array(arr) => {
@@ -318,7 +387,7 @@ mod tests {
         let sel = Selection {
             items: vec![SelectItem::Index(1)],
         };
-        let result = Select::new(sel).apply(input).unwrap();
+        let result = Select::new(sel, false).apply(input).unwrap();
         assert_eq!(result, text("e"));
     }
 
@@ -328,7 +397,7 @@ mod tests {
         let sel = Selection {
             items: vec![SelectItem::Index(-1)],
         };
-        let result = Select::new(sel).apply(input).unwrap();
+        let result = Select::new(sel, false).apply(input).unwrap();
         assert_eq!(result, text("o"));
     }
 
@@ -342,7 +411,7 @@ mod tests {
                 step: None,
             })],
         };
-        let result = Select::new(sel).apply(input).unwrap();
+        let result = Select::new(sel, false).apply(input).unwrap();
         assert_eq!(result, text("ell"));
     }
 
@@ -356,7 +425,122 @@ mod tests {
                 step: Some(-1),
             })],
         };
-        let result = Select::new(sel).apply(input).unwrap();
+        let result = Select::new(sel, false).apply(input).unwrap();
         assert_eq!(result, text("olleh"));
     }
+
+    #[test]
+    fn strict_select_rejects_out_of_range_index() {
+        let index = |idx| Selection {
+            items: vec![SelectItem::Index(idx)],
+        };
+        let err = Select::new(index(5), true)
+            .apply(line_array(&["a", "b", "c"]))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "index 5 out of range for array of length 3"
+        );
+        assert!(Select::new(index(-4), true).apply(text("abc")).is_err());
+        assert_eq!(
+            Select::new(index(-3), true).apply(text("abc")).unwrap(),
+            text("a")
+        );
+    }
+
+    #[test]
+    fn strict_select_rejects_any_out_of_range_index_in_multi_select() {
+        let sel = || Selection {
+            items: vec![
+                SelectItem::Index(0),
+                SelectItem::Index(1),
+                SelectItem::Index(6),
+            ],
+        };
+        // Lenient by default: the missing index is skipped
+        let result = Select::new(sel(), false)
+            .apply(line_array(&["a", "b", "c"]))
+            .unwrap();
+        assert_eq!(result, line_array(&["a", "b"]));
+
+        let err = Select::new(sel(), true)
+            .apply(line_array(&["a", "b", "c"]))
+            .unwrap_err();
+        assert!(err.to_string().contains("index 6"));
+        assert!(Select::new(sel(), true).apply(text("abc")).is_err());
+    }
+
+    #[test]
+    fn strict_select_allows_slices_past_the_end() {
+        let sel = Selection {
+            items: vec![SelectItem::Slice(Slice {
+                start: Some(1),
+                end: Some(10),
+                step: None,
+            })],
+        };
+        let result = Select::new(sel, true)
+            .apply(line_array(&["a", "b", "c"]))
+            .unwrap();
+        assert_eq!(result, line_array(&["b", "c"]));
+    }
+
+    fn rows(rows: &[&[&str]]) -> Value {
+        Value::Array(Array::from((
+            rows.iter()
+                .map(|row| {
+                    Value::Array(Array::from((
+                        row.iter().map(|s| text(s)).collect(),
+                        Level::Word,
+                    )))
+                })
+                .collect(),
+            Level::Line,
+        )))
+    }
+
+    fn predicate(column: i64, cmp: Comparison, value: Literal) -> Selection {
+        Selection {
+            items: vec![SelectItem::Predicate(Predicate {
+                column: Selection {
+                    items: vec![SelectItem::Index(column)],
+                },
+                cmp,
+                value,
+            })],
+        }
+    }
+
+    #[test]
+    fn select_where_column_equals_text() {
+        let input = rows(&[&["a", "x"], &["b", "y"], &["c", "x"], &["d"]]);
+        let sel = predicate(1, Comparison::Eq, Literal::Text("x".to_string()));
+        let result = Select::new(sel, false).apply(input).unwrap();
+        assert_eq!(result, rows(&[&["a", "x"], &["c", "x"]]));
+    }
+
+    #[test]
+    fn select_where_column_compares_numerically() {
+        let input = rows(&[&["a", "9"], &["b", "10"], &["c", "n/a"], &["d", "12.5"]]);
+        let sel = predicate(1, Comparison::Ge, Literal::Number(10.0));
+        let result = Select::new(sel, false).apply(input).unwrap();
+        // "n/a" isn't a number, so it never matches
+        assert_eq!(result, rows(&[&["b", "10"], &["d", "12.5"]]));
+
+        let input = rows(&[&["a", "10"], &["b", "10.0"], &["c", "7"]]);
+        let sel = predicate(1, Comparison::Ne, Literal::Number(10.0));
+        let result = Select::new(sel, false).apply(input).unwrap();
+        // A single match is the row itself, as for a single index
+        assert_eq!(
+            result,
+            Value::Array(Array::from((vec![text("c"), text("7")], Level::Word)))
+        );
+    }
+
+    #[test]
+    fn predicate_selects_no_chars() {
+        let sel = predicate(0, Comparison::Eq, Literal::Text("a".to_string()));
+        let result = Select::new(sel, false).apply(text("abc")).unwrap();
+        assert_eq!(result, text(""));
+    }
 }
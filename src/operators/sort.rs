@@ -1,6 +1,11 @@
+use std::cmp::Ordering;
+
+use crate::ast::{SelectItem, Selection};
 use crate::error::Result;
 use crate::interpreter::Transform;
-use crate::value::Value;
+use crate::value::{Array, Level, Value};
+
+use super::group::extract_key;
 
 pub struct SortDescending;
 
@@ -38,6 +43,189 @@ impl Transform for SortAscending {
     }
 }
 
+/// Sorts and drops repeated elements, like `sort -u`.
+pub struct SortUnique {
+    descending: bool,
+}
+
+impl SortUnique {
+    pub fn new(descending: bool) -> Self {
+        Self { descending }
+    }
+}
+
+impl Transform for SortUnique {
+    fn apply(&self, value: Value) -> Result<Value> {
+        match value {
+            Value::Array(mut arr) => {
+                arr.sort(self.descending);
+                arr.elements.dedup();
+                Ok(Value::Array(arr))
+            }
+            other => Ok(other),
+        }
+    }
+
+    fn requires_full_input(&self) -> bool {
+        true
+    }
+}
+
+/// Sorts elements by several selected keys, each ascending or descending.
+/// Elements missing a key sort before those that have it; ties keep their
+/// input order.
+pub struct SortBy {
+    keys: Vec<Selection>,
+    descending: Vec<bool>,
+}
+
+impl SortBy {
+    /// `descending[i]` gives the direction of `keys.items[i]`.
+    pub fn new(keys: Selection, descending: Vec<bool>) -> Self {
+        let keys = keys
+            .items
+            .into_iter()
+            .map(|item: SelectItem| Selection { items: vec![item] })
+            .collect();
+        Self { keys, descending }
+    }
+
+    fn compare(&self, a: &[Option<Value>], b: &[Option<Value>]) -> Ordering {
+        for ((a, b), &descending) in a.iter().zip(b).zip(&self.descending) {
+            let ord = a.cmp(b);
+            let ord = if descending { ord.reverse() } else { ord };
+            if ord != Ordering::Equal {
+                return ord;
+            }
+        }
+        Ordering::Equal
+    }
+}
+
+impl Transform for SortBy {
+    fn apply(&self, value: Value) -> Result<Value> {
+        match value {
+            Value::Array(mut arr) => {
+                let mut keyed: Vec<(Vec<Option<Value>>, Value)> = arr
+                    .elements
+                    .into_iter()
+                    .map(|elem| {
+                        let key = self
+                            .keys
+                            .iter()
+                            .map(|sel| match extract_key(&elem, sel) {
+                                // A missing index selects nothing
+                                Ok(Value::Array(a)) if a.is_empty() => None,
+                                key => key.ok(),
+                            })
+                            .collect();
+                        (key, elem)
+                    })
+                    .collect();
+                keyed.sort_by(|(a, _), (b, _)| self.compare(a, b));
+                arr.elements = keyed.into_iter().map(|(_, elem)| elem).collect();
+                Ok(Value::Array(arr))
+            }
+            other => Ok(other),
+        }
+    }
+
+    fn requires_full_input(&self) -> bool {
+        true
+    }
+}
+
+/// Sorts the characters within each string, recursing through nested arrays,
+/// so anagrams become equal. With `ignore_case`, text is lowercased first.
+pub struct SortChars {
+    ignore_case: bool,
+}
+
+impl SortChars {
+    pub fn new(ignore_case: bool) -> Self {
+        Self { ignore_case }
+    }
+}
+
+impl Transform for SortChars {
+    fn apply(&self, value: Value) -> Result<Value> {
+        match value {
+            Value::Array(mut arr) => {
+                arr.elements = arr
+                    .elements
+                    .into_iter()
+                    .map(|v| self.apply(v))
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(Value::Array(arr))
+            }
+            Value::Text(s) => {
+                let s = if self.ignore_case {
+                    s.to_lowercase()
+                } else {
+                    s
+                };
+                let mut chars = s.chars().collect::<Vec<_>>();
+                chars.sort();
+                Ok(Value::Text(chars.into_iter().collect::<String>()))
+            }
+            Value::Number(n) => Ok(Value::Number(n)),
+            Value::Bool(b) => Ok(Value::Bool(b)),
+        }
+    }
+}
+
+/// Replaces each element with `[rank, value]`, the largest value ranking 1.
+/// Ties share a rank and the next rank skips past them (competition ranking:
+/// 1, 2, 2, 4).
+pub struct Rank {
+    sorted: bool,
+}
+
+impl Rank {
+    /// With `sorted`, rows come out in rank order; otherwise in input order.
+    pub fn new(sorted: bool) -> Self {
+        Self { sorted }
+    }
+}
+
+impl Transform for Rank {
+    fn apply(&self, value: Value) -> Result<Value> {
+        let Value::Array(arr) = value else {
+            return Ok(value);
+        };
+
+        let mut order: Vec<usize> = (0..arr.len()).collect();
+        order.sort_by(|&a, &b| arr.elements[b].cmp(&arr.elements[a]));
+        let mut ranks = vec![0; arr.len()];
+        for (pos, &i) in order.iter().enumerate() {
+            ranks[i] = match pos.checked_sub(1).map(|prev| order[prev]) {
+                Some(prev) if arr.elements[prev] == arr.elements[i] => ranks[prev],
+                _ => pos + 1,
+            };
+        }
+
+        let mut rows: Vec<(usize, Value)> = ranks.into_iter().zip(arr.elements).collect();
+        if self.sorted {
+            // Stable, so tied values keep their input order
+            rows.sort_by_key(|(rank, _)| *rank);
+        }
+        let elements = rows
+            .into_iter()
+            .map(|(rank, value)| {
+                Value::Array(Array::from((
+                    vec![Value::Number(rank as f64), value],
+                    Level::Word,
+                )))
+            })
+            .collect();
+        Ok(Value::Array(Array::from((elements, arr.level))))
+    }
+
+    fn requires_full_input(&self) -> bool {
+        true
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -250,4 +438,180 @@ mod tests {
         let result = SortAscending.apply(input).unwrap();
         assert_eq!(result, Value::Number(42.0));
     }
+
+    #[test]
+    fn sort_unique_both_directions() {
+        let input = || {
+            Value::Array(Array::from((
+                vec![text("b"), text("a"), text("b"), text("c"), text("a")],
+                Level::Line,
+            )))
+        };
+        let expect = |words: &[&str]| {
+            Value::Array(Array::from((
+                words.iter().map(|w| text(w)).collect(),
+                Level::Line,
+            )))
+        };
+        assert_eq!(
+            SortUnique::new(false).apply(input()).unwrap(),
+            expect(&["a", "b", "c"])
+        );
+        assert_eq!(
+            SortUnique::new(true).apply(input()).unwrap(),
+            expect(&["c", "b", "a"])
+        );
+    }
+
+    fn grid(rows: &[[&str; 3]]) -> Value {
+        Value::Array(Array::from((
+            rows.iter()
+                .map(|r| {
+                    Value::Array(Array::from((
+                        r.iter().map(|s| text(s)).collect(),
+                        Level::Word,
+                    )))
+                })
+                .collect(),
+            Level::Line,
+        )))
+    }
+
+    fn keys(indices: &[i64]) -> Selection {
+        Selection {
+            items: indices.iter().map(|&i| SelectItem::Index(i)).collect(),
+        }
+    }
+
+    #[test]
+    fn sort_by_mixed_directions() {
+        let input = grid(&[
+            ["b", "x", "1"],
+            ["a", "y", "2"],
+            ["b", "z", "3"],
+            ["a", "w", "9"],
+            ["b", "v", "2"],
+        ]);
+        // Column 0 ascending, then column 2 descending
+        let result = SortBy::new(keys(&[0, 2]), vec![false, true])
+            .apply(input)
+            .unwrap();
+        assert_eq!(
+            result,
+            grid(&[
+                ["a", "w", "9"],
+                ["a", "y", "2"],
+                ["b", "z", "3"],
+                ["b", "v", "2"],
+                ["b", "x", "1"],
+            ])
+        );
+    }
+
+    #[test]
+    fn sort_by_ties_keep_input_order() {
+        let input = grid(&[["1", "b", "_"], ["0", "a", "_"], ["1", "a", "_"]]);
+        let result = SortBy::new(keys(&[0]), vec![true]).apply(input).unwrap();
+        assert_eq!(
+            result,
+            grid(&[["1", "b", "_"], ["1", "a", "_"], ["0", "a", "_"]])
+        );
+    }
+
+    #[test]
+    fn sort_by_missing_key_sorts_first() {
+        let short = Value::Array(Array::from((vec![text("z")], Level::Word)));
+        let full = Value::Array(Array::from((vec![text("a"), text("b")], Level::Word)));
+        let input = Value::Array(Array::from((vec![full, short], Level::Line)));
+        let result = SortBy::new(keys(&[1]), vec![false]).apply(input).unwrap();
+        match result {
+            Value::Array(arr) => {
+                assert_eq!(arr.elements[0].to_string(), "z");
+            }
+            _ => panic!("expected array"),
+        }
+    }
+
+    fn ranked(value: Value) -> Vec<(f64, Value)> {
+        let Value::Array(arr) = value else {
+            panic!("expected array");
+        };
+        arr.elements
+            .into_iter()
+            .map(|row| match row {
+                Value::Array(mut row) => {
+                    let value = row.elements.pop().unwrap();
+                    (row.elements[0].as_number().unwrap(), value)
+                }
+                _ => panic!("expected row"),
+            })
+            .collect()
+    }
+
+    fn numbers(ns: &[f64]) -> Value {
+        Value::Array(Array::from((
+            ns.iter().map(|&n| Value::Number(n)).collect(),
+            Level::Line,
+        )))
+    }
+
+    #[test]
+    fn rank_ties_share_rank_and_skip() {
+        let result = Rank::new(false)
+            .apply(numbers(&[70.0, 90.0, 80.0, 90.0, 70.0, 60.0]))
+            .unwrap();
+        assert_eq!(
+            ranked(result),
+            vec![
+                (4.0, Value::Number(70.0)),
+                (1.0, Value::Number(90.0)),
+                (3.0, Value::Number(80.0)),
+                (1.0, Value::Number(90.0)),
+                (4.0, Value::Number(70.0)),
+                (6.0, Value::Number(60.0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn rank_sorted_order() {
+        let input = Value::Array(Array::from((
+            vec![text("b"), text("c"), text("a"), text("c")],
+            Level::Line,
+        )));
+        let result = Rank::new(true).apply(input).unwrap();
+        assert_eq!(
+            ranked(result),
+            vec![
+                (1.0, text("c")),
+                (1.0, text("c")),
+                (3.0, text("b")),
+                (4.0, text("a")),
+            ]
+        );
+    }
+
+    #[test]
+    fn sort_chars_makes_anagrams_equal() {
+        let input = Value::Array(Array::from((
+            vec![text("listen"), text("silent"), text("Tinsel")],
+            Level::Word,
+        )));
+        let result = SortChars::new(false).apply(input.deep_copy()).unwrap();
+        assert_eq!(
+            result,
+            Value::Array(Array::from((
+                vec![text("eilnst"), text("eilnst"), text("Teilns")],
+                Level::Word,
+            )))
+        );
+        let folded = SortChars::new(true).apply(input).unwrap();
+        assert_eq!(
+            folded,
+            Value::Array(Array::from((
+                vec![text("eilnst"), text("eilnst"), text("eilnst")],
+                Level::Word,
+            )))
+        );
+    }
 }
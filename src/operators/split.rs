@@ -1,3 +1,4 @@
+use crate::ast::KeepDelimiter;
 use crate::error::Result;
 use crate::interpreter::Transform;
 use crate::value::{Array, Level, Value};
@@ -11,7 +12,25 @@ pub enum SplitMode {
     /// Split on a specific delimiter
     Delimiter(String),
     /// Split as CSV fields
-    Csv,
+    Csv(CsvFormat),
+    /// Split into fields of these widths, in chars
+    FixedWidth(Vec<usize>),
+}
+
+/// The field delimiter and quote character for CSV splitting and joining.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CsvFormat {
+    pub delimiter: u8,
+    pub quote: u8,
+}
+
+impl Default for CsvFormat {
+    fn default() -> Self {
+        Self {
+            delimiter: b',',
+            quote: b'"',
+        }
+    }
 }
 
 /// Splits text elements of an array based on the array's semantic level.
@@ -46,6 +65,7 @@ impl Split {
             Value::Array(arr) => Ok(Value::Array(arr)), // arrays are left unchanged
             Value::Text(s) => Ok(split_text(&s, level, &self.mode)),
             Value::Number(n) => Ok(Value::Number(n)),
+            Value::Bool(b) => Ok(Value::Bool(b)),
         }
     }
 }
@@ -83,6 +103,30 @@ fn split_text(s: &str, level: Level, mode: &SplitMode) -> Value {
     Value::Array(Array::from((elements, new_level)))
 }
 
+/// Split a header row into column names the same way `s` splits a line.
+pub fn header_names(line: &str, mode: &SplitMode) -> Vec<String> {
+    split_line(line, mode)
+        .into_iter()
+        .map(|v| match v {
+            Value::Text(s) => s,
+            other => other.to_string(),
+        })
+        .collect()
+}
+
+/// Split each line into a row of fields the same way `s` does.
+pub fn split_rows(mut lines: Array, mode: &SplitMode) -> Array {
+    lines.elements = lines
+        .elements
+        .into_iter()
+        .map(|v| match v {
+            Value::Text(s) => split_text(&s, Level::Line, mode),
+            other => other,
+        })
+        .collect();
+    lines
+}
+
 fn split_line(s: &str, mode: &SplitMode) -> Vec<Value> {
     match mode {
         SplitMode::Whitespace => s
@@ -93,9 +137,11 @@ fn split_line(s: &str, mode: &SplitMode) -> Vec<Value> {
             .split(delim.as_str())
             .map(|part| Value::Text(part.to_string()))
             .collect(),
-        SplitMode::Csv => {
+        SplitMode::Csv(format) => {
             let mut reader = csv::ReaderBuilder::new()
                 .has_headers(false)
+                .delimiter(format.delimiter)
+                .quote(format.quote)
                 .from_reader(s.as_bytes());
             let mut record = csv::StringRecord::new();
             if reader.read_record(&mut record).unwrap_or(false) {
@@ -104,20 +150,173 @@ fn split_line(s: &str, mode: &SplitMode) -> Vec<Value> {
                 vec![]
             }
         }
+        SplitMode::FixedWidth(widths) => split_fixed_width(s, widths),
+    }
+}
+
+/// Cut a line into fields of the given widths, counted in chars, with
+/// trailing spaces trimmed. A line too short for a field gives it empty text,
+/// so every row has a field per width; any text past the last width is one
+/// more field.
+fn split_fixed_width(s: &str, widths: &[usize]) -> Vec<Value> {
+    let mut rest = s;
+    let mut fields: Vec<Value> = widths
+        .iter()
+        .map(|&width| {
+            let end = rest
+                .char_indices()
+                .nth(width)
+                .map_or(rest.len(), |(i, _)| i);
+            let (field, after) = rest.split_at(end);
+            rest = after;
+            Value::Text(field.trim_end().to_string())
+        })
+        .collect();
+    if !rest.trim_end().is_empty() {
+        fields.push(Value::Text(rest.trim_end().to_string()));
+    }
+    fields
+}
+
+/// Lines of a sample to look at when guessing its delimiter.
+const DETECT_LINES: usize = 10;
+
+/// Guess whether `sample`, the start of some input, is comma-, tab- or
+/// pipe-separated.
+///
+/// Each candidate splits the first few lines into fields, and the one that
+/// gives the same number of fields (at least two) on the most lines wins, ties
+/// going to the one with more fields. Commas mean CSV; tabs and pipes are plain
+/// delimiters. A partial last line is ignored. Gives `None` if no candidate
+/// splits the lines at all.
+pub fn detect_split_mode(sample: &str) -> Option<SplitMode> {
+    let sample = match sample.rfind('\n') {
+        Some(end) => &sample[..end],
+        None => sample,
+    };
+
+    let mut best: Option<(f64, usize, u8)> = None;
+    for delimiter in [b',', b'\t', b'|'] {
+        let counts: Vec<usize> = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .delimiter(delimiter)
+            .from_reader(sample.as_bytes())
+            .records()
+            .take(DETECT_LINES)
+            .map_while(|record| record.ok().map(|r| r.len()))
+            .collect();
+        // The most common number of fields, and how many lines have it
+        let Some((fields, lines)) = counts
+            .iter()
+            .map(|&n| (n, counts.iter().filter(|&&m| m == n).count()))
+            .max_by_key(|&(n, lines)| (lines, n))
+        else {
+            continue;
+        };
+        if fields < 2 {
+            continue;
+        }
+        let consistency = lines as f64 / counts.len() as f64;
+        if best.is_none_or(|(c, f, _)| (consistency, fields) > (c, f)) {
+            best = Some((consistency, fields, delimiter));
+        }
     }
+
+    best.map(|(_, _, delimiter)| match delimiter {
+        b',' => SplitMode::Csv(CsvFormat::default()),
+        other => SplitMode::Delimiter((other as char).to_string()),
+    })
 }
 
 pub struct SplitDelim {
     delimiter: String,
+    keep: Option<KeepDelimiter>,
+    limit: Option<usize>,
 }
 
 impl SplitDelim {
     pub fn new(delimiter: String) -> Self {
-        Self { delimiter }
+        Self::with_options(delimiter, None, None)
+    }
+
+    /// Split on `delimiter`, optionally keeping each delimiter attached to one
+    /// side, and stopping once there are `limit` parts.
+    pub fn with_options(
+        delimiter: String,
+        keep: Option<KeepDelimiter>,
+        limit: Option<usize>,
+    ) -> Self {
+        Self {
+            delimiter,
+            keep,
+            limit,
+        }
+    }
+
+    fn split<'a>(&self, s: &'a str) -> Vec<&'a str> {
+        let Some(keep) = self.keep else {
+            return match self.limit {
+                Some(limit) => s.splitn(limit, &self.delimiter).collect(),
+                None => s.split(&self.delimiter).collect(),
+            };
+        };
+        let max_cuts = self
+            .limit
+            .map_or(usize::MAX, |limit| limit.saturating_sub(1));
+        let mut parts = Vec::new();
+        let mut start = 0;
+        for (pos, delim) in s.match_indices(&self.delimiter).take(max_cuts) {
+            let cut = match keep {
+                KeepDelimiter::WithPrevious => pos + delim.len(),
+                KeepDelimiter::WithNext => pos,
+            };
+            parts.push(&s[start..cut]);
+            start = cut;
+        }
+        parts.push(&s[start..]);
+        parts
     }
 }
 
 impl Transform for SplitDelim {
+    fn apply(&self, value: Value) -> Result<Value> {
+        match value {
+            Value::Array(mut arr) => {
+                arr.elements = arr
+                    .elements
+                    .into_iter()
+                    .map(|v| self.apply(v))
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(Value::Array(arr))
+            }
+            Value::Text(s) => {
+                let parts: Vec<Value> = self
+                    .split(&s)
+                    .into_iter()
+                    .map(|part| Value::Text(part.to_string()))
+                    .collect();
+                Ok(Value::Array(Array::from((parts, Level::Word))))
+            }
+            Value::Number(n) => Ok(Value::Number(n)),
+            Value::Bool(b) => Ok(Value::Bool(b)),
+        }
+    }
+}
+
+/// Splits text on any one of a set of characters. Adjacent delimiters give
+/// empty parts, as with `SplitDelim`.
+pub struct SplitAny {
+    chars: Vec<char>,
+}
+
+impl SplitAny {
+    pub fn new(chars: Vec<char>) -> Self {
+        Self { chars }
+    }
+}
+
+impl Transform for SplitAny {
     fn apply(&self, value: Value) -> Result<Value> {
         match value {
             Value::Array(mut arr) => {
@@ -130,12 +329,175 @@ impl Transform for SplitDelim {
             }
             Value::Text(s) => {
                 let parts: Vec<Value> = s
-                    .split(&self.delimiter)
+                    .split(self.chars.as_slice())
                     .map(|part| Value::Text(part.to_string()))
                     .collect();
                 Ok(Value::Array(Array::from((parts, Level::Word))))
             }
             Value::Number(n) => Ok(Value::Number(n)),
+            Value::Bool(b) => Ok(Value::Bool(b)),
+        }
+    }
+}
+
+/// Splits text into sentences, breaking after `.`, `!` or `?` (and any closing
+/// quotes or brackets) followed by whitespace. The punctuation stays with its
+/// sentence, and the sentences are trimmed.
+///
+/// Telling abbreviations from sentence ends is guesswork: a `.` doesn't end a
+/// sentence after a title like `Dr`, a single letter, a word with a dot inside
+/// it like `e.g`, or when the next word starts in lowercase.
+pub struct SplitSentences;
+
+/// Titles that are written with a dot but rarely end a sentence.
+const ABBREVIATIONS: &[&str] = &[
+    "mr", "mrs", "ms", "dr", "prof", "sr", "jr", "st", "vs", "no", "fig", "approx",
+];
+
+/// Whether the `.` ending `before` is more likely an abbreviation than the end
+/// of a sentence, given the text that follows.
+fn is_abbreviation(before: &str, after: &str) -> bool {
+    let word = before
+        .rsplit(char::is_whitespace)
+        .next()
+        .unwrap_or("")
+        .trim_start_matches(['"', '\'', '(', '[']);
+    let next_is_lowercase = after
+        .trim_start()
+        .chars()
+        .next()
+        .is_some_and(char::is_lowercase);
+    word.chars().count() == 1
+        || word.contains('.')
+        || ABBREVIATIONS.contains(&word.to_lowercase().as_str())
+        || next_is_lowercase
+}
+
+fn split_sentences(s: &str) -> Vec<&str> {
+    let is_end = |c: char| matches!(c, '.' | '!' | '?');
+    let is_closing = |c: char| matches!(c, '"' | '\'' | ')' | ']' | '”' | '’');
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    let mut pos = 0;
+    while let Some(offset) = s[pos..].find(is_end) {
+        let punct = pos + offset;
+        let run = &s[punct..];
+        let end = punct + run.find(|c| !is_end(c)).unwrap_or(run.len());
+        let tail = &s[end..];
+        let end = end + tail.find(|c| !is_closing(c)).unwrap_or(tail.len());
+        pos = end;
+        let after = &s[end..];
+        if !(after.is_empty() || after.starts_with(char::is_whitespace)) {
+            continue;
+        }
+        if s[punct..end].trim_end_matches(is_closing) == "."
+            && is_abbreviation(&s[start..punct], after)
+        {
+            continue;
+        }
+        let sentence = s[start..end].trim();
+        if !sentence.is_empty() {
+            sentences.push(sentence);
+        }
+        start = end;
+    }
+    let rest = s[start..].trim();
+    if !rest.is_empty() {
+        sentences.push(rest);
+    }
+    sentences
+}
+
+impl Transform for SplitSentences {
+    fn apply(&self, value: Value) -> Result<Value> {
+        match value {
+            Value::Array(mut arr) => {
+                arr.elements = arr
+                    .elements
+                    .into_iter()
+                    .map(|v| self.apply(v))
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(Value::Array(arr))
+            }
+            Value::Text(s) => {
+                let sentences: Vec<Value> = split_sentences(&s)
+                    .into_iter()
+                    .map(|sentence| Value::Text(sentence.to_string()))
+                    .collect();
+                Ok(Value::Array(Array::from((sentences, Level::Line))))
+            }
+            Value::Number(n) => Ok(Value::Number(n)),
+            Value::Bool(b) => Ok(Value::Bool(b)),
+        }
+    }
+}
+
+/// Parses `key=value` pairs, as in `level=error msg="disk full" code=500`,
+/// into a `[key, value]` row per pair. A value may be quoted with `"` to hold
+/// spaces, with `\"` and `\\` as escapes. A token without `=` gives an empty
+/// value, and text after the first `=` is all value.
+pub struct ParseKv;
+
+/// The pairs of a `key=value` line.
+fn key_values(s: &str) -> Vec<(String, String)> {
+    let mut pairs = Vec::new();
+    let mut chars = s.chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if chars.peek().is_none() {
+            break;
+        }
+        let mut key = String::new();
+        while let Some(c) = chars.next_if(|&c| c != '=' && !c.is_whitespace()) {
+            key.push(c);
+        }
+        let mut value = String::new();
+        if chars.next_if_eq(&'=').is_some() {
+            if chars.next_if_eq(&'"').is_some() {
+                // An unclosed quote runs to the end of the line
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => break,
+                        '\\' => value.extend(chars.next()),
+                        c => value.push(c),
+                    }
+                }
+            } else {
+                while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                    value.push(c);
+                }
+            }
+        }
+        pairs.push((key, value));
+    }
+    pairs
+}
+
+impl Transform for ParseKv {
+    fn apply(&self, value: Value) -> Result<Value> {
+        match value {
+            Value::Array(mut arr) => {
+                arr.elements = arr
+                    .elements
+                    .into_iter()
+                    .map(|v| self.apply(v))
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(Value::Array(arr))
+            }
+            Value::Text(s) => {
+                let pairs: Vec<Value> = key_values(&s)
+                    .into_iter()
+                    .map(|(key, value)| {
+                        Value::Array(Array::from((
+                            vec![Value::Text(key), Value::Text(value)],
+                            Level::Word,
+                        )))
+                    })
+                    .collect();
+                Ok(Value::Array(Array::from((pairs, Level::Line))))
+            }
+            Value::Number(n) => Ok(Value::Number(n)),
+            Value::Bool(b) => Ok(Value::Bool(b)),
         }
     }
 }
@@ -245,7 +607,9 @@ mod tests {
     #[test]
     fn split_csv_simple() {
         let input = line_array(&["a,b,c"]);
-        let result = Split::new(SplitMode::Csv).apply(input).unwrap();
+        let result = Split::new(SplitMode::Csv(CsvFormat::default()))
+            .apply(input)
+            .unwrap();
         match result {
             Value::Array(arr) => {
                 assert_eq!(arr.len(), 1);
@@ -266,7 +630,9 @@ mod tests {
     #[test]
     fn split_csv_quoted_fields() {
         let input = line_array(&[r#"a,"b,c",d"#]);
-        let result = Split::new(SplitMode::Csv).apply(input).unwrap();
+        let result = Split::new(SplitMode::Csv(CsvFormat::default()))
+            .apply(input)
+            .unwrap();
         match result {
             Value::Array(arr) => {
                 assert_eq!(arr.len(), 1);
@@ -287,7 +653,9 @@ mod tests {
     #[test]
     fn split_csv_quoted_with_quotes() {
         let input = line_array(&[r#"a,"b""c",d"#]);
-        let result = Split::new(SplitMode::Csv).apply(input).unwrap();
+        let result = Split::new(SplitMode::Csv(CsvFormat::default()))
+            .apply(input)
+            .unwrap();
         match result {
             Value::Array(arr) => {
                 assert_eq!(arr.len(), 1);
@@ -305,10 +673,99 @@ mod tests {
         }
     }
 
+    #[test]
+    fn split_csv_custom_delimiter_and_quote() {
+        let format = CsvFormat {
+            delimiter: b';',
+            quote: b'\'',
+        };
+        let input = line_array(&["a;'b;c';'it''s'", "1,5;2"]);
+        let result = Split::new(SplitMode::Csv(format)).apply(input).unwrap();
+        let expected = Value::Array(Array::from((
+            vec![
+                Value::Array(Array::from((
+                    vec![text("a"), text("b;c"), text("it's")],
+                    Level::Word,
+                ))),
+                Value::Array(Array::from((vec![text("1,5"), text("2")], Level::Word))),
+            ],
+            Level::Line,
+        )));
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn split_fixed_width_two_fields() {
+        let input = line_array(&["Smith     42", "Müller    7", "Lee"]);
+        let result = Split::new(SplitMode::FixedWidth(vec![10, 3]))
+            .apply(input)
+            .unwrap();
+        // Widths count chars, and short lines get empty fields
+        let row =
+            |a: &str, b: &str| Value::Array(Array::from((vec![text(a), text(b)], Level::Word)));
+        let expected = Value::Array(Array::from((
+            vec![row("Smith", "42"), row("Müller", "7"), row("Lee", "")],
+            Level::Line,
+        )));
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn split_fixed_width_keeps_the_rest() {
+        let input = line_array(&["ab  cd  and the rest  "]);
+        let result = Split::new(SplitMode::FixedWidth(vec![4, 4]))
+            .apply(input)
+            .unwrap();
+        let expected = Value::Array(Array::from((
+            vec![Value::Array(Array::from((
+                vec![text("ab"), text("cd"), text("and the rest")],
+                Level::Word,
+            )))],
+            Level::Line,
+        )));
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn detect_comma_separated() {
+        let sample = "name,qty,note\napple,3,\"red, sweet\"\nfig,12,ripe\nkiwi,4,";
+        assert!(matches!(
+            detect_split_mode(sample),
+            Some(SplitMode::Csv(format)) if format == CsvFormat::default()
+        ));
+    }
+
+    #[test]
+    fn detect_tab_separated() {
+        // The commas in the second column are inconsistent, the tabs aren't
+        let sample = "id\tnote\n1\tone, two\n2\tthree\n3\tfour, five, six\n";
+        assert!(matches!(
+            detect_split_mode(sample),
+            Some(SplitMode::Delimiter(d)) if d == "\t"
+        ));
+    }
+
+    #[test]
+    fn detect_pipe_separated() {
+        let sample = "a|b|c\n1|2|3\n";
+        assert!(matches!(
+            detect_split_mode(sample),
+            Some(SplitMode::Delimiter(d)) if d == "|"
+        ));
+    }
+
+    #[test]
+    fn detect_nothing_in_plain_text() {
+        assert!(detect_split_mode("the cat sat\non the mat\n").is_none());
+        assert!(detect_split_mode("").is_none());
+    }
+
     #[test]
     fn split_csv_empty() {
         let input = line_array(&[""]);
-        let result = Split::new(SplitMode::Csv).apply(input).unwrap();
+        let result = Split::new(SplitMode::Csv(CsvFormat::default()))
+            .apply(input)
+            .unwrap();
         match result {
             Value::Array(arr) => {
                 assert_eq!(arr.len(), 1);
@@ -413,4 +870,205 @@ mod tests {
         let result = SplitDelim::new(",".to_string()).apply(input).unwrap();
         assert_eq!(result, Value::Number(42.0));
     }
+
+    fn parts(value: Value) -> Vec<Value> {
+        match value {
+            Value::Array(arr) => arr.elements,
+            _ => panic!("expected array"),
+        }
+    }
+
+    #[test]
+    fn split_delim_keep_with_next() {
+        let split = SplitDelim::with_options(",".to_string(), Some(KeepDelimiter::WithNext), None);
+        let result = parts(split.apply(text("a,b,c")).unwrap());
+        assert_eq!(result, vec![text("a"), text(",b"), text(",c")]);
+    }
+
+    #[test]
+    fn split_delim_keep_with_previous() {
+        let split =
+            SplitDelim::with_options(", ".to_string(), Some(KeepDelimiter::WithPrevious), None);
+        let result = parts(split.apply(text("a, b, c")).unwrap());
+        assert_eq!(result, vec![text("a, "), text("b, "), text("c")]);
+    }
+
+    #[test]
+    fn split_delim_keep_at_edges() {
+        let split = SplitDelim::with_options(";".to_string(), Some(KeepDelimiter::WithNext), None);
+        let result = parts(split.apply(text(";a;")).unwrap());
+        assert_eq!(result, vec![text(""), text(";a"), text(";")]);
+    }
+
+    #[test]
+    fn split_delim_keep_recurses() {
+        let split = SplitDelim::with_options(",".to_string(), Some(KeepDelimiter::WithNext), None);
+        let result = parts(split.apply(line_array(&["a,b", "c"])).unwrap());
+        assert_eq!(
+            result,
+            vec![
+                Value::Array(Array::from((vec![text("a"), text(",b")], Level::Word))),
+                Value::Array(Array::from((vec![text("c")], Level::Word))),
+            ]
+        );
+    }
+
+    #[test]
+    fn split_delim_limit_keeps_rest_intact() {
+        let split = SplitDelim::with_options(":".to_string(), None, Some(2));
+        let result = parts(split.apply(text("key: value: with: colons")).unwrap());
+        assert_eq!(result, vec![text("key"), text(" value: with: colons")]);
+    }
+
+    #[test]
+    fn split_delim_limit_larger_than_delimiters() {
+        let split = SplitDelim::with_options(",".to_string(), None, Some(10));
+        let result = parts(split.apply(text("a,b,c")).unwrap());
+        assert_eq!(result, vec![text("a"), text("b"), text("c")]);
+    }
+
+    #[test]
+    fn split_delim_limit_of_one_is_whole_text() {
+        let split = SplitDelim::with_options(",".to_string(), None, Some(1));
+        let result = parts(split.apply(text("a,b")).unwrap());
+        assert_eq!(result, vec![text("a,b")]);
+    }
+
+    #[test]
+    fn split_delim_limit_with_keep() {
+        let split =
+            SplitDelim::with_options(",".to_string(), Some(KeepDelimiter::WithPrevious), Some(2));
+        let result = parts(split.apply(text("a,b,c")).unwrap());
+        assert_eq!(result, vec![text("a,"), text("b,c")]);
+    }
+
+    #[test]
+    fn split_any_of_several_chars() {
+        let split = SplitAny::new(vec![',', ';', '|']);
+        let result = parts(split.apply(text("a,b;c|d")).unwrap());
+        assert_eq!(result, vec![text("a"), text("b"), text("c"), text("d")]);
+    }
+
+    #[test]
+    fn split_any_adjacent_delimiters_give_empty_parts() {
+        let split = SplitAny::new(vec![',', ';']);
+        let result = parts(split.apply(text("a,;b")).unwrap());
+        assert_eq!(result, vec![text("a"), text(""), text("b")]);
+    }
+
+    #[test]
+    fn split_any_recurses() {
+        let split = SplitAny::new(vec![',', ';']);
+        let result = parts(split.apply(line_array(&["a;b", "c"])).unwrap());
+        assert_eq!(
+            result,
+            vec![
+                Value::Array(Array::from((vec![text("a"), text("b")], Level::Word))),
+                Value::Array(Array::from((vec![text("c")], Level::Word))),
+            ]
+        );
+    }
+
+    fn sentences(s: &str) -> Vec<&str> {
+        split_sentences(s)
+    }
+
+    #[test]
+    fn split_sentences_keeps_punctuation() {
+        let result = SplitSentences
+            .apply(text("It rained. Did it stop?  No!  \n"))
+            .unwrap();
+        assert_eq!(result, line_array(&["It rained.", "Did it stop?", "No!"]));
+        assert_eq!(
+            sentences("Wait... what?! Fine."),
+            ["Wait...", "what?!", "Fine."]
+        );
+        assert_eq!(
+            sentences("He said \"go.\" Then he left"),
+            ["He said \"go.\"", "Then he left"]
+        );
+    }
+
+    #[test]
+    fn split_sentences_skips_abbreviations() {
+        assert_eq!(
+            sentences("Dr. Smith met J. Doe at 3.5 p.m. on Monday. It went well."),
+            [
+                "Dr. Smith met J. Doe at 3.5 p.m. on Monday.",
+                "It went well."
+            ]
+        );
+        assert_eq!(
+            sentences("Bring fruit, e.g. apples. Or not."),
+            ["Bring fruit, e.g. apples.", "Or not."]
+        );
+        assert!(sentences("   ").is_empty());
+    }
+
+    #[test]
+    fn split_sentences_recurses() {
+        let input = line_array(&["One. Two.", "Three"]);
+        let result = SplitSentences.apply(input).unwrap();
+        let expected = Value::Array(Array::from((
+            vec![line_array(&["One.", "Two."]), line_array(&["Three"])],
+            Level::Line,
+        )));
+        assert_eq!(result, expected);
+    }
+
+    fn pairs(s: &str) -> Vec<(String, String)> {
+        key_values(s)
+    }
+
+    fn owned(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn parse_kv_unquoted() {
+        assert_eq!(
+            pairs("level=error code=500  path=/a?b=c"),
+            owned(&[("level", "error"), ("code", "500"), ("path", "/a?b=c")])
+        );
+        assert_eq!(
+            pairs("ts debug= =x"),
+            owned(&[("ts", ""), ("debug", ""), ("", "x")])
+        );
+        assert!(pairs("   ").is_empty());
+    }
+
+    #[test]
+    fn parse_kv_quoted() {
+        assert_eq!(
+            pairs(r#"msg="disk full" user=bob note="say \"hi\" \\o/" empty="""#),
+            owned(&[
+                ("msg", "disk full"),
+                ("user", "bob"),
+                ("note", r#"say "hi" \o/"#),
+                ("empty", ""),
+            ])
+        );
+        assert_eq!(
+            pairs(r#"a="unclosed value b=2"#),
+            owned(&[("a", "unclosed value b=2")])
+        );
+    }
+
+    #[test]
+    fn parse_kv_rows() {
+        let result = ParseKv.apply(line_array(&["a=1 b=\"x y\""])).unwrap();
+        let pair =
+            |k: &str, v: &str| Value::Array(Array::from((vec![text(k), text(v)], Level::Word)));
+        let expected = Value::Array(Array::from((
+            vec![Value::Array(Array::from((
+                vec![pair("a", "1"), pair("b", "x y")],
+                Level::Line,
+            )))],
+            Level::Line,
+        )));
+        assert_eq!(result, expected);
+    }
 }
@@ -0,0 +1,101 @@
+use crate::error::Result;
+use crate::interpreter::Transform;
+use crate::value::Value;
+
+/// Collapses runs of whitespace inside text to a single space, or with a set
+/// of characters, runs of the same one of those characters to one.
+pub struct Squeeze {
+    chars: Option<Vec<char>>,
+}
+
+impl Squeeze {
+    pub fn new(chars: Option<&str>) -> Self {
+        Self {
+            chars: chars.map(|c| c.chars().collect()),
+        }
+    }
+
+    fn squeeze(&self, s: &str) -> String {
+        let mut result = String::with_capacity(s.len());
+        let mut prev: Option<char> = None;
+        for c in s.chars() {
+            match &self.chars {
+                None if c.is_whitespace() => {
+                    if !prev.is_some_and(char::is_whitespace) {
+                        result.push(' ');
+                    }
+                }
+                Some(chars) if chars.contains(&c) && prev == Some(c) => {}
+                _ => result.push(c),
+            }
+            prev = Some(c);
+        }
+        result
+    }
+}
+
+impl Transform for Squeeze {
+    fn apply(&self, value: Value) -> Result<Value> {
+        match value {
+            Value::Array(mut arr) => {
+                arr.elements = arr
+                    .elements
+                    .into_iter()
+                    .map(|v| self.apply(v))
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(Value::Array(arr))
+            }
+            Value::Text(s) => Ok(Value::Text(self.squeeze(&s))),
+            Value::Number(n) => Ok(Value::Number(n)),
+            Value::Bool(b) => Ok(Value::Bool(b)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value::{Array, Level};
+
+    fn text(s: &str) -> Value {
+        Value::Text(s.to_string())
+    }
+
+    #[test]
+    fn squeeze_whitespace_runs() {
+        let result = Squeeze::new(None)
+            .apply(text("GET  /a \t 200   12ms"))
+            .unwrap();
+        assert_eq!(result, text("GET /a 200 12ms"));
+    }
+
+    #[test]
+    fn squeeze_keeps_single_space_at_ends() {
+        let result = Squeeze::new(None).apply(text("  a  ")).unwrap();
+        assert_eq!(result, text(" a "));
+    }
+
+    #[test]
+    fn squeeze_given_chars() {
+        let result = Squeeze::new(Some("-/"))
+            .apply(text("a--b//c-/d  e"))
+            .unwrap();
+        assert_eq!(result, text("a-b/c-/d  e"));
+    }
+
+    #[test]
+    fn squeeze_recurses() {
+        let input = Value::Array(Array::from((
+            vec![text("a  b"), Value::Number(1.0)],
+            Level::Line,
+        )));
+        let result = Squeeze::new(None).apply(input).unwrap();
+        assert_eq!(
+            result,
+            Value::Array(Array::from((
+                vec![text("a b"), Value::Number(1.0)],
+                Level::Line
+            )))
+        );
+    }
+}
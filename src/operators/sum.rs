@@ -1,12 +1,78 @@
-use crate::error::Result;
+use crate::ast::Selection;
+use crate::error::{Error, Result};
 use crate::interpreter::Transform;
-use crate::value::Value;
+use crate::value::{Array, Value};
 
-pub struct Sum;
+use super::group::extract_key;
+
+pub struct Sum {
+    strict: bool,
+}
+
+impl Sum {
+    /// In strict mode, non-numeric text is an error instead of counting as 0.
+    pub fn new(strict: bool) -> Self {
+        Self { strict }
+    }
+}
 
 impl Transform for Sum {
     fn apply(&self, value: Value) -> Result<Value> {
-        Ok(Value::Number(sum_recursive(&value)))
+        Ok(Value::Number(sum_recursive(&value, self.strict)?))
+    }
+
+    fn requires_full_input(&self) -> bool {
+        true
+    }
+}
+
+fn sum_recursive(value: &Value, strict: bool) -> Result<f64> {
+    match value {
+        Value::Array(arr) => arr.elements.iter().map(|v| sum_recursive(v, strict)).sum(),
+        Value::Number(n) => Ok(*n),
+        Value::Text(s) => match s.parse::<f64>() {
+            Ok(n) => Ok(n),
+            Err(_) if strict => Err(Error::runtime(format!(
+                "cannot sum non-numeric value '{}'",
+                s
+            ))),
+            Err(_) => Ok(0.0),
+        },
+        // As `n` converts them, so `&matches/.../+` counts matches
+        Value::Bool(b) => Ok(if *b { 1.0 } else { 0.0 }),
+    }
+}
+
+/// Sum of the per-row product of two selected columns, e.g. `sum(qty * price)`.
+pub struct WeightedSum {
+    a: Selection,
+    b: Selection,
+}
+
+impl WeightedSum {
+    pub fn new(a: Selection, b: Selection) -> Self {
+        Self { a, b }
+    }
+
+    /// Product of the two selected fields, or None if either is missing or non-numeric.
+    fn product(&self, row: &Value) -> Option<f64> {
+        let a = extract_key(row, &self.a).ok()?.as_number()?;
+        let b = extract_key(row, &self.b).ok()?.as_number()?;
+        Some(a * b)
+    }
+}
+
+impl Transform for WeightedSum {
+    fn apply(&self, value: Value) -> Result<Value> {
+        match value {
+            Value::Array(arr) => Ok(Value::Number(
+                arr.elements
+                    .iter()
+                    .filter_map(|row| self.product(row))
+                    .sum(),
+            )),
+            other => Ok(other),
+        }
     }
 
     fn requires_full_input(&self) -> bool {
@@ -14,17 +80,205 @@ impl Transform for Sum {
     }
 }
 
-fn sum_recursive(value: &Value) -> f64 {
+/// Collect the numeric leaves of a value. Non-numeric text is skipped, or is
+/// an error in strict mode.
+fn numbers_recursive(value: &Value, strict: bool, out: &mut Vec<f64>) -> Result<()> {
     match value {
-        Value::Array(arr) => arr.elements.iter().map(sum_recursive).sum(),
-        Value::Number(n) => *n,
-        Value::Text(s) => s.parse::<f64>().unwrap_or(0.0),
+        Value::Array(arr) => {
+            for v in &arr.elements {
+                numbers_recursive(v, strict, out)?;
+            }
+        }
+        Value::Number(n) => out.push(*n),
+        Value::Text(s) => match s.parse::<f64>() {
+            Ok(n) => out.push(n),
+            Err(_) if strict => {
+                return Err(Error::runtime(format!(
+                    "cannot compute spread of non-numeric value '{}'",
+                    s
+                )));
+            }
+            Err(_) => {}
+        },
+        Value::Bool(b) => out.push(if *b { 1.0 } else { 0.0 }),
+    }
+    Ok(())
+}
+
+/// Variance of the numeric leaves, dividing by `n - 1` for a sample or `n`
+/// for a population. None when there are too few values (fewer than two for a
+/// sample, none for a population).
+fn variance(value: &Value, sample: bool, strict: bool) -> Result<Option<f64>> {
+    let mut numbers = Vec::new();
+    numbers_recursive(value, strict, &mut numbers)?;
+    let n = numbers.len();
+    let denominator = if sample { n.saturating_sub(1) } else { n };
+    if denominator == 0 {
+        return Ok(None);
+    }
+    let mean = numbers.iter().sum::<f64>() / n as f64;
+    let squares: f64 = numbers.iter().map(|x| (x - mean) * (x - mean)).sum();
+    Ok(Some(squares / denominator as f64))
+}
+
+fn number_or_empty(n: Option<f64>) -> Value {
+    match n {
+        Some(n) => Value::Number(n),
+        None => Value::Text(String::new()),
+    }
+}
+
+/// Variance of all numeric values, or empty when there are too few of them.
+pub struct Variance {
+    sample: bool,
+    strict: bool,
+}
+
+impl Variance {
+    /// `sample` divides by `n - 1` rather than `n`. In strict mode,
+    /// non-numeric text is an error instead of being skipped.
+    pub fn new(sample: bool, strict: bool) -> Self {
+        Self { sample, strict }
+    }
+}
+
+impl Transform for Variance {
+    fn apply(&self, value: Value) -> Result<Value> {
+        Ok(number_or_empty(variance(&value, self.sample, self.strict)?))
+    }
+
+    fn requires_full_input(&self) -> bool {
+        true
+    }
+}
+
+/// Standard deviation of all numeric values, or empty when there are too few
+/// of them.
+pub struct StdDev {
+    sample: bool,
+    strict: bool,
+}
+
+impl StdDev {
+    /// `sample` divides by `n - 1` rather than `n`. In strict mode,
+    /// non-numeric text is an error instead of being skipped.
+    pub fn new(sample: bool, strict: bool) -> Self {
+        Self { sample, strict }
+    }
+}
+
+impl Transform for StdDev {
+    fn apply(&self, value: Value) -> Result<Value> {
+        let variance = variance(&value, self.sample, self.strict)?;
+        Ok(number_or_empty(variance.map(f64::sqrt)))
+    }
+
+    fn requires_full_input(&self) -> bool {
+        true
+    }
+}
+
+/// The value at a percentile of all numeric values, interpolating linearly
+/// between the two nearest ranks, or empty when there are none.
+pub struct Quantile {
+    percent: f64,
+    strict: bool,
+}
+
+impl Quantile {
+    /// Fails unless `percent` is from 0 to 100. In strict mode, non-numeric
+    /// text is an error instead of being skipped.
+    pub fn new(percent: f64, strict: bool) -> Result<Self> {
+        if !(0.0..=100.0).contains(&percent) {
+            return Err(Error::runtime(format!(
+                "percentile must be from 0 to 100, got {}",
+                percent
+            )));
+        }
+        Ok(Self { percent, strict })
+    }
+}
+
+impl Transform for Quantile {
+    fn apply(&self, value: Value) -> Result<Value> {
+        let mut numbers = Vec::new();
+        numbers_recursive(&value, self.strict, &mut numbers)?;
+        if numbers.is_empty() {
+            return Ok(number_or_empty(None));
+        }
+        numbers.sort_by(f64::total_cmp);
+
+        // Position among the sorted values, from 0 to len - 1
+        let rank = self.percent / 100.0 * (numbers.len() - 1) as f64;
+        let lower = rank.floor() as usize;
+        let upper = rank.ceil() as usize;
+        let fraction = rank - lower as f64;
+        let n = numbers[lower] + (numbers[upper] - numbers[lower]) * fraction;
+        Ok(Value::Number(n))
+    }
+
+    fn requires_full_input(&self) -> bool {
+        true
+    }
+}
+
+/// Replaces each element with the mean of the trailing window of `n` elements
+/// ending at it. The first `n - 1` elements average over as many as there are
+/// so far, so the output is as long as the input.
+pub struct MovingAverage {
+    n: usize,
+    strict: bool,
+}
+
+impl MovingAverage {
+    /// Fails if `n` is 0. Non-numeric elements count as 0, or are an error in
+    /// strict mode.
+    pub fn new(n: usize, strict: bool) -> Result<Self> {
+        if n == 0 {
+            return Err(Error::runtime("moving average window must be at least 1"));
+        }
+        Ok(Self { n, strict })
+    }
+}
+
+impl Transform for MovingAverage {
+    fn apply(&self, value: Value) -> Result<Value> {
+        let Value::Array(arr) = value else {
+            return Ok(value);
+        };
+
+        let numbers = arr
+            .elements
+            .iter()
+            .map(|v| match v.as_number() {
+                Some(n) => Ok(n),
+                None if self.strict => Err(Error::runtime(format!(
+                    "cannot average non-numeric value '{}'",
+                    v
+                ))),
+                None => Ok(0.0),
+            })
+            .collect::<Result<Vec<f64>>>()?;
+
+        let elements = (0..numbers.len())
+            .map(|i| {
+                let window = &numbers[(i + 1).saturating_sub(self.n)..=i];
+                Value::Number(window.iter().sum::<f64>() / window.len() as f64)
+            })
+            .collect();
+        Ok(Value::Array(Array::from((elements, arr.level))))
+    }
+
+    fn requires_full_input(&self) -> bool {
+        true
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ast::SelectItem;
+    use crate::operators::ToNumber;
     use crate::value::{Array, Level};
 
     fn text(s: &str) -> Value {
@@ -42,7 +296,7 @@ mod tests {
             ],
             Level::Line,
         )));
-        let result = Sum.apply(input).unwrap();
+        let result = Sum::new(false).apply(input).unwrap();
         assert_eq!(result, Value::Number(10.0));
     }
 
@@ -52,7 +306,7 @@ mod tests {
             vec![text("1"), text("2"), text("3")],
             Level::Line,
         )));
-        let result = Sum.apply(input).unwrap();
+        let result = Sum::new(false).apply(input).unwrap();
         assert_eq!(result, Value::Number(6.0));
     }
 
@@ -67,35 +321,35 @@ mod tests {
             ],
             Level::Line,
         )));
-        let result = Sum.apply(input).unwrap();
+        let result = Sum::new(false).apply(input).unwrap();
         assert_eq!(result, Value::Number(6.0));
     }
 
     #[test]
     fn sum_empty_array() {
         let input = Value::Array(Array::from((vec![], Level::Line)));
-        let result = Sum.apply(input).unwrap();
+        let result = Sum::new(false).apply(input).unwrap();
         assert_eq!(result, Value::Number(0.0));
     }
 
     #[test]
     fn sum_single_number() {
         let input = Value::Number(42.0);
-        let result = Sum.apply(input).unwrap();
+        let result = Sum::new(false).apply(input).unwrap();
         assert_eq!(result, Value::Number(42.0));
     }
 
     #[test]
     fn sum_numeric_text() {
         let input = text("42");
-        let result = Sum.apply(input).unwrap();
+        let result = Sum::new(false).apply(input).unwrap();
         assert_eq!(result, Value::Number(42.0));
     }
 
     #[test]
     fn sum_non_numeric_text() {
         let input = text("hello");
-        let result = Sum.apply(input).unwrap();
+        let result = Sum::new(false).apply(input).unwrap();
         assert_eq!(result, Value::Number(0.0));
     }
 
@@ -104,7 +358,222 @@ mod tests {
         let inner1 = Value::Array(Array::from((vec![text("1"), text("2")], Level::Word)));
         let inner2 = Value::Array(Array::from((vec![text("3"), text("4")], Level::Word)));
         let input = Value::Array(Array::from((vec![inner1, inner2], Level::Line)));
-        let result = Sum.apply(input).unwrap();
+        let result = Sum::new(false).apply(input).unwrap();
         assert_eq!(result, Value::Number(10.0));
     }
+
+    #[test]
+    fn sum_strict_rejects_non_numeric() {
+        let input = Value::Array(Array::from((
+            vec![text("1"), text("2x"), text("3")],
+            Level::Line,
+        )));
+        let err = Sum::new(true).apply(input).unwrap_err();
+        assert_eq!(err.message, "cannot sum non-numeric value '2x'");
+    }
+
+    #[test]
+    fn sum_counts_true_as_one() {
+        let input = Value::Array(Array::from((
+            vec![Value::Bool(true), Value::Bool(false), Value::Bool(true)],
+            Level::Line,
+        )));
+        let expected = Value::Number(2.0);
+        assert_eq!(Sum::new(false).apply(input.deep_copy()).unwrap(), expected);
+        assert_eq!(Sum::new(true).apply(input.deep_copy()).unwrap(), expected);
+        // The same as converting with `n` first
+        let converted = ToNumber::new(false).apply(input).unwrap();
+        assert_eq!(Sum::new(false).apply(converted).unwrap(), expected);
+    }
+
+    #[test]
+    fn sum_strict_nested_numbers() {
+        let inner = Value::Array(Array::from((vec![text("1"), text("2")], Level::Word)));
+        let input = Value::Array(Array::from((vec![inner, Value::Number(3.0)], Level::Line)));
+        let result = Sum::new(true).apply(input).unwrap();
+        assert_eq!(result, Value::Number(6.0));
+    }
+
+    fn index(i: i64) -> Selection {
+        Selection {
+            items: vec![SelectItem::Index(i)],
+        }
+    }
+
+    fn table(rows: Vec<Vec<Value>>) -> Value {
+        Value::Array(Array::from((
+            rows.into_iter()
+                .map(|r| Value::Array(Array::from((r, Level::Word))))
+                .collect(),
+            Level::Line,
+        )))
+    }
+
+    #[test]
+    fn weighted_sum_numbers() {
+        let input = table(vec![
+            vec![Value::Number(2.0), Value::Number(3.0)],
+            vec![Value::Number(4.0), Value::Number(5.0)],
+        ]);
+        let result = WeightedSum::new(index(0), index(1)).apply(input).unwrap();
+        assert_eq!(result, Value::Number(26.0));
+    }
+
+    #[test]
+    fn weighted_sum_coerces_text() {
+        let input = table(vec![
+            vec![text("widget"), text("2"), text("3")],
+            vec![text("gadget"), text("4"), text("5")],
+        ]);
+        let result = WeightedSum::new(index(1), index(-1)).apply(input).unwrap();
+        assert_eq!(result, Value::Number(26.0));
+    }
+
+    #[test]
+    fn weighted_sum_skips_non_numeric_rows() {
+        let input = table(vec![
+            vec![text("qty"), text("price")],
+            vec![text("2"), text("3")],
+            vec![text("1")],
+            vec![text("4"), text("5")],
+        ]);
+        let result = WeightedSum::new(index(0), index(1)).apply(input).unwrap();
+        assert_eq!(result, Value::Number(26.0));
+    }
+
+    #[test]
+    fn weighted_sum_empty_array() {
+        let input = table(vec![]);
+        let result = WeightedSum::new(index(0), index(1)).apply(input).unwrap();
+        assert_eq!(result, Value::Number(0.0));
+    }
+
+    fn numbers(ns: &[f64]) -> Value {
+        Value::Array(Array::from((
+            ns.iter().map(|&n| Value::Number(n)).collect(),
+            Level::Line,
+        )))
+    }
+
+    #[test]
+    fn moving_average_ramp() {
+        let result = MovingAverage::new(3, false)
+            .unwrap()
+            .apply(numbers(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]))
+            .unwrap();
+        assert_eq!(result, numbers(&[1.0, 1.5, 2.0, 3.0, 4.0, 5.0]));
+    }
+
+    #[test]
+    fn moving_average_window_of_one_is_identity() {
+        let input = Value::Array(Array::from((
+            vec![text("4"), Value::Number(2.0)],
+            Level::Line,
+        )));
+        let result = MovingAverage::new(1, false).unwrap().apply(input).unwrap();
+        assert_eq!(result, numbers(&[4.0, 2.0]));
+    }
+
+    #[test]
+    fn moving_average_non_numeric() {
+        let input = Value::Array(Array::from((vec![text("2"), text("x")], Level::Line)));
+        let lenient = MovingAverage::new(2, false).unwrap();
+        assert_eq!(
+            lenient.apply(input.deep_copy()).unwrap(),
+            numbers(&[2.0, 1.0])
+        );
+        let strict = MovingAverage::new(2, true).unwrap();
+        assert!(strict.apply(input).is_err());
+        assert!(MovingAverage::new(0, false).is_err());
+    }
+
+    #[test]
+    fn variance_and_std_dev() {
+        // Mean 5, squared deviations sum to 32
+        let input = numbers(&[2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0]);
+        let spread = |op: &dyn Transform| op.apply(input.deep_copy()).unwrap();
+        assert_eq!(spread(&Variance::new(false, false)), Value::Number(4.0));
+        assert_eq!(spread(&StdDev::new(false, false)), Value::Number(2.0));
+        assert_eq!(
+            spread(&Variance::new(true, false)),
+            Value::Number(32.0 / 7.0)
+        );
+        assert_eq!(
+            spread(&StdDev::new(true, false)),
+            Value::Number((32.0f64 / 7.0).sqrt())
+        );
+    }
+
+    #[test]
+    fn variance_recurses_and_skips_text() {
+        let input = Value::Array(Array::from((
+            vec![
+                numbers(&[1.0, 3.0]),
+                text("n/a"),
+                Value::Array(Array::from((vec![text("5")], Level::Word))),
+            ],
+            Level::Line,
+        )));
+        // [1, 3, 5]: mean 3, squared deviations sum to 8
+        assert_eq!(
+            Variance::new(true, false).apply(input.deep_copy()).unwrap(),
+            Value::Number(4.0)
+        );
+        assert!(Variance::new(true, true).apply(input).is_err());
+    }
+
+    #[test]
+    fn variance_of_too_few_values_is_empty() {
+        let empty = text("");
+        assert_eq!(
+            Variance::new(false, false).apply(numbers(&[])).unwrap(),
+            empty
+        );
+        assert_eq!(
+            Variance::new(false, false).apply(numbers(&[3.0])).unwrap(),
+            Value::Number(0.0)
+        );
+        assert_eq!(
+            StdDev::new(true, false).apply(numbers(&[3.0])).unwrap(),
+            empty
+        );
+    }
+
+    #[test]
+    fn quantile_endpoints_and_median() {
+        let input = numbers(&[40.0, 10.0, 30.0, 20.0, 50.0]);
+        let at = |p| {
+            Quantile::new(p, false)
+                .unwrap()
+                .apply(input.deep_copy())
+                .unwrap()
+        };
+        assert_eq!(at(0.0), Value::Number(10.0));
+        assert_eq!(at(50.0), Value::Number(30.0));
+        assert_eq!(at(100.0), Value::Number(50.0));
+    }
+
+    #[test]
+    fn quantile_interpolates() {
+        // Rank 0.9 * 3 = 2.7 between 30 and 40
+        let input = Value::Array(Array::from((
+            vec![text("40"), text("10"), text("x"), text("30"), text("20")],
+            Level::Line,
+        )));
+        let result = Quantile::new(90.0, false).unwrap().apply(input).unwrap();
+        let Value::Number(n) = result else {
+            panic!("expected number");
+        };
+        assert!((n - 37.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn quantile_of_nothing_is_empty() {
+        let result = Quantile::new(50.0, false)
+            .unwrap()
+            .apply(numbers(&[]))
+            .unwrap();
+        assert_eq!(result, text(""));
+        assert!(Quantile::new(100.5, false).is_err());
+    }
 }
@@ -1,4 +1,4 @@
-use crate::ast::Selection;
+use crate::ast::{Selection, TrimSide};
 use crate::error::Result;
 use crate::interpreter::Transform;
 use crate::value::Value;
@@ -20,6 +20,7 @@ impl Transform for Trim {
             }
             Value::Text(s) => Ok(Value::Text(s.trim().to_string())),
             Value::Number(n) => Ok(Value::Number(n)),
+            Value::Bool(b) => Ok(Value::Bool(b)),
         }
     }
 }
@@ -43,6 +44,70 @@ impl Transform for TrimSelected {
     }
 }
 
+/// Trims any of a set of characters from one or both ends of text.
+pub struct TrimChars {
+    chars: Vec<char>,
+    side: TrimSide,
+}
+
+impl TrimChars {
+    pub fn new(chars: &str, side: TrimSide) -> Self {
+        Self {
+            chars: chars.chars().collect(),
+            side,
+        }
+    }
+}
+
+impl Transform for TrimChars {
+    fn apply(&self, value: Value) -> Result<Value> {
+        match value {
+            Value::Array(mut arr) => {
+                arr.elements = arr
+                    .elements
+                    .into_iter()
+                    .map(|v| self.apply(v))
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(Value::Array(arr))
+            }
+            Value::Text(s) => {
+                let chars = self.chars.as_slice();
+                let trimmed = match self.side {
+                    TrimSide::Both => s.trim_matches(chars),
+                    TrimSide::Start => s.trim_start_matches(chars),
+                    TrimSide::End => s.trim_end_matches(chars),
+                };
+                Ok(Value::Text(trimmed.to_string()))
+            }
+            Value::Number(n) => Ok(Value::Number(n)),
+            Value::Bool(b) => Ok(Value::Bool(b)),
+        }
+    }
+}
+
+pub struct TrimCharsSelected {
+    selection: Selection,
+    trim: TrimChars,
+}
+
+impl TrimCharsSelected {
+    pub fn new(selection: Selection, chars: &str, side: TrimSide) -> Self {
+        Self {
+            selection,
+            trim: TrimChars::new(chars, side),
+        }
+    }
+}
+
+impl Transform for TrimCharsSelected {
+    fn apply(&self, value: Value) -> Result<Value> {
+        match value {
+            Value::Array(arr) => apply_to_selected(arr, &self.selection, |v| self.trim.apply(v)),
+            other => Ok(other),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -191,4 +256,40 @@ mod tests {
         let result = TrimSelected::new(sel).apply(input).unwrap();
         assert_eq!(result, text("  hello  "));
     }
+
+    #[test]
+    fn trim_chars_both_ends() {
+        let trim = TrimChars::new("\"[]", TrimSide::Both);
+        let input = line_array(&["\"quoted\"", "[bracketed]", "in\"side"]);
+        let result = trim.apply(input).unwrap();
+        assert_eq!(result, line_array(&["quoted", "bracketed", "in\"side"]));
+    }
+
+    #[test]
+    fn trim_chars_one_side() {
+        let input = || text("--x--");
+        assert_eq!(
+            TrimChars::new("-", TrimSide::Start).apply(input()).unwrap(),
+            text("x--")
+        );
+        assert_eq!(
+            TrimChars::new("-", TrimSide::End).apply(input()).unwrap(),
+            text("--x")
+        );
+    }
+
+    #[test]
+    fn trim_chars_selected() {
+        let input = Value::Array(Array::from((vec![text("'a'"), text("'b'")], Level::Word)));
+        let sel = Selection {
+            items: vec![SelectItem::Index(1)],
+        };
+        let result = TrimCharsSelected::new(sel, "'", TrimSide::Both)
+            .apply(input)
+            .unwrap();
+        assert_eq!(
+            result,
+            Value::Array(Array::from((vec![text("'a'"), text("b")], Level::Word)))
+        );
+    }
 }
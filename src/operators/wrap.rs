@@ -0,0 +1,115 @@
+use crate::error::Result;
+use crate::interpreter::Transform;
+use crate::value::{Array, Level, Value};
+
+/// Wraps text at word boundaries so no line is longer than `width` chars,
+/// like `fmt -w`. Each text element of an array is replaced by its lines, so
+/// the array may grow. Runs of whitespace become single spaces, and a word
+/// longer than the width is left whole on a line of its own.
+pub struct Wrap {
+    width: usize,
+}
+
+impl Wrap {
+    pub fn new(width: usize) -> Self {
+        Self { width }
+    }
+
+    fn wrap(&self, s: &str) -> Vec<String> {
+        let mut lines = Vec::new();
+        let mut line = String::new();
+        let mut line_len = 0;
+        for word in s.split_whitespace() {
+            let word_len = word.chars().count();
+            if line_len > 0 && line_len + 1 + word_len > self.width {
+                lines.push(std::mem::take(&mut line));
+                line_len = 0;
+            }
+            if line_len > 0 {
+                line.push(' ');
+                line_len += 1;
+            }
+            line.push_str(word);
+            line_len += word_len;
+        }
+        // A blank line stays as one, e.g. between paragraphs
+        if line_len > 0 || lines.is_empty() {
+            lines.push(line);
+        }
+        lines
+    }
+}
+
+impl Transform for Wrap {
+    fn apply(&self, value: Value) -> Result<Value> {
+        match value {
+            Value::Array(mut arr) => {
+                let mut elements = Vec::with_capacity(arr.elements.len());
+                for elem in arr.elements {
+                    match elem {
+                        Value::Text(s) => {
+                            elements.extend(self.wrap(&s).into_iter().map(Value::Text))
+                        }
+                        other => elements.push(self.apply(other)?),
+                    }
+                }
+                arr.elements = elements;
+                Ok(Value::Array(arr))
+            }
+            Value::Text(s) => {
+                let lines = self.wrap(&s).into_iter().map(Value::Text).collect();
+                Ok(Value::Array(Array::from((lines, Level::Line))))
+            }
+            other => Ok(other),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(s: &str) -> Value {
+        Value::Text(s.to_string())
+    }
+
+    fn line_array(lines: &[&str]) -> Value {
+        Value::Array(Array::from((
+            lines.iter().map(|s| text(s)).collect(),
+            Level::Line,
+        )))
+    }
+
+    #[test]
+    fn wrap_at_word_boundaries() {
+        let input = line_array(&["the quick brown fox jumps", "over"]);
+        let result = Wrap::new(10).apply(input).unwrap();
+        assert_eq!(
+            result,
+            line_array(&["the quick", "brown fox", "jumps", "over"])
+        );
+    }
+
+    #[test]
+    fn wrap_fits_exactly() {
+        let result = Wrap::new(9).apply(text("the quick brown")).unwrap();
+        assert_eq!(result, line_array(&["the quick", "brown"]));
+    }
+
+    #[test]
+    fn wrap_long_words_overflow() {
+        let input = line_array(&["see https://example.com/a/long/path now"]);
+        let result = Wrap::new(10).apply(input).unwrap();
+        assert_eq!(
+            result,
+            line_array(&["see", "https://example.com/a/long/path", "now"])
+        );
+    }
+
+    #[test]
+    fn wrap_squeezes_whitespace_and_keeps_blank_lines() {
+        let input = line_array(&["  a   b  ", "", "c"]);
+        let result = Wrap::new(80).apply(input).unwrap();
+        assert_eq!(result, line_array(&["a b", "", "c"]));
+    }
+}
@@ -1,12 +1,18 @@
 use regex::Regex;
 use winnow::ModalResult;
-use winnow::ascii::digit1;
-use winnow::combinator::{alt, cut_err, opt, repeat, separated};
+use winnow::ascii::{digit0, digit1, float};
+use winnow::combinator::{
+    alt, cut_err, delimited, opt, peek, preceded, repeat, separated, terminated,
+};
 use winnow::error::{StrContext, StrContextValue};
 use winnow::prelude::*;
-use winnow::token::{one_of, take_till};
+use winnow::token::{none_of, one_of, take_till, take_while};
 
-use crate::ast::{Operator, Programme, SelectItem, Selection, Slice};
+use crate::ast::{
+    AggFunc, ArithOp, CaseStyle, Comparison, KeepDelimiter, Literal, Operator, Predicate,
+    Programme, SelectItem, Selection, Slice, TrimSide,
+};
+use crate::value::Level;
 
 /// Parse a complete programme (sequence of operators).
 pub fn parse_programme(input: &str) -> std::result::Result<Programme, String> {
@@ -38,7 +44,9 @@ pub fn parse_programme(input: &str) -> std::result::Result<Programme, String> {
         } else {
             "parse error: unexpected character".to_string()
         };
-        format!("{}\n  {}\n  {}^", message, input, " ".repeat(offset))
+        // The caret is placed by column, so count chars rather than bytes
+        let column = input[..offset.min(input.len())].chars().count();
+        format!("{}\n  {}\n  {}^", message, input, " ".repeat(column))
     })
 }
 
@@ -51,28 +59,93 @@ fn programme(input: &mut &str) -> ModalResult<Programme> {
 /// Parser for a single operator.
 fn operator(input: &mut &str) -> ModalResult<Operator> {
     alt((
+        "d%".value(Operator::DedupeWithPercentages),
+        "##".value(Operator::CountLeaves),
+        "di".value(Operator::DedupeWithCountsIgnoreCase),
+        trim_chars_op,
+        scalar_op,
         simple_op,
+        text_op,
+        pattern_op,
+        reduce_op,
+        counted_op,
+        named_op,
+        deep_map_op,
+        selection_op,
+    ))
+    .parse_next(input)
+}
+
+/// Parser for deep map: `*<op>`
+fn deep_map_op(input: &mut &str) -> ModalResult<Operator> {
+    '*'.parse_next(input)?;
+    let op = cut_err(operator)
+        .context(StrContext::Expected(StrContextValue::Description(
+            "<operator>",
+        )))
+        .parse_next(input)?;
+    Ok(Operator::DeepMap(Box::new(op)))
+}
+
+/// Parser for operators that transform text: splitting, joining, case and so on.
+fn text_op(input: &mut &str) -> ModalResult<Operator> {
+    alt((
         split_delim_op,
         join_delim_op,
+        columnate_op,
+        strip_comments_op,
+        format_op,
         lowercase_selected_op,
         uppercase_selected_op,
         to_number_selected_op,
         trim_selected_op,
         partition_op,
-        replace_op,
-        match_op,
-        filter_op,
+    ))
+    .parse_next(input)
+}
+
+/// Parser for regex operators and filters.
+fn pattern_op(input: &mut &str) -> ModalResult<Operator> {
+    alt((replace_op, match_op, extract_op, filter_op, predicate_op)).parse_next(input)
+}
+
+/// Parser for operators that group or aggregate.
+fn reduce_op(input: &mut &str) -> ModalResult<Operator> {
+    alt((
         group_by_op,
+        agg_op,
+        group_agg_op,
+        weighted_sum_op,
+        arithmetic_op,
+        quantile_op,
         dedupe_selection_op,
-        selection_op,
+        dedupe_keep_first_op,
     ))
     .parse_next(input)
 }
 
+/// Parser for scalar arithmetic: `+{<number>}` or `*{<number>}`. The braces
+/// keep `+` (sum) and `*` (deep map) followed by a selection, as in `+0`,
+/// meaning what they always have.
+fn scalar_op(input: &mut &str) -> ModalResult<Operator> {
+    let op = terminated(one_of(('+', '*')), '{').parse_next(input)?;
+    let n = cut_err(terminated(number, '}'))
+        .context(StrContext::Expected(StrContextValue::Description(
+            "<number> and '}'",
+        )))
+        .parse_next(input)?;
+    Ok(match op {
+        '+' => Operator::AddScalar(n),
+        '*' => Operator::MulScalar(n),
+        _ => unreachable!(),
+    })
+}
+
 /// Parser for simple single-character operators.
 fn simple_op(input: &mut &str) -> ModalResult<Operator> {
     one_of((
-        's', 'j', '@', '^', 'u', 'l', 't', 'n', 'x', 'f', 'd', '+', '#', 'c', 'o', 'O', ';',
+        's', 'j', '@', '^', 'u', 'l', 't', 'n', 'x', 'f', 'd', '+', '#', 'o', 'O', 'z', 'w', 'b',
+        'R', ';',
     ))
     .map(|c| match c {
         's' => Operator::Split,
@@ -88,24 +161,88 @@ fn simple_op(input: &mut &str) -> ModalResult<Operator> {
         'd' => Operator::DedupeWithCounts,
         '+' => Operator::Sum,
         '#' => Operator::Count,
-        'c' => Operator::Columnate,
         'o' => Operator::SortDescending,
         'O' => Operator::SortAscending,
+        'z' => Operator::Shuffle,
+        'w' => Operator::Stats,
+        'b' => Operator::Histogram,
+        'R' => Operator::ReverseChars,
         ';' => Operator::NoOp,
         _ => unreachable!(),
     })
     .parse_next(input)
 }
 
-/// Parser for split delimiter operator: `S<char>` or `S"<delim>"`
+/// Parser for split delimiter operator: `S<char>`, `S"<delim>"` or `S[<chars>]`, optionally
+/// keeping the delimiter with `S>"<delim>"` or `S<"<delim>"`, and with an
+/// optional trailing limit on the number of parts in braces (`S:{2}`). The
+/// braces keep `S:0` meaning a split followed by a selection.
+///
+/// The keep flags need a quoted delimiter, since `S>` and `S<` split on `>` and `<`.
 fn split_delim_op(input: &mut &str) -> ModalResult<Operator> {
     'S'.parse_next(input)?;
-    let delim = cut_err(alt((non_empty_quoted_string, single_char_delim)))
+    if let Some(chars) = opt(delimiter_set).parse_next(input)? {
+        return Ok(Operator::SplitAny(chars));
+    }
+    let keep = opt(terminated(one_of(('<', '>')), peek('"')))
+        .map(|flag| match flag {
+            Some('<') => Some(KeepDelimiter::WithPrevious),
+            Some(_) => Some(KeepDelimiter::WithNext),
+            None => None,
+        })
+        .parse_next(input)?;
+    let delimiter = cut_err(alt((non_empty_quoted_string, single_char_delim)))
         .context(StrContext::Expected(StrContextValue::Description(
             "<delimiter>",
         )))
         .parse_next(input)?;
-    Ok(Operator::SplitDelim(delim))
+    // A limit of 0 means no limit
+    let limit = opt(preceded(
+        '{',
+        cut_err(terminated(count, '}')).context(StrContext::Expected(
+            StrContextValue::Description("<count> and '}'"),
+        )),
+    ))
+    .parse_next(input)?
+    .filter(|&n| n > 0);
+    Ok(if keep.is_none() && limit.is_none() {
+        Operator::SplitDelim(delimiter)
+    } else {
+        Operator::SplitDelimWith {
+            delimiter,
+            keep,
+            limit,
+        }
+    })
+}
+
+/// Parser for a set of delimiter characters: `[<chars>]`, with escapes as in
+/// quoted strings. Without a closing `]` this fails, so `S[` still splits on `[`.
+fn delimiter_set(input: &mut &str) -> ModalResult<Vec<char>> {
+    '['.parse_next(input)?;
+    let chars = repeat(
+        1..,
+        alt((preceded('\\', parse_escape_char), none_of(['\\', ']']))),
+    )
+    .parse_next(input)?;
+    ']'.parse_next(input)?;
+    Ok(chars)
+}
+
+/// Parser for columnate: `c`, then an optional maximum column width in
+/// braces, `{<n>}`, and an optional `"<separator>"` to put between columns.
+/// Digits straight after `c` are a selection, as they always were.
+fn columnate_op(input: &mut &str) -> ModalResult<Operator> {
+    'c'.parse_next(input)?;
+    let width = opt(preceded(
+        '{',
+        cut_err(terminated(count.verify(|&n| n > 0), '}')).context(StrContext::Expected(
+            StrContextValue::Description("a width of at least 1 and '}'"),
+        )),
+    ))
+    .parse_next(input)?;
+    let separator = opt(quoted_string).parse_next(input)?;
+    Ok(Operator::Columnate { width, separator })
 }
 
 /// Parser for join delimiter operator: `J<char>` or `J"<delim>"`
@@ -119,6 +256,36 @@ fn join_delim_op(input: &mut &str) -> ModalResult<Operator> {
     Ok(Operator::JoinDelim(delim))
 }
 
+/// Parser for strip comments operator: `C<char>` or `C"<marker>"`, with `!`
+/// before the marker to keep comment-only lines. A lone `!` is the marker.
+fn strip_comments_op(input: &mut &str) -> ModalResult<Operator> {
+    'C'.parse_next(input)?;
+    let (keep_empty, marker) = cut_err(alt((
+        preceded('!', comment_marker).map(|marker| (true, marker)),
+        comment_marker.map(|marker| (false, marker)),
+    )))
+    .context(StrContext::Expected(StrContextValue::Description(
+        "<marker>",
+    )))
+    .parse_next(input)?;
+    Ok(Operator::StripComments { marker, keep_empty })
+}
+
+fn comment_marker(input: &mut &str) -> ModalResult<String> {
+    alt((non_empty_quoted_string, single_char_delim)).parse_next(input)
+}
+
+/// Parser for format operator: `F"<template>"`
+fn format_op(input: &mut &str) -> ModalResult<Operator> {
+    'F'.parse_next(input)?;
+    let template = cut_err(quoted_string)
+        .context(StrContext::Expected(StrContextValue::Description(
+            "<template>",
+        )))
+        .parse_next(input)?;
+    Ok(Operator::Format(template))
+}
+
 /// Parser for lowercase selected operator: `L<selection>`
 fn lowercase_selected_op(input: &mut &str) -> ModalResult<Operator> {
     'L'.parse_next(input)?;
@@ -160,7 +327,43 @@ fn trim_selected_op(input: &mut &str) -> ModalResult<Operator> {
             "<selection>",
         )))
         .parse_next(input)?;
-    Ok(Operator::TrimSelected(sel))
+    Ok(match opt(trim_chars).parse_next(input)? {
+        Some((side, chars)) => Operator::TrimCharsSelected {
+            selection: sel,
+            chars,
+            side,
+        },
+        None => Operator::TrimSelected(sel),
+    })
+}
+
+/// Parser for trim characters operator: `t"<chars>"`, `t<<chars>` or `t><chars>`
+fn trim_chars_op(input: &mut &str) -> ModalResult<Operator> {
+    't'.parse_next(input)?;
+    let (side, chars) = trim_chars(input)?;
+    Ok(Operator::TrimChars { chars, side })
+}
+
+/// Parser for the characters to trim: `"<chars>"` for both ends, or `<` / `>`
+/// followed by a character or quoted string for the start or end only.
+fn trim_chars(input: &mut &str) -> ModalResult<(TrimSide, String)> {
+    let side = opt(one_of(('<', '>')))
+        .map(|flag| match flag {
+            Some('<') => TrimSide::Start,
+            Some(_) => TrimSide::End,
+            None => TrimSide::Both,
+        })
+        .parse_next(input)?;
+    let chars = if side == TrimSide::Both {
+        non_empty_quoted_string.parse_next(input)?
+    } else {
+        cut_err(alt((non_empty_quoted_string, single_char_delim)))
+            .context(StrContext::Expected(StrContextValue::Description(
+                "<characters>",
+            )))
+            .parse_next(input)?
+    };
+    Ok((side, chars))
 }
 
 /// Parser for partition operator: `p<selection>`
@@ -193,18 +396,47 @@ fn replace_op(input: &mut &str) -> ModalResult<Operator> {
         .context(StrContext::Expected(StrContextValue::Description("'/'")))
         .parse_next(input)?;
     let replacement = slash_delimited_pattern(input, false)?;
+    if has_unclosed_group_ref(&replacement) {
+        return cut_err(winnow::combinator::fail)
+            .context(StrContext::Expected(StrContextValue::Description(
+                "'}' to close '${' in replacement",
+            )))
+            .parse_next(input);
+    }
     cut_err('/')
         .context(StrContext::Expected(StrContextValue::Description(
             "closing '/'",
         )))
         .parse_next(input)?;
+    // Flags are letters no operator starts with, so a selection can follow
+    let flags: &str = take_while(0.., ['i', 'M']).parse_next(input)?;
     Ok(Operator::Replace {
         selection: sel,
         pattern,
         replacement,
+        case_insensitive: flags.contains('i'),
+        first_only: flags.contains('M'),
     })
 }
 
+/// Returns true if a replacement has a `${` group reference with no closing `}`,
+/// which the regex crate would otherwise treat as literal text.
+fn has_unclosed_group_ref(replacement: &str) -> bool {
+    let mut rest = replacement;
+    while let Some(pos) = rest.find('$') {
+        rest = &rest[pos + 1..];
+        if let Some(after) = rest.strip_prefix('$') {
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix('{') {
+            match after.find('}') {
+                Some(end) => rest = &after[end + 1..],
+                None => return true,
+            }
+        }
+    }
+    false
+}
+
 /// Parse a non-empty quoted string (for delimiters that can't be empty).
 fn non_empty_quoted_string(input: &mut &str) -> ModalResult<String> {
     // Check for empty string "" before consuming
@@ -421,8 +653,30 @@ fn match_op(input: &mut &str) -> ModalResult<Operator> {
     Ok(Operator::Match { pattern })
 }
 
+/// Parser for extract operator: `e/<regex>/`
+fn extract_op(input: &mut &str) -> ModalResult<Operator> {
+    'e'.parse_next(input)?;
+    '/'.parse_next(input)?;
+    let before = input.len();
+    let pattern = slash_delimited_pattern(input, true)?;
+    let pattern_len = before - input.len();
+    validate_regex(&pattern, pattern_len).parse_next(input)?;
+    cut_err('/')
+        .context(StrContext::Expected(StrContextValue::Description(
+            "closing '/'",
+        )))
+        .parse_next(input)?;
+    Ok(Operator::Extract { pattern })
+}
+
 /// Parser for filter operator: `/<regex>/` or `!/<regex>/`
 fn filter_op(input: &mut &str) -> ModalResult<Operator> {
+    let (pattern, negate) = negatable_regex(input)?;
+    Ok(Operator::Filter { pattern, negate })
+}
+
+/// Parser for `/<regex>/` or `!/<regex>/`, returning the pattern and whether it is negated.
+fn negatable_regex(input: &mut &str) -> ModalResult<(String, bool)> {
     let negate = opt('!').parse_next(input)?.is_some();
     '/'.parse_next(input)?;
     let before = input.len();
@@ -434,7 +688,7 @@ fn filter_op(input: &mut &str) -> ModalResult<Operator> {
             "closing '/'",
         )))
         .parse_next(input)?;
-    Ok(Operator::Filter { pattern, negate })
+    Ok((pattern, negate))
 }
 
 /// Parser for group by operator: `g<selection>`
@@ -448,15 +702,220 @@ fn group_by_op(input: &mut &str) -> ModalResult<Operator> {
     Ok(Operator::GroupBy(sel))
 }
 
+/// Parser for aggregate operator: `A<selection>[<funcs>][<selection>]`
+fn agg_op(input: &mut &str) -> ModalResult<Operator> {
+    'A'.parse_next(input)?;
+    let key = cut_err(selection)
+        .context(StrContext::Expected(StrContextValue::Description(
+            "<selection>",
+        )))
+        .parse_next(input)?;
+    let funcs = agg_funcs(input)?;
+    let value = opt(selection).parse_next(input)?;
+    Ok(Operator::Agg { key, funcs, value })
+}
+
+/// Parser for aggregating grouped rows: `G[<funcs>][<selection>]`
+fn group_agg_op(input: &mut &str) -> ModalResult<Operator> {
+    'G'.parse_next(input)?;
+    let funcs = agg_funcs(input)?;
+    let value = opt(selection).parse_next(input)?;
+    Ok(Operator::GroupAgg { funcs, value })
+}
+
+/// Parser for a bracketed list of aggregate functions: `[<func>,...]`
+fn agg_funcs(input: &mut &str) -> ModalResult<Vec<AggFunc>> {
+    cut_err('[')
+        .context(StrContext::Expected(StrContextValue::Description("'['")))
+        .parse_next(input)?;
+    let funcs = cut_err(separated(1.., agg_func, ','))
+        .context(StrContext::Expected(StrContextValue::Description(
+            "<aggregate>",
+        )))
+        .parse_next(input)?;
+    cut_err(']')
+        .context(StrContext::Expected(StrContextValue::Description("']'")))
+        .parse_next(input)?;
+    Ok(funcs)
+}
+
+/// Parser for a single aggregate function: `#`, `+`, `a`, `<` or `>`.
+fn agg_func(input: &mut &str) -> ModalResult<AggFunc> {
+    one_of(('#', '+', 'a', '<', '>'))
+        .map(|c| match c {
+            '#' => AggFunc::Count,
+            '+' => AggFunc::Sum,
+            'a' => AggFunc::Mean,
+            '<' => AggFunc::Min,
+            '>' => AggFunc::Max,
+            _ => unreachable!(),
+        })
+        .parse_next(input)
+}
+
+/// Parser for weighted sum operator: `W<selection>*<selection>`
+fn weighted_sum_op(input: &mut &str) -> ModalResult<Operator> {
+    'W'.parse_next(input)?;
+    let a = cut_err(selection)
+        .context(StrContext::Expected(StrContextValue::Description(
+            "<selection>",
+        )))
+        .parse_next(input)?;
+    cut_err('*')
+        .context(StrContext::Expected(StrContextValue::Description("'*'")))
+        .parse_next(input)?;
+    let b = cut_err(selection)
+        .context(StrContext::Expected(StrContextValue::Description(
+            "<selection>",
+        )))
+        .parse_next(input)?;
+    Ok(Operator::WeightedSum { a, b })
+}
+
+/// Parser for per-row arithmetic: `=<selection><op><selection>`
+fn arithmetic_op(input: &mut &str) -> ModalResult<Operator> {
+    '='.parse_next(input)?;
+    let a = cut_err(selection)
+        .context(StrContext::Expected(StrContextValue::Description(
+            "<selection>",
+        )))
+        .parse_next(input)?;
+    let op = cut_err(alt((
+        '+'.value(ArithOp::Add),
+        '-'.value(ArithOp::Sub),
+        '*'.value(ArithOp::Mul),
+        '/'.value(ArithOp::Div),
+    )))
+    .context(StrContext::Expected(StrContextValue::Description(
+        "'+', '-', '*' or '/'",
+    )))
+    .parse_next(input)?;
+    let b = cut_err(selection)
+        .context(StrContext::Expected(StrContextValue::Description(
+            "<selection>",
+        )))
+        .parse_next(input)?;
+    Ok(Operator::Arithmetic { a, op, b })
+}
+
+/// Parser for quantile operator: `q<percent>`
+fn quantile_op(input: &mut &str) -> ModalResult<Operator> {
+    'q'.parse_next(input)?;
+    let percent = cut_err(number)
+        .context(StrContext::Expected(StrContextValue::Description(
+            "<percent>",
+        )))
+        .parse_next(input)?;
+    Ok(Operator::Quantile(percent))
+}
+
+/// Parser for predicate filters: `?<selection><cmp><number>`, `?#<cmp><number>`,
+/// `?<selection>/<regex>/` or `?<selection>!/<regex>/`, `?|/<regex>/` or
+/// `?&/<regex>/` (each optionally negated), and for conditional
+/// operators: `?/<regex>/<op>` or `?!/<regex>/<op>`
+fn predicate_op(input: &mut &str) -> ModalResult<Operator> {
+    '?'.parse_next(input)?;
+    if let Some((pattern, negate)) = opt(negatable_regex).parse_next(input)? {
+        let op = cut_err(operator)
+            .context(StrContext::Expected(StrContextValue::Description(
+                "<operator>",
+            )))
+            .parse_next(input)?;
+        return Ok(Operator::ApplyIf {
+            pattern,
+            negate,
+            op: Box::new(op),
+        });
+    }
+    if let Some(quantifier) = opt(one_of(('|', '&'))).parse_next(input)? {
+        let (pattern, negate) = cut_err(negatable_regex)
+            .context(StrContext::Expected(StrContextValue::Description(
+                "/<regex>/",
+            )))
+            .parse_next(input)?;
+        return Ok(Operator::FilterFields {
+            pattern,
+            negate,
+            all: quantifier == '&',
+        });
+    }
+    if opt('#').parse_next(input)?.is_some() {
+        let (cmp, value) = comparison_rhs(input)?;
+        return Ok(Operator::CompareLength { cmp, value });
+    }
+    let sel = cut_err(selection)
+        .context(StrContext::Expected(StrContextValue::Description(
+            "<selection>",
+        )))
+        .parse_next(input)?;
+    if let Some((pattern, negate)) = opt(negatable_regex).parse_next(input)? {
+        return Ok(Operator::FilterSelected {
+            selection: sel,
+            pattern,
+            negate,
+        });
+    }
+    let (cmp, value) = comparison_rhs(input)?;
+    Ok(Operator::Compare {
+        selection: sel,
+        cmp,
+        value,
+    })
+}
+
+/// Parser for the `<cmp><number>` part of a comparison filter.
+fn comparison_rhs(input: &mut &str) -> ModalResult<(Comparison, f64)> {
+    let cmp = cut_err(comparison)
+        .context(StrContext::Expected(StrContextValue::Description(
+            "<comparison>",
+        )))
+        .parse_next(input)?;
+    let value = cut_err(float)
+        .context(StrContext::Expected(StrContextValue::Description(
+            "<number>",
+        )))
+        .parse_next(input)?;
+    Ok((cmp, value))
+}
+
+/// Parser for a comparison operator: `<`, `<=`, `>`, `>=`, `=` or `!=`
+fn comparison(input: &mut &str) -> ModalResult<Comparison> {
+    alt((
+        "<=".value(Comparison::Le),
+        ">=".value(Comparison::Ge),
+        "!=".value(Comparison::Ne),
+        '<'.value(Comparison::Lt),
+        '>'.value(Comparison::Gt),
+        '='.value(Comparison::Eq),
+    ))
+    .parse_next(input)
+}
+
 /// Parser for dedupe by selection with counts: `D<selection>`
 fn dedupe_selection_op(input: &mut &str) -> ModalResult<Operator> {
     'D'.parse_next(input)?;
+    let ignore_case = opt('i').parse_next(input)?.is_some();
+    let sel = cut_err(selection)
+        .context(StrContext::Expected(StrContextValue::Description(
+            "<selection>",
+        )))
+        .parse_next(input)?;
+    Ok(if ignore_case {
+        Operator::DedupeSelectionWithCountsIgnoreCase(sel)
+    } else {
+        Operator::DedupeSelectionWithCounts(sel)
+    })
+}
+
+/// Parser for dedupe by selection without counts: `k<selection>`
+fn dedupe_keep_first_op(input: &mut &str) -> ModalResult<Operator> {
+    'k'.parse_next(input)?;
     let sel = cut_err(selection)
         .context(StrContext::Expected(StrContextValue::Description(
             "<selection>",
         )))
         .parse_next(input)?;
-    Ok(Operator::DedupeSelectionWithCounts(sel))
+    Ok(Operator::DedupeSelection(sel))
 }
 
 /// Parser for selection operator (indices, slices, multi-select).
@@ -473,7 +932,7 @@ fn selection(input: &mut &str) -> ModalResult<Selection> {
 
 /// Parser for a single select item (either a slice or an index).
 fn select_item(input: &mut &str) -> ModalResult<SelectItem> {
-    alt((slice_item, index_item)).parse_next(input)
+    alt((slice_item, index_item, name_item, predicate_item, last_item)).parse_next(input)
 }
 
 /// Parser for a slice (must contain at least one ':').
@@ -486,72 +945,478 @@ fn slice_item(input: &mut &str) -> ModalResult<SelectItem> {
     Ok(SelectItem::Slice(Slice { start, end, step }))
 }
 
+/// Parser for the last `n` elements: `~<n>`, the same as the slice `-<n>:`.
+fn last_item(input: &mut &str) -> ModalResult<SelectItem> {
+    '~'.parse_next(input)?;
+    let n = cut_err(count)
+        .context(StrContext::Expected(StrContextValue::Description(
+            "<count>",
+        )))
+        .parse_next(input)?;
+    if n == 0 {
+        return cut_err(winnow::combinator::fail)
+            .context(StrContext::Expected(StrContextValue::Description(
+                "a count of at least 1",
+            )))
+            .parse_next(input);
+    }
+    Ok(SelectItem::Slice(Slice {
+        start: Some(-(n as i64)),
+        end: None,
+        step: None,
+    }))
+}
+
 /// Parser for a single index (returns as SelectItem).
 fn index_item(input: &mut &str) -> ModalResult<SelectItem> {
     index.map(SelectItem::Index).parse_next(input)
 }
 
-/// Parser for an integer index (possibly negative).
-fn index(input: &mut &str) -> ModalResult<i64> {
-    (opt('-'), digit1)
-        .map(|(neg, digits): (Option<char>, &str)| {
-            let value: i64 = digits.parse().expect("digit1 guarantees valid digits");
-            if neg.is_some() { -value } else { value }
-        })
-        .parse_next(input)
+/// Parser for a quoted column name: `"<name>"`
+fn name_item(input: &mut &str) -> ModalResult<SelectItem> {
+    quoted_string.map(SelectItem::Name).parse_next(input)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::ast::{Operator, SelectItem, Selection, Slice};
+/// Parser for a predicate: `[<selection><cmp><value>]`, where the value is a
+/// number or a quoted string
+fn predicate_item(input: &mut &str) -> ModalResult<SelectItem> {
+    '['.parse_next(input)?;
+    let column = cut_err(selection)
+        .context(StrContext::Expected(StrContextValue::Description(
+            "<selection>",
+        )))
+        .parse_next(input)?;
+    let cmp = cut_err(comparison)
+        .context(StrContext::Expected(StrContextValue::Description(
+            "<comparison>",
+        )))
+        .parse_next(input)?;
+    let value = cut_err(alt((
+        quoted_string.map(Literal::Text),
+        float.map(Literal::Number),
+    )))
+    .context(StrContext::Expected(StrContextValue::Description(
+        "a number or \"<text>\"",
+    )))
+    .parse_next(input)?;
+    cut_err(']')
+        .context(StrContext::Expected(StrContextValue::Description("']'")))
+        .parse_next(input)?;
+    Ok(SelectItem::Predicate(Predicate { column, cmp, value }))
+}
 
-    #[test]
-    fn empty_programme() {
-        let result = parse_programme("").unwrap();
-        assert_eq!(result.operators, vec![]);
-    }
+/// Parser for named operators: `&<name>` followed by any arguments.
+fn named_op(input: &mut &str) -> ModalResult<Operator> {
+    '&'.parse_next(input)?;
+    cut_err(alt((
+        named_text_op,
+        level_op,
+        context_op,
+        matches_op,
+        line_range_op,
+        uniq_op,
+        "mode".value(Operator::Mode),
+        sort_unique_op,
+        sort_chars_op,
+        sort_by_op,
+        rank_op,
+        chunk_op,
+        ngram_op,
+        repeat_op,
+        clamp_op,
+        moving_average_op,
+        spread_op,
+        date_op,
+    )))
+    .context(StrContext::Expected(StrContextValue::Description(
+        "<operator name>",
+    )))
+    .parse_next(input)
+}
 
-    #[test]
-    fn simple_operators() {
-        let result = parse_programme("sjul").unwrap();
-        assert_eq!(
-            result.operators,
-            vec![
-                Operator::Split,
-                Operator::Join,
-                Operator::Uppercase,
-                Operator::Lowercase,
-            ]
-        );
-    }
+/// Parser for filtering with context: `context<n>/<regex>/` or
+/// `context<before>:<after>/<regex>/`, optionally negated with `!`
+fn context_op(input: &mut &str) -> ModalResult<Operator> {
+    "context".parse_next(input)?;
+    let (before, after) = cut_err((count, opt(preceded(':', count))))
+        .context(StrContext::Expected(StrContextValue::Description(
+            "<count> or <before>:<after>",
+        )))
+        .parse_next(input)?;
+    let (pattern, negate) = cut_err(negatable_regex)
+        .context(StrContext::Expected(StrContextValue::Description(
+            "/<regex>/",
+        )))
+        .parse_next(input)?;
+    Ok(Operator::FilterContext {
+        pattern,
+        negate,
+        before,
+        after: after.unwrap_or(before),
+    })
+}
 
-    #[test]
-    fn descend_ascend() {
-        let result = parse_programme("@^").unwrap();
-        assert_eq!(result.operators, vec![Operator::Descend, Operator::Ascend,]);
-    }
+/// Parser for mapping elements to booleans: `matches/<regex>/`, optionally
+/// negated with `!`
+fn matches_op(input: &mut &str) -> ModalResult<Operator> {
+    "matches".parse_next(input)?;
+    let (pattern, negate) = cut_err(negatable_regex)
+        .context(StrContext::Expected(StrContextValue::Description(
+            "/<regex>/",
+        )))
+        .parse_next(input)?;
+    Ok(Operator::Matches { pattern, negate })
+}
 
-    #[test]
-    fn single_index() {
-        let result = parse_programme("0").unwrap();
-        assert_eq!(
-            result.operators,
-            vec![Operator::Selection(Selection {
-                items: vec![SelectItem::Index(0)]
-            })]
-        );
-    }
+/// Parser for a 1-based line range: `lines<first>-<last>` or `lines<first>-`
+fn line_range_op(input: &mut &str) -> ModalResult<Operator> {
+    "lines".parse_next(input)?;
+    let (first, _, last) = cut_err((count.verify(|&n| n > 0), '-', opt(count)))
+        .context(StrContext::Expected(StrContextValue::Description(
+            "<first>-<last>, counting from 1",
+        )))
+        .parse_next(input)?;
+    Ok(Operator::LineRange { first, last })
+}
 
-    #[test]
-    fn negative_index() {
-        let result = parse_programme("-1").unwrap();
-        assert_eq!(
-            result.operators,
-            vec![Operator::Selection(Selection {
-                items: vec![SelectItem::Index(-1)]
-            })]
-        );
+/// Parser for named operators that rewrite or split text.
+fn named_text_op(input: &mut &str) -> ModalResult<Operator> {
+    alt((
+        squeeze_op,
+        expand_op,
+        wrap_op,
+        "stripansi".value(Operator::StripAnsi),
+        "sentences".value(Operator::SplitSentences),
+        "kv".value(Operator::ParseKv),
+        identifier_case_op,
+        rot_op,
+    ))
+    .parse_next(input)
+}
+
+/// Parser for identifier case conversions: `snake`, `camel`, `kebab` or `slug`
+fn identifier_case_op(input: &mut &str) -> ModalResult<Operator> {
+    alt((
+        "snake".value(Operator::ConvertCase(CaseStyle::Snake)),
+        "camel".value(Operator::ConvertCase(CaseStyle::Camel)),
+        "kebab".value(Operator::ConvertCase(CaseStyle::Kebab)),
+        "slug".value(Operator::Slugify),
+    ))
+    .parse_next(input)
+}
+
+/// Parser for letter rotation: `rot<n>`, e.g. `rot13`
+fn rot_op(input: &mut &str) -> ModalResult<Operator> {
+    "rot".parse_next(input)?;
+    let shift = cut_err(index)
+        .context(StrContext::Expected(StrContextValue::Description(
+            "<shift>",
+        )))
+        .parse_next(input)?;
+    Ok(Operator::Rot(shift))
+}
+
+/// Parser for squeeze operator: `squeeze` or `squeeze"<chars>"`
+fn squeeze_op(input: &mut &str) -> ModalResult<Operator> {
+    "squeeze".parse_next(input)?;
+    let chars = opt(non_empty_quoted_string).parse_next(input)?;
+    Ok(Operator::Squeeze(chars))
+}
+
+/// Parser for level relabelling: `level"<name>"`
+fn level_op(input: &mut &str) -> ModalResult<Operator> {
+    "level".parse_next(input)?;
+    let level = cut_err(delimited(
+        '"',
+        alt((
+            "file".value(Level::File),
+            "line".value(Level::Line),
+            "word".value(Level::Word),
+            "char".value(Level::Char),
+        )),
+        '"',
+    ))
+    .context(StrContext::Expected(StrContextValue::Description(
+        "\"file\", \"line\", \"word\" or \"char\"",
+    )))
+    .parse_next(input)?;
+    Ok(Operator::SetLevel(level))
+}
+
+/// Parser for tab expansion: `expand` (tab stops every 8 columns) or `expand<n>`
+fn expand_op(input: &mut &str) -> ModalResult<Operator> {
+    "expand".parse_next(input)?;
+    let n = opt(count).parse_next(input)?;
+    if n == Some(0) {
+        return cut_err(winnow::combinator::fail)
+            .context(StrContext::Expected(StrContextValue::Description(
+                "a tab width of at least 1",
+            )))
+            .parse_next(input);
+    }
+    Ok(Operator::ExpandTabs(n.unwrap_or(8)))
+}
+
+/// Parser for line wrapping: `wrap` (at the terminal width) or `wrap<n>`
+fn wrap_op(input: &mut &str) -> ModalResult<Operator> {
+    "wrap".parse_next(input)?;
+    let width = opt(count).parse_next(input)?;
+    if width == Some(0) {
+        return cut_err(winnow::combinator::fail)
+            .context(StrContext::Expected(StrContextValue::Description(
+                "a width of at least 1",
+            )))
+            .parse_next(input);
+    }
+    Ok(Operator::Wrap(width))
+}
+
+/// Parser for adjacent dedupe: `uniq`, or `uniqc` to count each run
+fn uniq_op(input: &mut &str) -> ModalResult<Operator> {
+    "uniq".parse_next(input)?;
+    let counts = opt('c').parse_next(input)?.is_some();
+    Ok(Operator::AdjacentDedupe { counts })
+}
+
+/// Parser for sort-unique: `sortu` (ascending) or `rsortu` (descending)
+fn sort_unique_op(input: &mut &str) -> ModalResult<Operator> {
+    alt((
+        "sortu".value(Operator::SortUnique { descending: false }),
+        "rsortu".value(Operator::SortUnique { descending: true }),
+    ))
+    .parse_next(input)
+}
+
+/// Parser for character sort: `sortchars`, or `sortcharsi` to ignore case
+fn sort_chars_op(input: &mut &str) -> ModalResult<Operator> {
+    "sortchars".parse_next(input)?;
+    let ignore_case = opt('i').parse_next(input)?.is_some();
+    Ok(Operator::SortChars { ignore_case })
+}
+
+/// Parser for multi-key sort: `sortby<key>[<|>],...`
+fn sort_by_op(input: &mut &str) -> ModalResult<Operator> {
+    "sortby".parse_next(input)?;
+    let keys: Vec<(SelectItem, bool)> = cut_err(separated(1.., sort_key, ','))
+        .context(StrContext::Expected(StrContextValue::Description(
+            "<sort keys>",
+        )))
+        .parse_next(input)?;
+    let (items, descending) = keys.into_iter().unzip();
+    Ok(Operator::SortBy {
+        keys: Selection { items },
+        descending,
+    })
+}
+
+/// Parser for one sort key: a select item, then `<` (ascending, the default)
+/// or `>` (descending).
+fn sort_key(input: &mut &str) -> ModalResult<(SelectItem, bool)> {
+    let item = select_item.parse_next(input)?;
+    let descending = opt(one_of(('<', '>'))).parse_next(input)? == Some('>');
+    Ok((item, descending))
+}
+
+/// Parser for rank: `rank`, or `ranko` to output in rank order
+fn rank_op(input: &mut &str) -> ModalResult<Operator> {
+    "rank".parse_next(input)?;
+    let sorted = opt('o').parse_next(input)?.is_some();
+    Ok(Operator::Rank { sorted })
+}
+
+/// Parser for chunk-by: `chunk<selection>`
+fn chunk_op(input: &mut &str) -> ModalResult<Operator> {
+    "chunk".parse_next(input)?;
+    let sel = cut_err(selection)
+        .context(StrContext::Expected(StrContextValue::Description(
+            "<selection>",
+        )))
+        .parse_next(input)?;
+    Ok(Operator::ChunkBy(sel))
+}
+
+/// Parser for n-grams: `ngram<n>`
+fn ngram_op(input: &mut &str) -> ModalResult<Operator> {
+    "ngram".parse_next(input)?;
+    let n = cut_err(count)
+        .context(StrContext::Expected(StrContextValue::Description(
+            "<count>",
+        )))
+        .parse_next(input)?;
+    Ok(Operator::Ngram(n))
+}
+
+/// Parser for repetition: `repeat<n>` (each element) or `cycle<n>` (the whole array)
+fn repeat_op(input: &mut &str) -> ModalResult<Operator> {
+    let whole = alt(("repeat".value(false), "cycle".value(true))).parse_next(input)?;
+    let n = cut_err(count)
+        .context(StrContext::Expected(StrContextValue::Description(
+            "<count>",
+        )))
+        .parse_next(input)?;
+    Ok(Operator::Repeat { n, whole })
+}
+
+/// Parser for clamp: `clamp<min>:<max>`
+fn clamp_op(input: &mut &str) -> ModalResult<Operator> {
+    "clamp".parse_next(input)?;
+    let (min, _, max) = cut_err((number, ':', number))
+        .context(StrContext::Expected(StrContextValue::Description(
+            "<min>:<max>",
+        )))
+        .parse_next(input)?;
+    Ok(Operator::Clamp { min, max })
+}
+
+/// Parser for moving average: `movavg<n>`
+fn moving_average_op(input: &mut &str) -> ModalResult<Operator> {
+    "movavg".parse_next(input)?;
+    let n = cut_err(count)
+        .context(StrContext::Expected(StrContextValue::Description(
+            "<count>",
+        )))
+        .parse_next(input)?;
+    Ok(Operator::MovingAverage(n))
+}
+
+/// Parser for spread: `var`, `std` (sample) or `pvar`, `pstd` (population)
+fn spread_op(input: &mut &str) -> ModalResult<Operator> {
+    alt((
+        "var".value(Operator::Variance { sample: true }),
+        "pvar".value(Operator::Variance { sample: false }),
+        "std".value(Operator::StdDev { sample: true }),
+        "pstd".value(Operator::StdDev { sample: false }),
+    ))
+    .parse_next(input)
+}
+
+/// Parser for the date operators.
+fn date_op(input: &mut &str) -> ModalResult<Operator> {
+    alt((parse_date_op, format_date_op, epoch_op)).parse_next(input)
+}
+
+/// Parser for date parsing operator: `parsedate"<format>"`
+fn parse_date_op(input: &mut &str) -> ModalResult<Operator> {
+    "parsedate".parse_next(input)?;
+    date_format(input).map(Operator::ParseDate)
+}
+
+/// Parser for date formatting operator: `formatdate"<format>"`
+fn format_date_op(input: &mut &str) -> ModalResult<Operator> {
+    "formatdate".parse_next(input)?;
+    date_format(input).map(Operator::FormatDate)
+}
+
+/// Parser for epoch operator: `epoch` or `epoch"<format>"`
+fn epoch_op(input: &mut &str) -> ModalResult<Operator> {
+    "epoch".parse_next(input)?;
+    let format = opt(non_empty_quoted_string).parse_next(input)?;
+    Ok(Operator::ToEpoch(format))
+}
+
+/// Parser for a quoted strftime-style date format.
+fn date_format(input: &mut &str) -> ModalResult<String> {
+    cut_err(non_empty_quoted_string)
+        .context(StrContext::Expected(StrContextValue::Description(
+            "<date format>",
+        )))
+        .parse_next(input)
+}
+
+/// Parser for operators taking a count: `h<n>`, `H<n>` or `Z<n>`
+fn counted_op(input: &mut &str) -> ModalResult<Operator> {
+    let op = one_of(('h', 'H', 'Z')).parse_next(input)?;
+    let n = cut_err(count)
+        .context(StrContext::Expected(StrContextValue::Description(
+            "<count>",
+        )))
+        .parse_next(input)?;
+    Ok(match op {
+        'h' => Operator::Head(n),
+        'H' => Operator::Tail(n),
+        'Z' => Operator::Sample(n),
+        _ => unreachable!(),
+    })
+}
+
+/// Parser for a non-negative count.
+fn count(input: &mut &str) -> ModalResult<usize> {
+    digit1.try_map(str::parse).parse_next(input)
+}
+
+/// Parser for an integer index (possibly negative).
+fn index(input: &mut &str) -> ModalResult<i64> {
+    (opt('-'), digit1)
+        .map(|(neg, digits): (Option<char>, &str)| {
+            let value: i64 = digits.parse().expect("digit1 guarantees valid digits");
+            if neg.is_some() { -value } else { value }
+        })
+        .parse_next(input)
+}
+
+/// Parser for a decimal number literal (possibly negative), such as `3`,
+/// `-0.5` or `.25`.
+fn number(input: &mut &str) -> ModalResult<f64> {
+    (
+        opt('-'),
+        alt(((digit1, opt(('.', digit0))).void(), ('.', digit1).void())),
+    )
+        .take()
+        .try_map(str::parse)
+        .parse_next(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::{Operator, SelectItem, Selection, Slice};
+
+    #[test]
+    fn empty_programme() {
+        let result = parse_programme("").unwrap();
+        assert_eq!(result.operators, vec![]);
+    }
+
+    #[test]
+    fn simple_operators() {
+        let result = parse_programme("sjul").unwrap();
+        assert_eq!(
+            result.operators,
+            vec![
+                Operator::Split,
+                Operator::Join,
+                Operator::Uppercase,
+                Operator::Lowercase,
+            ]
+        );
+    }
+
+    #[test]
+    fn descend_ascend() {
+        let result = parse_programme("@^").unwrap();
+        assert_eq!(result.operators, vec![Operator::Descend, Operator::Ascend,]);
+    }
+
+    #[test]
+    fn single_index() {
+        let result = parse_programme("0").unwrap();
+        assert_eq!(
+            result.operators,
+            vec![Operator::Selection(Selection {
+                items: vec![SelectItem::Index(0)]
+            })]
+        );
+    }
+
+    #[test]
+    fn negative_index() {
+        let result = parse_programme("-1").unwrap();
+        assert_eq!(
+            result.operators,
+            vec![Operator::Selection(Selection {
+                items: vec![SelectItem::Index(-1)]
+            })]
+        );
     }
 
     #[test]
@@ -677,6 +1542,69 @@ mod tests {
         );
     }
 
+    #[test]
+    fn select_last_n() {
+        assert_eq!(
+            parse_programme("~20").unwrap(),
+            parse_programme("-20:").unwrap()
+        );
+        assert_eq!(
+            parse_programme("s@0,~2").unwrap(),
+            parse_programme("s@0,-2:").unwrap()
+        );
+        assert!(parse_programme("~").is_err());
+        assert!(parse_programme("~0").is_err());
+    }
+
+    #[test]
+    fn select_by_predicate() {
+        let result = parse_programme(r#"s[1="apple"],[-1>=2.5]"#).unwrap();
+        let predicate = |column, cmp, value| {
+            SelectItem::Predicate(Predicate {
+                column: Selection {
+                    items: vec![SelectItem::Index(column)],
+                },
+                cmp,
+                value,
+            })
+        };
+        assert_eq!(
+            result.operators,
+            vec![
+                Operator::Split,
+                Operator::Selection(Selection {
+                    items: vec![
+                        predicate(1, Comparison::Eq, Literal::Text("apple".to_string())),
+                        predicate(-1, Comparison::Ge, Literal::Number(2.5)),
+                    ]
+                })
+            ]
+        );
+        assert!(parse_programme("s[1=").is_err());
+        assert!(parse_programme("s[1=2").is_err());
+    }
+
+    #[test]
+    fn select_by_name() {
+        let result = parse_programme(r#""status",0;A"method"[#]"#).unwrap();
+        assert_eq!(
+            result.operators,
+            vec![
+                Operator::Selection(Selection {
+                    items: vec![SelectItem::Name("status".to_string()), SelectItem::Index(0),]
+                }),
+                Operator::NoOp,
+                Operator::Agg {
+                    key: Selection {
+                        items: vec![SelectItem::Name("method".to_string())]
+                    },
+                    funcs: vec![AggFunc::Count],
+                    value: None,
+                },
+            ]
+        );
+    }
+
     #[test]
     fn combined_programme() {
         let result = parse_programme("s@0j").unwrap();
@@ -745,186 +1673,1182 @@ mod tests {
     }
 
     #[test]
-    fn filter_remove() {
-        let result = parse_programme("!/^a/").unwrap();
+    fn filter_remove() {
+        let result = parse_programme("!/^a/").unwrap();
+        assert_eq!(
+            result.operators,
+            vec![Operator::Filter {
+                pattern: "^a".to_string(),
+                negate: true,
+            }]
+        );
+    }
+
+    #[test]
+    fn filter_complex_pattern() {
+        let result = parse_programme("/foo.*bar/").unwrap();
+        assert_eq!(
+            result.operators,
+            vec![Operator::Filter {
+                pattern: "foo.*bar".to_string(),
+                negate: false,
+            }]
+        );
+    }
+
+    #[test]
+    fn filter_combined_with_other_ops() {
+        let result = parse_programme("s/^a/l").unwrap();
+        assert_eq!(
+            result.operators,
+            vec![
+                Operator::Split,
+                Operator::Filter {
+                    pattern: "^a".to_string(),
+                    negate: false,
+                },
+                Operator::Lowercase,
+            ]
+        );
+    }
+
+    #[test]
+    fn filter_chained() {
+        let result = parse_programme("/foo/!/bar/").unwrap();
+        assert_eq!(
+            result.operators,
+            vec![
+                Operator::Filter {
+                    pattern: "foo".to_string(),
+                    negate: false,
+                },
+                Operator::Filter {
+                    pattern: "bar".to_string(),
+                    negate: true,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn group_by_single_index() {
+        let result = parse_programme("g0").unwrap();
+        assert_eq!(
+            result.operators,
+            vec![Operator::GroupBy(Selection {
+                items: vec![SelectItem::Index(0)]
+            })]
+        );
+    }
+
+    #[test]
+    fn group_by_negative_index() {
+        let result = parse_programme("g-1").unwrap();
+        assert_eq!(
+            result.operators,
+            vec![Operator::GroupBy(Selection {
+                items: vec![SelectItem::Index(-1)]
+            })]
+        );
+    }
+
+    #[test]
+    fn group_by_composite_key() {
+        let result = parse_programme("g0,2").unwrap();
+        assert_eq!(
+            result.operators,
+            vec![Operator::GroupBy(Selection {
+                items: vec![SelectItem::Index(0), SelectItem::Index(2)]
+            })]
+        );
+    }
+
+    #[test]
+    fn group_by_slice() {
+        let result = parse_programme("g0:3").unwrap();
+        assert_eq!(
+            result.operators,
+            vec![Operator::GroupBy(Selection {
+                items: vec![SelectItem::Slice(Slice {
+                    start: Some(0),
+                    end: Some(3),
+                    step: None,
+                })]
+            })]
+        );
+    }
+
+    #[test]
+    fn group_by_followed_by_ops() {
+        let result = parse_programme("sg0o").unwrap();
+        assert_eq!(
+            result.operators,
+            vec![
+                Operator::Split,
+                Operator::GroupBy(Selection {
+                    items: vec![SelectItem::Index(0)]
+                }),
+                Operator::SortDescending,
+            ]
+        );
+    }
+
+    #[test]
+    fn group_by_missing_selection_error() {
+        let result = parse_programme("sg");
+        assert_eq!(
+            result,
+            Err("parse error: expected <selection>\n  sg\n    ^".to_string())
+        );
+    }
+
+    #[test]
+    fn agg_with_value_column() {
+        let prog = parse_programme("A0[#,+,a]2").unwrap();
+        assert_eq!(
+            prog.operators,
+            vec![Operator::Agg {
+                key: Selection {
+                    items: vec![SelectItem::Index(0)],
+                },
+                funcs: vec![AggFunc::Count, AggFunc::Sum, AggFunc::Mean],
+                value: Some(Selection {
+                    items: vec![SelectItem::Index(2)],
+                }),
+            }]
+        );
+    }
+
+    #[test]
+    fn agg_without_value_column() {
+        let prog = parse_programme("A1[<,>]o").unwrap();
+        assert_eq!(
+            prog.operators,
+            vec![
+                Operator::Agg {
+                    key: Selection {
+                        items: vec![SelectItem::Index(1)],
+                    },
+                    funcs: vec![AggFunc::Min, AggFunc::Max],
+                    value: None,
+                },
+                Operator::SortDescending,
+            ]
+        );
+    }
+
+    #[test]
+    fn agg_missing_funcs_error() {
+        let result = parse_programme("A0#");
+        assert_eq!(
+            result,
+            Err("parse error: expected '['\n  A0#\n    ^".to_string())
+        );
+    }
+
+    #[test]
+    fn agg_unknown_func_error() {
+        let result = parse_programme("A0[x]");
+        assert_eq!(
+            result,
+            Err("parse error: expected <aggregate>\n  A0[x]\n     ^".to_string())
+        );
+    }
+
+    #[test]
+    fn weighted_sum() {
+        let prog = parse_programme("sW1*-1").unwrap();
+        assert_eq!(
+            prog.operators,
+            vec![
+                Operator::Split,
+                Operator::WeightedSum {
+                    a: Selection {
+                        items: vec![SelectItem::Index(1)],
+                    },
+                    b: Selection {
+                        items: vec![SelectItem::Index(-1)],
+                    },
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn arithmetic() {
+        let prog = parse_programme("s=2-1;=-1/0").unwrap();
+        let index = |i| Selection {
+            items: vec![SelectItem::Index(i)],
+        };
+        assert_eq!(
+            prog.operators,
+            vec![
+                Operator::Split,
+                Operator::Arithmetic {
+                    a: index(2),
+                    op: ArithOp::Sub,
+                    b: index(1),
+                },
+                Operator::NoOp,
+                Operator::Arithmetic {
+                    a: index(-1),
+                    op: ArithOp::Div,
+                    b: index(0),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn arithmetic_requires_operator() {
+        let err = parse_programme("=2").unwrap_err();
+        assert!(err.to_string().contains("'+', '-', '*' or '/'"));
+    }
+
+    #[test]
+    fn weighted_sum_missing_star_error() {
+        let result = parse_programme("W1,2");
+        assert_eq!(
+            result,
+            Err("parse error: expected '*'\n  W1,2\n      ^".to_string())
+        );
+    }
+
+    #[test]
+    fn head_and_tail() {
+        let prog = parse_programme("h10H2").unwrap();
+        assert_eq!(prog.operators, vec![Operator::Head(10), Operator::Tail(2)]);
+    }
+
+    #[test]
+    fn dedupe_with_percentages() {
+        let prog = parse_programme("d%d").unwrap();
+        assert_eq!(
+            prog.operators,
+            vec![Operator::DedupeWithPercentages, Operator::DedupeWithCounts]
+        );
+    }
+
+    #[test]
+    fn group_agg() {
+        let prog = parse_programme("sg0G[#,+]1").unwrap();
+        assert_eq!(
+            prog.operators[2],
+            Operator::GroupAgg {
+                funcs: vec![AggFunc::Count, AggFunc::Sum],
+                value: Some(Selection {
+                    items: vec![SelectItem::Index(1)]
+                }),
+            }
+        );
+    }
+
+    #[test]
+    fn dedupe_ignore_case() {
+        let prog = parse_programme("diDi1").unwrap();
+        assert_eq!(
+            prog.operators,
+            vec![
+                Operator::DedupeWithCountsIgnoreCase,
+                Operator::DedupeSelectionWithCountsIgnoreCase(Selection {
+                    items: vec![SelectItem::Index(1)]
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn dedupe_keep_first() {
+        let prog = parse_programme("k0,2").unwrap();
+        assert_eq!(
+            prog.operators,
+            vec![Operator::DedupeSelection(Selection {
+                items: vec![SelectItem::Index(0), SelectItem::Index(2)]
+            })]
+        );
+    }
+
+    #[test]
+    fn stats() {
+        let prog = parse_programme("w;sw").unwrap();
+        assert_eq!(
+            prog.operators,
+            vec![
+                Operator::Stats,
+                Operator::NoOp,
+                Operator::Split,
+                Operator::Stats
+            ]
+        );
+    }
+
+    #[test]
+    fn count_leaves() {
+        let prog = parse_programme("s##;#").unwrap();
+        assert_eq!(
+            prog.operators,
+            vec![
+                Operator::Split,
+                Operator::CountLeaves,
+                Operator::NoOp,
+                Operator::Count
+            ]
+        );
+    }
+
+    #[test]
+    fn histogram() {
+        let prog = parse_programme("sfdob").unwrap();
+        assert_eq!(prog.operators.last(), Some(&Operator::Histogram));
+    }
+
+    #[test]
+    fn sample_and_shuffle() {
+        let prog = parse_programme("Z5zh3").unwrap();
+        assert_eq!(
+            prog.operators,
+            vec![Operator::Sample(5), Operator::Shuffle, Operator::Head(3)]
+        );
+    }
+
+    #[test]
+    fn head_missing_count_error() {
+        let result = parse_programme("hx");
+        assert_eq!(
+            result,
+            Err("parse error: expected <count>\n  hx\n   ^".to_string())
+        );
+    }
+
+    #[test]
+    fn compare_filter() {
+        let prog = parse_programme("?2>100").unwrap();
+        assert_eq!(
+            prog.operators,
+            vec![Operator::Compare {
+                selection: Selection {
+                    items: vec![SelectItem::Index(2)]
+                },
+                cmp: Comparison::Gt,
+                value: 100.0,
+            }]
+        );
+    }
+
+    #[test]
+    fn compare_filter_operators() {
+        for (prog, cmp, value) in [
+            ("?0<1.5", Comparison::Lt, 1.5),
+            ("?0<=-2", Comparison::Le, -2.0),
+            ("?0>=0", Comparison::Ge, 0.0),
+            ("?0=3", Comparison::Eq, 3.0),
+            ("?0!=3", Comparison::Ne, 3.0),
+        ] {
+            match &parse_programme(prog).unwrap().operators[0] {
+                Operator::Compare {
+                    cmp: c, value: v, ..
+                } => {
+                    assert_eq!((*c, *v), (cmp, value), "{}", prog);
+                }
+                other => panic!("expected compare, got {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn filter_selected() {
+        let prog = parse_programme("?1/^ok$/").unwrap();
+        assert_eq!(
+            prog.operators,
+            vec![Operator::FilterSelected {
+                selection: Selection {
+                    items: vec![SelectItem::Index(1)]
+                },
+                pattern: "^ok$".to_string(),
+                negate: false,
+            }]
+        );
+    }
+
+    #[test]
+    fn filter_fields() {
+        let prog = parse_programme("?|/x/?&!/y/").unwrap();
+        assert_eq!(
+            prog.operators,
+            vec![
+                Operator::FilterFields {
+                    pattern: "x".to_string(),
+                    negate: false,
+                    all: false,
+                },
+                Operator::FilterFields {
+                    pattern: "y".to_string(),
+                    negate: true,
+                    all: true,
+                },
+            ]
+        );
+        assert!(parse_programme("?|x").is_err());
+    }
+
+    #[test]
+    fn filter_selected_negated_vs_not_equal() {
+        let prog = parse_programme("?1!/ok/?1!=3").unwrap();
+        assert!(matches!(
+            prog.operators[0],
+            Operator::FilterSelected { negate: true, .. }
+        ));
+        assert!(matches!(
+            prog.operators[1],
+            Operator::Compare {
+                cmp: Comparison::Ne,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn compare_length_filter() {
+        let prog = parse_programme("?#>80").unwrap();
+        assert_eq!(
+            prog.operators,
+            vec![Operator::CompareLength {
+                cmp: Comparison::Gt,
+                value: 80.0,
+            }]
+        );
+    }
+
+    #[test]
+    fn compare_length_missing_comparison_error() {
+        let result = parse_programme("?#80");
+        assert_eq!(
+            result,
+            Err("parse error: expected <comparison>\n  ?#80\n    ^".to_string())
+        );
+    }
+
+    #[test]
+    fn compare_filter_missing_number_error() {
+        let result = parse_programme("?1>x");
+        assert_eq!(
+            result,
+            Err("parse error: expected <number>\n  ?1>x\n     ^".to_string())
+        );
+    }
+
+    #[test]
+    fn extract_pattern() {
+        let prog = parse_programme(r"e/(\d+)ms/x").unwrap();
+        assert_eq!(
+            prog.operators,
+            vec![
+                Operator::Extract {
+                    pattern: r"(\d+)ms".to_string()
+                },
+                Operator::DeleteEmpty,
+            ]
+        );
+    }
+
+    #[test]
+    fn extract_missing_closing_slash_error() {
+        let result = parse_programme("e/foo");
+        assert_eq!(
+            result,
+            Err("parse error: expected closing '/'\n  e/foo\n       ^".to_string())
+        );
+    }
+
+    #[test]
+    fn filter_empty_pattern_error() {
+        let result = parse_programme("//");
+        assert_eq!(
+            result,
+            Err("parse error: expected <pattern>\n  //\n   ^".to_string())
+        );
+    }
+
+    #[test]
+    fn filter_missing_closing_slash_error() {
+        let result = parse_programme("/foo");
+        assert_eq!(
+            result,
+            Err("parse error: expected closing '/'\n  /foo\n      ^".to_string())
+        );
+    }
+
+    #[test]
+    fn split_delim_single_char() {
+        let result = parse_programme("S,").unwrap();
+        assert_eq!(
+            result.operators,
+            vec![Operator::SplitDelim(",".to_string())]
+        );
+    }
+
+    #[test]
+    fn split_delim_colon() {
+        let result = parse_programme("S:").unwrap();
+        assert_eq!(
+            result.operators,
+            vec![Operator::SplitDelim(":".to_string())]
+        );
+    }
+
+    #[test]
+    fn split_delim_quoted_multi_char() {
+        let result = parse_programme(r#"S"::""#).unwrap();
+        assert_eq!(
+            result.operators,
+            vec![Operator::SplitDelim("::".to_string())]
+        );
+    }
+
+    #[test]
+    fn split_delim_quoted_single_char() {
+        let result = parse_programme(r#"S",""#).unwrap();
+        assert_eq!(
+            result.operators,
+            vec![Operator::SplitDelim(",".to_string())]
+        );
+    }
+
+    #[test]
+    fn split_delim_keep_flags() {
+        let result = parse_programme(r#"S>","S<"::""#).unwrap();
+        assert_eq!(
+            result.operators,
+            vec![
+                Operator::SplitDelimWith {
+                    delimiter: ",".to_string(),
+                    keep: Some(KeepDelimiter::WithNext),
+                    limit: None,
+                },
+                Operator::SplitDelimWith {
+                    delimiter: "::".to_string(),
+                    keep: Some(KeepDelimiter::WithPrevious),
+                    limit: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn trim_chars() {
+        let result = parse_programme(r#"t"[]"t<0t>",;"tT1"'""#).unwrap();
+        assert_eq!(
+            result.operators,
+            vec![
+                Operator::TrimChars {
+                    chars: "[]".to_string(),
+                    side: TrimSide::Both,
+                },
+                Operator::TrimChars {
+                    chars: "0".to_string(),
+                    side: TrimSide::Start,
+                },
+                Operator::TrimChars {
+                    chars: ",;".to_string(),
+                    side: TrimSide::End,
+                },
+                Operator::Trim,
+                Operator::TrimCharsSelected {
+                    selection: Selection {
+                        items: vec![SelectItem::Index(1)]
+                    },
+                    chars: "'".to_string(),
+                    side: TrimSide::Both,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn trim_selected_then_op() {
+        let result = parse_programme("T0l").unwrap();
+        assert_eq!(
+            result.operators,
+            vec![
+                Operator::TrimSelected(Selection {
+                    items: vec![SelectItem::Index(0)]
+                }),
+                Operator::Lowercase,
+            ]
+        );
+    }
+
+    #[test]
+    fn squeeze() {
+        let result = parse_programme(r#"&squeeze&squeeze"-""#).unwrap();
+        assert_eq!(
+            result.operators,
+            vec![
+                Operator::Squeeze(None),
+                Operator::Squeeze(Some("-".to_string()))
+            ]
+        );
+    }
+
+    #[test]
+    fn filter_context() {
+        let result = parse_programme("&context2/ERR/&context0:3!/ok/").unwrap();
+        assert_eq!(
+            result.operators,
+            vec![
+                Operator::FilterContext {
+                    pattern: "ERR".to_string(),
+                    negate: false,
+                    before: 2,
+                    after: 2,
+                },
+                Operator::FilterContext {
+                    pattern: "ok".to_string(),
+                    negate: true,
+                    before: 0,
+                    after: 3,
+                },
+            ]
+        );
+        assert!(parse_programme("&context/ERR/").is_err());
+        assert!(parse_programme("&context2").is_err());
+    }
+
+    #[test]
+    fn matches() {
+        let result = parse_programme("&matches/^a/&matches!/b/").unwrap();
+        assert_eq!(
+            result.operators,
+            vec![
+                Operator::Matches {
+                    pattern: "^a".to_string(),
+                    negate: false,
+                },
+                Operator::Matches {
+                    pattern: "b".to_string(),
+                    negate: true,
+                },
+            ]
+        );
+        assert!(parse_programme("&matches").is_err());
+    }
+
+    #[test]
+    fn line_range() {
+        let result = parse_programme("&lines100-200&lines5-s").unwrap();
+        assert_eq!(
+            result.operators,
+            vec![
+                Operator::LineRange {
+                    first: 100,
+                    last: Some(200)
+                },
+                Operator::LineRange {
+                    first: 5,
+                    last: None
+                },
+                Operator::Split,
+            ]
+        );
+        assert!(parse_programme("&lines0-10").is_err());
+        assert!(parse_programme("&lines10").is_err());
+    }
+
+    #[test]
+    fn split_sentences() {
+        let result = parse_programme("&sentencess").unwrap();
+        assert_eq!(
+            result.operators,
+            vec![Operator::SplitSentences, Operator::Split]
+        );
+    }
+
+    #[test]
+    fn expand_tabs() {
+        let result = parse_programme("&expand&expand4c").unwrap();
+        assert_eq!(
+            result.operators,
+            vec![
+                Operator::ExpandTabs(8),
+                Operator::ExpandTabs(4),
+                Operator::Columnate {
+                    width: None,
+                    separator: None
+                }
+            ]
+        );
+        assert!(parse_programme("&expand0").is_err());
+    }
+
+    #[test]
+    fn parse_kv() {
+        let result = parse_programme(r#"&kv@?0/^level$/"#).unwrap();
+        assert_eq!(result.operators[0], Operator::ParseKv);
+        assert_eq!(result.operators.len(), 3);
+    }
+
+    #[test]
+    fn strip_ansi() {
+        let result = parse_programme("&stripansi#").unwrap();
+        assert_eq!(result.operators, vec![Operator::StripAnsi, Operator::Count]);
+    }
+
+    #[test]
+    fn wrap() {
+        let result = parse_programme("&wrap&wrap72").unwrap();
+        assert_eq!(
+            result.operators,
+            vec![Operator::Wrap(None), Operator::Wrap(Some(72))]
+        );
+        assert!(parse_programme("&wrap0").is_err());
+    }
+
+    #[test]
+    fn set_level() {
+        let result = parse_programme(r#"&level"line"@&level"char""#).unwrap();
+        assert_eq!(
+            result.operators,
+            vec![
+                Operator::SetLevel(Level::Line),
+                Operator::Descend,
+                Operator::SetLevel(Level::Char),
+            ]
+        );
+        assert!(parse_programme(r#"&level"lines""#).is_err());
+        assert!(parse_programme("&level").is_err());
+    }
+
+    #[test]
+    fn uniq() {
+        let result = parse_programme("&uniq&uniqc&uniq;c").unwrap();
+        assert_eq!(
+            result.operators,
+            vec![
+                Operator::AdjacentDedupe { counts: false },
+                Operator::AdjacentDedupe { counts: true },
+                Operator::AdjacentDedupe { counts: false },
+                Operator::NoOp,
+                Operator::Columnate {
+                    width: None,
+                    separator: None
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn sort_unique() {
+        let result = parse_programme("&sortu&rsortu").unwrap();
+        assert_eq!(
+            result.operators,
+            vec![
+                Operator::SortUnique { descending: false },
+                Operator::SortUnique { descending: true },
+            ]
+        );
+    }
+
+    #[test]
+    fn sort_by() {
+        let result = parse_programme(r#"&sortby0,2>,"name"<;&sortby-1>"#).unwrap();
+        assert_eq!(
+            result.operators,
+            vec![
+                Operator::SortBy {
+                    keys: Selection {
+                        items: vec![
+                            SelectItem::Index(0),
+                            SelectItem::Index(2),
+                            SelectItem::Name("name".to_string()),
+                        ]
+                    },
+                    descending: vec![false, true, false],
+                },
+                Operator::NoOp,
+                Operator::SortBy {
+                    keys: Selection {
+                        items: vec![SelectItem::Index(-1)]
+                    },
+                    descending: vec![true],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn chunk_by() {
+        let result = parse_programme("s&chunk0,2").unwrap();
+        assert_eq!(
+            result.operators,
+            vec![
+                Operator::Split,
+                Operator::ChunkBy(Selection {
+                    items: vec![SelectItem::Index(0), SelectItem::Index(2)]
+                }),
+            ]
+        );
+        assert!(parse_programme("&chunk").is_err());
+    }
+
+    #[test]
+    fn deep_map() {
+        let result = parse_programme("s**u*N0").unwrap();
+        assert_eq!(
+            result.operators,
+            vec![
+                Operator::Split,
+                Operator::DeepMap(Box::new(Operator::DeepMap(Box::new(Operator::Uppercase)))),
+                Operator::DeepMap(Box::new(Operator::ToNumberSelected(Selection {
+                    items: vec![SelectItem::Index(0)]
+                }))),
+            ]
+        );
+    }
+
+    #[test]
+    fn deep_map_missing_operator_error() {
+        let result = parse_programme("s*");
+        assert_eq!(
+            result,
+            Err("parse error: expected <operator>\n  s*\n    ^".to_string())
+        );
+    }
+
+    #[test]
+    fn apply_if() {
+        let result = parse_programme("?/ERROR/u?!/^#/s").unwrap();
+        assert_eq!(
+            result.operators,
+            vec![
+                Operator::ApplyIf {
+                    pattern: "ERROR".to_string(),
+                    negate: false,
+                    op: Box::new(Operator::Uppercase),
+                },
+                Operator::ApplyIf {
+                    pattern: "^#".to_string(),
+                    negate: true,
+                    op: Box::new(Operator::Split),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn apply_if_missing_operator_error() {
+        let result = parse_programme("?/x/");
+        assert_eq!(
+            result,
+            Err("parse error: expected <operator>\n  ?/x/\n      ^".to_string())
+        );
+    }
+
+    #[test]
+    fn scalar_arithmetic() {
+        let result = parse_programme("s@N1*{1.8}+{32};+{-0.5}*{.25}").unwrap();
+        assert_eq!(
+            result.operators,
+            vec![
+                Operator::Split,
+                Operator::Descend,
+                Operator::ToNumberSelected(Selection {
+                    items: vec![SelectItem::Index(1)]
+                }),
+                Operator::MulScalar(1.8),
+                Operator::AddScalar(32.0),
+                Operator::NoOp,
+                Operator::AddScalar(-0.5),
+                Operator::MulScalar(0.25),
+            ]
+        );
+    }
+
+    #[test]
+    fn sum_and_deep_map_then_selection() {
+        // Digits after `+` and `*` are a selection, as they always were
+        let result = parse_programme("+0*1").unwrap();
+        assert_eq!(
+            result.operators,
+            vec![
+                Operator::Sum,
+                Operator::Selection(Selection {
+                    items: vec![SelectItem::Index(0)]
+                }),
+                Operator::DeepMap(Box::new(Operator::Selection(Selection {
+                    items: vec![SelectItem::Index(1)]
+                }))),
+            ]
+        );
+        assert!(parse_programme("+{").is_err());
+        assert!(parse_programme("*{2").is_err());
+        assert!(parse_programme("+{x}").is_err());
+    }
+
+    #[test]
+    fn sum_and_deep_map_without_number() {
+        let result = parse_programme("+*u+").unwrap();
+        assert_eq!(
+            result.operators,
+            vec![
+                Operator::Sum,
+                Operator::DeepMap(Box::new(Operator::Uppercase)),
+                Operator::Sum,
+            ]
+        );
+    }
+
+    #[test]
+    fn rank() {
+        let result = parse_programme("&rank&ranko").unwrap();
         assert_eq!(
             result.operators,
-            vec![Operator::Filter {
-                pattern: "^a".to_string(),
-                negate: true,
-            }]
+            vec![
+                Operator::Rank { sorted: false },
+                Operator::Rank { sorted: true },
+            ]
         );
     }
 
     #[test]
-    fn filter_complex_pattern() {
-        let result = parse_programme("/foo.*bar/").unwrap();
+    fn moving_average() {
+        let result = parse_programme("n&movavg7").unwrap();
         assert_eq!(
             result.operators,
-            vec![Operator::Filter {
-                pattern: "foo.*bar".to_string(),
-                negate: false,
-            }]
+            vec![Operator::ToNumber, Operator::MovingAverage(7)]
         );
+        assert!(parse_programme("&movavg").is_err());
     }
 
     #[test]
-    fn filter_combined_with_other_ops() {
-        let result = parse_programme("s/^a/l").unwrap();
+    fn spread() {
+        let result = parse_programme("&var&pvar&std&pstd").unwrap();
         assert_eq!(
             result.operators,
             vec![
-                Operator::Split,
-                Operator::Filter {
-                    pattern: "^a".to_string(),
-                    negate: false,
-                },
-                Operator::Lowercase,
+                Operator::Variance { sample: true },
+                Operator::Variance { sample: false },
+                Operator::StdDev { sample: true },
+                Operator::StdDev { sample: false },
             ]
         );
     }
 
     #[test]
-    fn filter_chained() {
-        let result = parse_programme("/foo/!/bar/").unwrap();
+    fn quantile() {
+        let result = parse_programme("q95q99.9").unwrap();
+        assert_eq!(
+            result.operators,
+            vec![Operator::Quantile(95.0), Operator::Quantile(99.9)]
+        );
+        assert_eq!(
+            parse_programme("q"),
+            Err("parse error: expected <percent>\n  q\n   ^".to_string())
+        );
+    }
+
+    #[test]
+    fn convert_case() {
+        let result = parse_programme("&snake&camel&kebab").unwrap();
         assert_eq!(
             result.operators,
             vec![
-                Operator::Filter {
-                    pattern: "foo".to_string(),
-                    negate: false,
-                },
-                Operator::Filter {
-                    pattern: "bar".to_string(),
-                    negate: true,
-                },
+                Operator::ConvertCase(CaseStyle::Snake),
+                Operator::ConvertCase(CaseStyle::Camel),
+                Operator::ConvertCase(CaseStyle::Kebab),
             ]
         );
     }
 
     #[test]
-    fn group_by_single_index() {
-        let result = parse_programme("g0").unwrap();
+    fn rot() {
+        let result = parse_programme("&rot13").unwrap();
+        assert_eq!(result.operators, vec![Operator::Rot(13)]);
+        let result = parse_programme("&rot-3").unwrap();
+        assert_eq!(result.operators, vec![Operator::Rot(-3)]);
+        assert!(parse_programme("&rot").is_err());
+    }
+
+    #[test]
+    fn slugify() {
+        let result = parse_programme("&slug").unwrap();
+        assert_eq!(result.operators, vec![Operator::Slugify]);
+    }
+
+    #[test]
+    fn ngram() {
+        let result = parse_programme("s&ngram2").unwrap();
+        assert_eq!(result.operators, vec![Operator::Split, Operator::Ngram(2)]);
+        assert!(parse_programme("&ngram").is_err());
+    }
+
+    #[test]
+    fn reverse_chars() {
+        let result = parse_programme("s@R").unwrap();
         assert_eq!(
             result.operators,
-            vec![Operator::GroupBy(Selection {
-                items: vec![SelectItem::Index(0)]
-            })]
+            vec![Operator::Split, Operator::Descend, Operator::ReverseChars]
         );
     }
 
     #[test]
-    fn group_by_negative_index() {
-        let result = parse_programme("g-1").unwrap();
+    fn sort_chars() {
+        let result = parse_programme("&sortchars&sortcharsi&sortby0").unwrap();
         assert_eq!(
             result.operators,
-            vec![Operator::GroupBy(Selection {
-                items: vec![SelectItem::Index(-1)]
-            })]
+            vec![
+                Operator::SortChars { ignore_case: false },
+                Operator::SortChars { ignore_case: true },
+                Operator::SortBy {
+                    keys: Selection {
+                        items: vec![SelectItem::Index(0)]
+                    },
+                    descending: vec![false],
+                },
+            ]
         );
     }
 
     #[test]
-    fn group_by_composite_key() {
-        let result = parse_programme("g0,2").unwrap();
+    fn repeat() {
+        let result = parse_programme("&repeat3&cycle0").unwrap();
         assert_eq!(
             result.operators,
-            vec![Operator::GroupBy(Selection {
-                items: vec![SelectItem::Index(0), SelectItem::Index(2)]
-            })]
+            vec![
+                Operator::Repeat { n: 3, whole: false },
+                Operator::Repeat { n: 0, whole: true },
+            ]
         );
+        assert!(parse_programme("&cycle").is_err());
     }
 
     #[test]
-    fn group_by_slice() {
-        let result = parse_programme("g0:3").unwrap();
+    fn mode() {
+        let result = parse_programme("&mode").unwrap();
+        assert_eq!(result.operators, vec![Operator::Mode]);
+    }
+
+    #[test]
+    fn clamp() {
+        let result = parse_programme("&clamp0:100&clamp-1.5:.5").unwrap();
         assert_eq!(
             result.operators,
-            vec![Operator::GroupBy(Selection {
-                items: vec![SelectItem::Slice(Slice {
-                    start: Some(0),
-                    end: Some(3),
-                    step: None,
-                })]
-            })]
+            vec![
+                Operator::Clamp {
+                    min: 0.0,
+                    max: 100.0
+                },
+                Operator::Clamp {
+                    min: -1.5,
+                    max: 0.5
+                },
+            ]
+        );
+        assert_eq!(
+            parse_programme("&clamp5"),
+            Err("parse error: expected <min>:<max>\n  &clamp5\n         ^".to_string())
         );
     }
 
     #[test]
-    fn group_by_followed_by_ops() {
-        let result = parse_programme("sg0o").unwrap();
+    fn date_ops() {
+        let result =
+            parse_programme(r#"&parsedate"%d/%m/%Y"&formatdate"%F"&epoch&epoch"%s""#).unwrap();
         assert_eq!(
             result.operators,
             vec![
-                Operator::Split,
-                Operator::GroupBy(Selection {
-                    items: vec![SelectItem::Index(0)]
-                }),
-                Operator::SortDescending,
+                Operator::ParseDate("%d/%m/%Y".to_string()),
+                Operator::FormatDate("%F".to_string()),
+                Operator::ToEpoch(None),
+                Operator::ToEpoch(Some("%s".to_string())),
             ]
         );
     }
 
     #[test]
-    fn group_by_missing_selection_error() {
-        let result = parse_programme("sg");
+    fn date_op_missing_format_error() {
+        let result = parse_programme("&formatdate");
         assert_eq!(
             result,
-            Err("parse error: expected <selection>\n  sg\n    ^".to_string())
+            Err("parse error: expected <date format>\n  &formatdate\n             ^".to_string())
         );
     }
 
     #[test]
-    fn filter_empty_pattern_error() {
-        let result = parse_programme("//");
+    fn unknown_named_op_error() {
+        let result = parse_programme("&nope");
         assert_eq!(
             result,
-            Err("parse error: expected <pattern>\n  //\n   ^".to_string())
+            Err("parse error: expected <operator name>\n  &nope\n   ^".to_string())
         );
     }
 
     #[test]
-    fn filter_missing_closing_slash_error() {
-        let result = parse_programme("/foo");
+    fn split_any() {
+        let result = parse_programme(r"S[,;\t]").unwrap();
         assert_eq!(
-            result,
-            Err("parse error: expected closing '/'\n  /foo\n      ^".to_string())
+            result.operators,
+            vec![Operator::SplitAny(vec![',', ';', '\t'])]
         );
     }
 
     #[test]
-    fn split_delim_single_char() {
-        let result = parse_programme("S,").unwrap();
+    fn split_on_open_bracket_without_set() {
+        let result = parse_programme("S[l").unwrap();
         assert_eq!(
             result.operators,
-            vec![Operator::SplitDelim(",".to_string())]
+            vec![Operator::SplitDelim("[".to_string()), Operator::Lowercase]
         );
     }
 
     #[test]
-    fn split_delim_colon() {
-        let result = parse_programme("S:").unwrap();
+    fn split_delim_then_selection() {
+        // Digits after a delimiter are a selection, as they always were
+        let result = parse_programme("S,1").unwrap();
         assert_eq!(
             result.operators,
-            vec![Operator::SplitDelim(":".to_string())]
+            vec![
+                Operator::SplitDelim(",".to_string()),
+                Operator::Selection(Selection {
+                    items: vec![SelectItem::Index(1)]
+                }),
+            ]
         );
     }
 
     #[test]
-    fn split_delim_quoted_multi_char() {
-        let result = parse_programme(r#"S"::""#).unwrap();
+    fn split_delim_limit() {
+        assert!(parse_programme("S:{").is_err());
+        assert!(parse_programme("S:{2").is_err());
+        let result = parse_programme(r#"S:{2}1S"::"0"#).unwrap();
         assert_eq!(
             result.operators,
-            vec![Operator::SplitDelim("::".to_string())]
+            vec![
+                Operator::SplitDelimWith {
+                    delimiter: ":".to_string(),
+                    keep: None,
+                    limit: Some(2),
+                },
+                Operator::Selection(Selection {
+                    items: vec![SelectItem::Index(1)]
+                }),
+                Operator::SplitDelim("::".to_string()),
+                Operator::Selection(Selection {
+                    items: vec![SelectItem::Index(0)]
+                }),
+            ]
         );
     }
 
     #[test]
-    fn split_delim_quoted_single_char() {
-        let result = parse_programme(r#"S",""#).unwrap();
+    fn split_delim_on_angle_brackets() {
+        let result = parse_programme("S>S<l").unwrap();
         assert_eq!(
             result.operators,
-            vec![Operator::SplitDelim(",".to_string())]
+            vec![
+                Operator::SplitDelim(">".to_string()),
+                Operator::SplitDelim("<".to_string()),
+                Operator::Lowercase,
+            ]
         );
     }
 
@@ -997,6 +2921,22 @@ mod tests {
         );
     }
 
+    #[test]
+    fn error_caret_after_multibyte_delimiter() {
+        let result = parse_programme("S\"→\"g");
+        assert_eq!(
+            result,
+            Err("parse error: expected <selection>\n  S\"→\"g\n       ^".to_string())
+        );
+        let result = parse_programme("Sé/[/");
+        assert_eq!(
+            result,
+            Err(
+                "parse error: invalid regex: unclosed character class\n  Sé/[/\n     ^".to_string()
+            )
+        );
+    }
+
     #[test]
     fn split_delim_followed_by_ops() {
         let result = parse_programme("S,l").unwrap();
@@ -1006,6 +2946,83 @@ mod tests {
         );
     }
 
+    #[test]
+    fn strip_comments_single_char() {
+        let prog = parse_programme("C#x").unwrap();
+        assert_eq!(
+            prog.operators,
+            vec![
+                Operator::StripComments {
+                    marker: "#".to_string(),
+                    keep_empty: false
+                },
+                Operator::DeleteEmpty
+            ]
+        );
+    }
+
+    #[test]
+    fn strip_comments_quoted_marker() {
+        let prog = parse_programme(r#"C"//""#).unwrap();
+        assert_eq!(
+            prog.operators,
+            vec![Operator::StripComments {
+                marker: "//".to_string(),
+                keep_empty: false
+            }]
+        );
+    }
+
+    #[test]
+    fn strip_comments_keeping_lines() {
+        let prog = parse_programme(r#"C!#C!"//"C!"#).unwrap();
+        assert_eq!(
+            prog.operators,
+            vec![
+                Operator::StripComments {
+                    marker: "#".to_string(),
+                    keep_empty: true
+                },
+                Operator::StripComments {
+                    marker: "//".to_string(),
+                    keep_empty: true
+                },
+                // A lone `!` is the marker
+                Operator::StripComments {
+                    marker: "!".to_string(),
+                    keep_empty: false
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn strip_comments_missing_marker_error() {
+        let result = parse_programme("C");
+        assert_eq!(
+            result,
+            Err("parse error: expected <marker>\n  C\n   ^".to_string())
+        );
+    }
+
+    #[test]
+    fn format_template() {
+        let prog = parse_programme(r#"F"{1}:\t{0}"j"#).unwrap();
+        assert_eq!(
+            prog.operators,
+            vec![Operator::Format("{1}:\t{0}".to_string()), Operator::Join]
+        );
+    }
+
+    #[test]
+    fn format_missing_template_error() {
+        let result = parse_programme("F");
+        assert_eq!(
+            result,
+            Err("parse error: expected <template>\n  F\n   ^".to_string())
+        );
+    }
+
     #[test]
     fn split_delim_missing_delimiter_error() {
         let result = parse_programme("S");
@@ -1231,6 +3248,8 @@ mod tests {
                 selection: None,
                 pattern: "foo".to_string(),
                 replacement: "bar".to_string(),
+                case_insensitive: false,
+                first_only: false,
             }]
         );
     }
@@ -1244,6 +3263,8 @@ mod tests {
                 selection: None,
                 pattern: "foo".to_string(),
                 replacement: "".to_string(),
+                case_insensitive: false,
+                first_only: false,
             }]
         );
     }
@@ -1259,6 +3280,8 @@ mod tests {
                 }),
                 pattern: "foo".to_string(),
                 replacement: "bar".to_string(),
+                case_insensitive: false,
+                first_only: false,
             }]
         );
     }
@@ -1278,6 +3301,8 @@ mod tests {
                 }),
                 pattern: "foo".to_string(),
                 replacement: "bar".to_string(),
+                case_insensitive: false,
+                first_only: false,
             }]
         );
     }
@@ -1292,12 +3317,91 @@ mod tests {
                     selection: None,
                     pattern: "a".to_string(),
                     replacement: "b".to_string(),
+                    case_insensitive: false,
+                    first_only: false,
                 },
                 Operator::Lowercase,
             ]
         );
     }
 
+    #[test]
+    fn replace_with_flags() {
+        let result = parse_programme("r/foo/bar/iM").unwrap();
+        assert_eq!(
+            result.operators,
+            vec![Operator::Replace {
+                selection: None,
+                pattern: "foo".to_string(),
+                replacement: "bar".to_string(),
+                case_insensitive: true,
+                first_only: true,
+            }]
+        );
+    }
+
+    #[test]
+    fn replace_then_selection() {
+        // Digits after a replace are a selection, not flags
+        let result = parse_programme("r/a/b/10").unwrap();
+        assert_eq!(
+            result.operators,
+            vec![
+                Operator::Replace {
+                    selection: None,
+                    pattern: "a".to_string(),
+                    replacement: "b".to_string(),
+                    case_insensitive: false,
+                    first_only: false,
+                },
+                Operator::Selection(Selection {
+                    items: vec![SelectItem::Index(10)]
+                })
+            ]
+        );
+
+        let result = parse_programme("r/a/b/M1").unwrap();
+        assert!(matches!(
+            result.operators[0],
+            Operator::Replace {
+                first_only: true,
+                ..
+            }
+        ));
+        assert_eq!(
+            result.operators[1],
+            Operator::Selection(Selection {
+                items: vec![SelectItem::Index(1)]
+            })
+        );
+    }
+
+    #[test]
+    fn replace_named_group() {
+        let result = parse_programme(r"r/(?P<word>\w+)/${word}!/").unwrap();
+        assert!(matches!(
+            &result.operators[0],
+            Operator::Replace { replacement, .. } if replacement == "${word}!"
+        ));
+    }
+
+    #[test]
+    fn replace_unclosed_group_ref_error() {
+        let result = parse_programme(r"r/(?P<w>\w+)/${w!/");
+        assert_eq!(
+            result,
+            Err(
+                "parse error: expected '}' to close '${' in replacement\n  r/(?P<w>\\w+)/${w!/\n                   ^"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn replace_escaped_dollar_is_not_group_ref() {
+        assert!(parse_programme("r/a/$${/").is_ok());
+    }
+
     #[test]
     fn replace_missing_pattern_error() {
         let result = parse_programme("r//b/");
@@ -1474,7 +3578,13 @@ mod tests {
     #[test]
     fn columnate_simple() {
         let result = parse_programme("c").unwrap();
-        assert_eq!(result.operators, vec![Operator::Columnate]);
+        assert_eq!(
+            result.operators,
+            vec![Operator::Columnate {
+                width: None,
+                separator: None
+            }]
+        );
     }
 
     #[test]
@@ -1482,8 +3592,73 @@ mod tests {
         let result = parse_programme("scj").unwrap();
         assert_eq!(
             result.operators,
-            vec![Operator::Split, Operator::Columnate, Operator::Join]
+            vec![
+                Operator::Split,
+                Operator::Columnate {
+                    width: None,
+                    separator: None
+                },
+                Operator::Join
+            ]
+        );
+    }
+
+    #[test]
+    fn columnate_with_separator() {
+        let result = parse_programme(r#"sc" | "j"#).unwrap();
+        assert_eq!(
+            result.operators,
+            vec![
+                Operator::Split,
+                Operator::Columnate {
+                    width: None,
+                    separator: Some(" | ".to_string())
+                },
+                Operator::Join
+            ]
+        );
+    }
+
+    #[test]
+    fn columnate_with_width() {
+        let result = parse_programme(r#"c{20}" | "c{8}"#).unwrap();
+        assert_eq!(
+            result.operators,
+            vec![
+                Operator::Columnate {
+                    width: Some(20),
+                    separator: Some(" | ".to_string())
+                },
+                Operator::Columnate {
+                    width: Some(8),
+                    separator: None
+                }
+            ]
         );
+        assert!(parse_programme("c{0}").is_err());
+        assert!(parse_programme("c{").is_err());
+        assert!(parse_programme("c{20").is_err());
+    }
+
+    #[test]
+    fn columnate_then_selection() {
+        // Digits after `c` are a selection, as they always were
+        for (programme, index) in [("sc0", 0), ("sc1", 1)] {
+            let result = parse_programme(programme).unwrap();
+            assert_eq!(
+                result.operators,
+                vec![
+                    Operator::Split,
+                    Operator::Columnate {
+                        width: None,
+                        separator: None
+                    },
+                    Operator::Selection(Selection {
+                        items: vec![SelectItem::Index(index)]
+                    }),
+                ]
+            );
+        }
     }
 
     #[test]
@@ -8,6 +8,7 @@
 //! - `Level`: Semantic level determining how arrays split and join
 
 use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::fs;
 use std::io::{self, BufRead, BufReader, Read};
@@ -16,6 +17,8 @@ use std::path::Path;
 use serde::Serialize;
 use serde::ser::{SerializeSeq, Serializer};
 
+use crate::operators::{CsvFormat, value_to_key};
+
 /// Semantic level of array contents - determines split/join behavior.
 ///
 /// | Level | `s` splits into | `j` joins with |
@@ -66,6 +69,16 @@ impl Level {
             Level::Char => "",
         }
     }
+
+    /// The level's name, as written in `&level"<name>"` and shown by `--debug`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Level::File => "file",
+            Level::Line => "line",
+            Level::Word => "word",
+            Level::Char => "char",
+        }
+    }
 }
 
 /// A value in the t language.
@@ -74,33 +87,148 @@ pub enum Value {
     Array(Array),
     Text(String),
     Number(f64),
+    Bool(bool),
 }
 
-impl fmt::Display for Value {
+/// How numbers are rendered on output.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct NumberFormat {
+    /// Fixed number of decimal places for non-integral numbers
+    pub precision: Option<usize>,
+    /// Separator between groups of three digits in text output, e.g. `,`
+    pub group_separator: Option<char>,
+}
+
+impl NumberFormat {
+    /// Format a number. Integral values never get a decimal point.
+    pub fn format(&self, n: f64) -> String {
+        match self.precision {
+            Some(p) if as_integral(n).is_none() && n.is_finite() => format!("{:.*}", p, n),
+            _ => format_number(n),
+        }
+    }
+
+    /// Format a number for JSON output: as `format`, but `null` for infinity
+    /// and NaN, which JSON has no syntax for. serde_json does the same.
+    pub fn format_json(&self, n: f64) -> String {
+        if n.is_finite() {
+            self.format(n)
+        } else {
+            "null".to_string()
+        }
+    }
+
+    /// Format a number for text output: as `format`, with the digits before
+    /// the decimal point grouped in threes. JSON output uses `format_json`,
+    /// so it always has valid numbers.
+    pub fn format_text(&self, n: f64) -> String {
+        let formatted = self.format(n);
+        match self.group_separator {
+            Some(separator) => group_digits(&formatted, separator),
+            None => formatted,
+        }
+    }
+}
+
+/// Insert `separator` between groups of three digits in the integer part of a
+/// formatted number, so `-1234567.5` becomes `-1,234,567.5`. Exponent
+/// notation, infinity and NaN are left as they are.
+fn group_digits(formatted: &str, separator: char) -> String {
+    if formatted.contains(['e', 'E']) {
+        return formatted.to_string();
+    }
+    let (sign, rest) = match formatted.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", formatted),
+    };
+    let int_len = rest.find('.').unwrap_or(rest.len());
+    let (int_part, fraction) = rest.split_at(int_len);
+    if !int_part.bytes().all(|b| b.is_ascii_digit()) {
+        return formatted.to_string();
+    }
+    let mut grouped = String::from(sign);
+    for (i, digit) in int_part.chars().enumerate() {
+        if i > 0 && (int_len - i) % 3 == 0 {
+            grouped.push(separator);
+        }
+        grouped.push(digit);
+    }
+    grouped.push_str(fraction);
+    grouped
+}
+
+/// Returns the integer a number represents, if it is integral.
+///
+/// Values within a few ulps of an integer (e.g. `0.1 * 30.0`, which is
+/// `3.0000000000000004`) count as integral, so float noise doesn't leak into
+/// output.
+pub fn as_integral(n: f64) -> Option<f64> {
+    let rounded = n.round();
+    if !n.is_finite() || (n - rounded).abs() > 4.0 * f64::EPSILON * n.abs() {
+        return None;
+    }
+    // Avoid printing "-0"
+    Some(if rounded == 0.0 { 0.0 } else { rounded })
+}
+
+/// Format a number for output, shared by text and JSON so both agree.
+///
+/// Integral values print without a fractional part and magnitudes from 1e21
+/// use exponent notation (as JSON serializers do); everything else uses the
+/// shortest representation that round-trips.
+pub fn format_number(n: f64) -> String {
+    if n.is_finite() && n.abs() >= 1e21 {
+        format!("{:e}", n)
+    } else if let Some(i) = as_integral(n) {
+        format!("{:.0}", i)
+    } else {
+        n.to_string()
+    }
+}
+
+/// A value paired with a number format, for display.
+pub struct Formatted<'a> {
+    value: &'a Value,
+    numbers: &'a NumberFormat,
+}
+
+impl fmt::Display for Formatted<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
+        match self.value {
             Value::Text(s) => write!(f, "{}", s),
-            Value::Number(n) => write!(f, "{}", n),
-            Value::Array(arr) => write!(f, "{}", arr),
+            Value::Number(n) => write!(f, "{}", self.numbers.format_text(*n)),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Array(arr) => write_array(f, arr, self.numbers),
         }
     }
 }
 
-impl fmt::Display for Array {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let cell = std::cell::OnceCell::new();
-        for elem in &self.elements {
-            match cell.get() {
-                None => {
-                    write!(f, "{}", elem)?;
-                    cell.set(self.level.join_delimiter()).unwrap();
-                }
-                Some(&d) => {
-                    write!(f, "{}{}", d, elem)?;
-                }
+/// Write array elements joined by the level's delimiter.
+fn write_array(f: &mut fmt::Formatter<'_>, arr: &Array, numbers: &NumberFormat) -> fmt::Result {
+    let cell = std::cell::OnceCell::new();
+    for elem in &arr.elements {
+        match cell.get() {
+            None => {
+                write!(f, "{}", elem.formatted(numbers))?;
+                cell.set(arr.level.join_delimiter()).unwrap();
+            }
+            Some(&d) => {
+                write!(f, "{}{}", d, elem.formatted(numbers))?;
             }
         }
-        Ok(())
+    }
+    Ok(())
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.formatted(&NumberFormat::default()))
+    }
+}
+
+impl fmt::Display for Array {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_array(f, self, &NumberFormat::default())
     }
 }
 
@@ -111,7 +239,11 @@ impl Serialize for Value {
     {
         match self {
             Value::Text(s) => serializer.serialize_str(s),
-            Value::Number(n) => serializer.serialize_f64(*n),
+            Value::Number(n) => match as_integral(*n) {
+                Some(i) if i.abs() < 1e18 => serializer.serialize_i64(i as i64),
+                _ => serializer.serialize_f64(*n),
+            },
+            Value::Bool(b) => serializer.serialize_bool(*b),
             Value::Array(arr) => arr.serialize(serializer),
         }
     }
@@ -140,6 +272,15 @@ impl Value {
             Value::Array(arr) => Value::Array(arr.deep_copy()),
             Value::Text(s) => Value::Text(s.clone()),
             Value::Number(n) => Value::Number(*n),
+            Value::Bool(b) => Value::Bool(*b),
+        }
+    }
+
+    /// Display this value with numbers rendered by the given format.
+    pub fn formatted<'a>(&'a self, numbers: &'a NumberFormat) -> Formatted<'a> {
+        Formatted {
+            value: self,
+            numbers,
         }
     }
 
@@ -152,21 +293,34 @@ impl Value {
     ///
     /// - Empty strings are empty
     /// - Empty arrays are empty
-    /// - Numbers are never empty (including 0)
+    /// - Numbers and booleans are never empty (including 0 and false)
     pub fn is_empty(&self) -> bool {
         match self {
             Value::Text(s) => s.is_empty(),
             Value::Array(arr) => arr.is_empty(),
-            Value::Number(_) => false,
+            Value::Number(_) | Value::Bool(_) => false,
+        }
+    }
+
+    /// Interpret this value as a number, if possible.
+    ///
+    /// Numbers are returned as-is, text is parsed, booleans and arrays are
+    /// never numeric.
+    pub fn as_number(&self) -> Option<f64> {
+        match self {
+            Value::Number(n) => Some(*n),
+            Value::Text(s) => s.parse::<f64>().ok(),
+            Value::Bool(_) | Value::Array(_) => None,
         }
     }
 
-    /// Returns a type tag for ordering: Number < Text < Array.
+    /// Returns a type tag for ordering: Bool < Number < Text < Array.
     fn type_order(&self) -> u8 {
         match self {
-            Value::Number(_) => 0,
-            Value::Text(_) => 1,
-            Value::Array(_) => 2,
+            Value::Bool(_) => 0,
+            Value::Number(_) => 1,
+            Value::Text(_) => 2,
+            Value::Array(_) => 3,
         }
     }
 }
@@ -180,10 +334,12 @@ impl PartialOrd for Value {
 }
 
 impl Ord for Value {
-    /// Compare values for sorting. Order: Number < Text < Array.
+    /// Compare values for sorting. Order: Bool < Number < Text < Array,
+    /// with false before true.
     /// Arrays compare lexicographically (Python-style).
     fn cmp(&self, other: &Self) -> Ordering {
         match (self, other) {
+            (Value::Bool(a), Value::Bool(b)) => a.cmp(b),
             (Value::Number(a), Value::Number(b)) => a.total_cmp(b),
             (Value::Text(a), Value::Text(b)) => a.cmp(b),
             (Value::Array(a), Value::Array(b)) => a.cmp(b),
@@ -199,6 +355,57 @@ pub struct Array {
     pub elements: Vec<Value>,
 }
 
+/// How `Array::combine` merges two arrays treated as sets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SetOp {
+    /// Elements of the first that are not in the second
+    Difference,
+    /// Elements of the first that are also in the second
+    Intersection,
+    /// Elements of either
+    Union,
+}
+
+/// Read CSV records into `elements` as word arrays of their fields, until
+/// `elements` holds `record_limit` rows. A quoted field may span several
+/// lines, so records are read by the CSV parser rather than line by line.
+fn read_csv_records<R: Read>(
+    reader: R,
+    format: CsvFormat,
+    record_limit: Option<usize>,
+    elements: &mut Vec<Value>,
+) -> io::Result<()> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .delimiter(format.delimiter)
+        .quote(format.quote)
+        .from_reader(reader);
+    let remaining = record_limit.map_or(usize::MAX, |limit| limit - elements.len());
+    for record in reader.records().take(remaining) {
+        let fields = record?
+            .iter()
+            .map(|field| Value::Text(field.to_string()))
+            .collect();
+        elements.push(Value::Array(Array::from((fields, Level::Word))));
+    }
+    Ok(())
+}
+
+/// The fields of a row, treating anything but an array as a row of one.
+fn row_fields(row: Value) -> Vec<Value> {
+    match row {
+        Value::Array(arr) => arr.elements,
+        other => vec![other],
+    }
+}
+
+/// Resolve a possibly negative field index against a row of `len` fields.
+fn field_index(len: usize, index: i64) -> Option<usize> {
+    let actual = if index < 0 { index + len as i64 } else { index };
+    (0..len as i64).contains(&actual).then_some(actual as usize)
+}
+
 impl Array {
     /// Create an explicit deep copy of this array.
     ///
@@ -235,18 +442,31 @@ impl Array {
     /// Load input from stdin.
     /// - `Level::File`: entire stdin as one Text element
     /// - `Level::Line`: stdin split into lines
-    pub fn from_stdin(level: Level) -> io::Result<Self> {
+    ///
+    /// With a `line_limit`, line-level reading stops once that many lines are read.
+    pub fn from_stdin(level: Level, line_limit: Option<usize>) -> io::Result<Self> {
         let stdin = io::stdin();
-        Self::from_reader(stdin.lock(), level)
+        Self::from_reader(stdin.lock(), level, line_limit)
     }
 
     /// Load input from files.
     /// - `Level::File`: each file as one Text element
     /// - `Level::Line`: all files split into lines, concatenated
-    pub fn from_files(paths: &[impl AsRef<Path>], level: Level) -> io::Result<Self> {
+    ///
+    /// With a `line_limit`, line-level reading stops once that many lines are
+    /// read in total; remaining files are not opened.
+    pub fn from_files(
+        paths: &[impl AsRef<Path>],
+        level: Level,
+        line_limit: Option<usize>,
+    ) -> io::Result<Self> {
         let mut elements = Vec::new();
 
         for path in paths {
+            if line_limit.is_some_and(|limit| elements.len() >= limit) {
+                break;
+            }
+
             let file = fs::File::open(path)?;
             let reader = BufReader::new(file);
 
@@ -263,7 +483,8 @@ impl Array {
                     elements.push(Value::Text(contents));
                 }
                 _ => {
-                    for line in reader.lines() {
+                    let remaining = line_limit.map_or(usize::MAX, |limit| limit - elements.len());
+                    for line in reader.lines().take(remaining) {
                         elements.push(Value::Text(line?));
                     }
                 }
@@ -273,8 +494,50 @@ impl Array {
         Ok(Self { level, elements })
     }
 
+    /// Load files side by side: row `i` holds line `i` of each file, in the
+    /// order given. Files that run out early are padded with empty text.
+    ///
+    /// With a `line_limit`, reading stops after that many rows.
+    pub fn from_files_zipped(
+        paths: &[impl AsRef<Path>],
+        line_limit: Option<usize>,
+    ) -> io::Result<Self> {
+        let mut readers = paths
+            .iter()
+            .map(|path| Ok(BufReader::new(fs::File::open(path)?).lines()))
+            .collect::<io::Result<Vec<_>>>()?;
+
+        let mut elements = Vec::new();
+        while line_limit.is_none_or(|limit| elements.len() < limit) {
+            let mut row = Vec::with_capacity(readers.len());
+            let mut any = false;
+            for lines in &mut readers {
+                match lines.next().transpose()? {
+                    Some(line) => {
+                        any = true;
+                        row.push(Value::Text(line));
+                    }
+                    None => row.push(Value::Text(String::new())),
+                }
+            }
+            if !any {
+                break;
+            }
+            elements.push(Value::Array(Array::from((row, Level::Word))));
+        }
+
+        Ok(Self {
+            level: Level::Line,
+            elements,
+        })
+    }
+
     /// Load from a reader.
-    fn from_reader<R: BufRead>(reader: R, level: Level) -> io::Result<Self> {
+    fn from_reader<R: BufRead>(
+        reader: R,
+        level: Level,
+        line_limit: Option<usize>,
+    ) -> io::Result<Self> {
         let mut elements = Vec::new();
 
         match level {
@@ -291,7 +554,7 @@ impl Array {
                 elements.push(Value::Text(contents));
             }
             _ => {
-                for line in reader.lines() {
+                for line in reader.lines().take(line_limit.unwrap_or(usize::MAX)) {
                     elements.push(Value::Text(line?));
                 }
             }
@@ -300,6 +563,140 @@ impl Array {
         Ok(Self { level, elements })
     }
 
+    /// Load CSV records from stdin, one row of fields per record.
+    ///
+    /// With a `record_limit`, reading stops once that many records are read.
+    pub fn from_csv_stdin(format: CsvFormat, record_limit: Option<usize>) -> io::Result<Self> {
+        let mut elements = Vec::new();
+        read_csv_records(io::stdin().lock(), format, record_limit, &mut elements)?;
+        Ok(Self {
+            level: Level::Line,
+            elements,
+        })
+    }
+
+    /// Load CSV records from files, concatenated, one row of fields per record.
+    ///
+    /// With a `record_limit`, reading stops once that many records are read in
+    /// total; remaining files are not opened.
+    pub fn from_csv_files(
+        paths: &[impl AsRef<Path>],
+        format: CsvFormat,
+        record_limit: Option<usize>,
+    ) -> io::Result<Self> {
+        let mut elements = Vec::new();
+        for path in paths {
+            if record_limit.is_some_and(|limit| elements.len() >= limit) {
+                break;
+            }
+            let file = BufReader::new(fs::File::open(path)?);
+            read_csv_records(file, format, record_limit, &mut elements)?;
+        }
+        Ok(Self {
+            level: Level::Line,
+            elements,
+        })
+    }
+
+    /// Group lines into paragraphs: runs of non-blank lines joined with
+    /// newlines, as one Text element each at `Level::File`. Blank (or
+    /// whitespace-only) lines only separate paragraphs, so leading, trailing
+    /// and repeated blanks never produce empty ones.
+    pub fn into_paragraphs(self) -> Self {
+        let mut elements = Vec::new();
+        let mut current: Vec<String> = Vec::new();
+        for value in self.elements {
+            let line = match value {
+                Value::Text(s) => s,
+                other => other.to_string(),
+            };
+            if line.trim().is_empty() {
+                if !current.is_empty() {
+                    elements.push(Value::Text(current.join("\n")));
+                    current.clear();
+                }
+            } else {
+                current.push(line);
+            }
+        }
+        if !current.is_empty() {
+            elements.push(Value::Text(current.join("\n")));
+        }
+        Self {
+            level: Level::File,
+            elements,
+        }
+    }
+
+    /// Combine with `other` as sets. Each distinct element appears once, in
+    /// the order first seen in `self` (then in `other`, for a union).
+    /// Elements must be exactly equal to match, so text comparison is
+    /// case-sensitive.
+    pub fn combine(self, other: Array, op: SetOp) -> Self {
+        let other_keys: HashSet<String> = other.elements.iter().map(value_to_key).collect();
+        let mut seen = HashSet::new();
+        let mut elements = Vec::new();
+        for value in self.elements {
+            let key = value_to_key(&value);
+            let keep = match op {
+                SetOp::Difference => !other_keys.contains(&key),
+                SetOp::Intersection => other_keys.contains(&key),
+                SetOp::Union => true,
+            };
+            if keep && seen.insert(key) {
+                elements.push(value);
+            }
+        }
+        if op == SetOp::Union {
+            for value in other.elements {
+                if seen.insert(value_to_key(&value)) {
+                    elements.push(value);
+                }
+            }
+        }
+        Self {
+            level: self.level,
+            elements,
+        }
+    }
+
+    /// Join rows of `self` to rows of `other` whose field at `key` is equal.
+    /// Each match gives a row of the fields of `self` followed by the fields
+    /// of `other` without its key. Rows with no match, or with no field at
+    /// `key`, are dropped; a key that appears in several rows of `other` gives
+    /// one row per match, in `other`'s order.
+    pub fn join_on(self, other: Array, key: i64) -> Self {
+        let mut matches: HashMap<String, Vec<Vec<Value>>> = HashMap::new();
+        for row in other.elements {
+            let mut fields = row_fields(row);
+            if let Some(i) = field_index(fields.len(), key) {
+                let field = fields.remove(i);
+                matches
+                    .entry(value_to_key(&field))
+                    .or_default()
+                    .push(fields);
+            }
+        }
+
+        let mut elements = Vec::new();
+        for row in self.elements {
+            let fields = row_fields(row);
+            let Some(rest) =
+                field_index(fields.len(), key).and_then(|i| matches.get(&value_to_key(&fields[i])))
+            else {
+                continue;
+            };
+            for rest in rest {
+                let joined = fields.iter().chain(rest).map(Value::deep_copy).collect();
+                elements.push(Value::Array(Array::from((joined, Level::Word))));
+            }
+        }
+        Self {
+            level: Level::Line,
+            elements,
+        }
+    }
+
     /// Get element by index. Negative indices count from end.
     pub fn get(&self, index: i64) -> Option<&Value> {
         let len = self.elements.len() as i64;
@@ -321,13 +718,14 @@ impl Array {
         self.elements.is_empty()
     }
 
-    /// Sort the array in place.
+    /// Sort the array in place. The sort is stable in both directions:
+    /// descending reverses the comparison rather than the result, so equal
+    /// elements keep their input order either way.
     pub fn sort(&mut self, descending: bool) {
-        if descending {
-            self.elements.sort_by(|a, b| b.cmp(a));
-        } else {
-            self.elements.sort();
-        }
+        self.elements.sort_by(|a, b| {
+            let ord = a.cmp(b);
+            if descending { ord.reverse() } else { ord }
+        });
     }
 
     /// Returns an iterator over the elements.
@@ -395,6 +793,16 @@ mod tests {
         assert!(num < arr);
     }
 
+    #[test]
+    fn test_bool_ordering() {
+        let f = Value::Bool(false);
+        let t = Value::Bool(true);
+
+        assert!(f < t);
+        assert!(t < Value::Number(-1.0));
+        assert!(t < Value::Text(String::new()));
+    }
+
     #[test]
     fn test_number_comparison() {
         let a = Value::Number(1.0);
@@ -413,6 +821,140 @@ mod tests {
         assert!(a < b);
     }
 
+    #[test]
+    fn test_number_format_default() {
+        let numbers = NumberFormat::default();
+        assert_eq!(numbers.format(42.0), "42");
+        assert_eq!(numbers.format(1.0 / 3.0), "0.3333333333333333");
+    }
+
+    #[test]
+    fn test_number_format_precision() {
+        let numbers = NumberFormat {
+            precision: Some(2),
+            ..NumberFormat::default()
+        };
+        assert_eq!(numbers.format(1.0 / 3.0), "0.33");
+        assert_eq!(numbers.format(2.5), "2.50");
+        assert_eq!(numbers.format(-0.125), "-0.12");
+        assert_eq!(numbers.format(42.0), "42");
+        assert_eq!(
+            NumberFormat {
+                precision: Some(0),
+                ..NumberFormat::default()
+            }
+            .format(2.5),
+            "2"
+        );
+    }
+
+    #[test]
+    fn test_number_format_group_digits() {
+        let numbers = NumberFormat {
+            group_separator: Some(','),
+            ..NumberFormat::default()
+        };
+        assert_eq!(numbers.format_text(1000000.0), "1,000,000");
+        assert_eq!(numbers.format_text(999.0), "999");
+        assert_eq!(numbers.format_text(-1234.0), "-1,234");
+        assert_eq!(numbers.format_text(-123.0), "-123");
+        // Only the digits before the decimal point are grouped
+        assert_eq!(numbers.format_text(1234567.125), "1,234,567.125");
+        assert_eq!(numbers.format_text(0.000125), "0.000125");
+        assert_eq!(numbers.format_text(1e21), "1e21");
+        // JSON output never groups
+        assert_eq!(numbers.format(1000000.0), "1000000");
+
+        let numbers = NumberFormat {
+            precision: Some(2),
+            group_separator: Some(' '),
+        };
+        assert_eq!(numbers.format_text(-12345.678), "-12 345.68");
+    }
+
+    #[test]
+    fn test_format_number_integral() {
+        assert_eq!(format_number(1000000.0), "1000000");
+        assert_eq!(format_number(0.1 * 30.0), "3");
+        assert_eq!(format_number(-0.0), "0");
+        assert_eq!(format_number(-42.0), "-42");
+        assert_eq!(format_number(0.1 + 0.2), "0.30000000000000004");
+        assert_eq!(format_number(2.5), "2.5");
+    }
+
+    #[test]
+    fn test_format_number_large_and_small() {
+        assert_eq!(format_number(1e20), "100000000000000000000");
+        assert_eq!(format_number(1e21), "1e21");
+        assert_eq!(format_number(-1.5e300), "-1.5e300");
+        assert_eq!(format_number(1e-7), "0.0000001");
+    }
+
+    #[test]
+    fn test_number_format_precision_near_integral() {
+        let numbers = NumberFormat {
+            precision: Some(3),
+            ..NumberFormat::default()
+        };
+        assert_eq!(numbers.format(0.1 * 30.0), "3");
+        assert_eq!(numbers.format(2.0 / 3.0), "0.667");
+    }
+
+    #[test]
+    fn test_serialize_numbers_match_display() {
+        for n in [3.0, 0.1 * 30.0, 2.5, 1e21, -7.0, 123456789.0] {
+            let value = Value::Number(n);
+            let json = serde_json::to_string(&value).unwrap();
+            let parsed: f64 = json.parse().unwrap();
+            let shown: f64 = value.to_string().parse().unwrap();
+            assert_eq!(parsed, shown, "{}", n);
+        }
+        assert_eq!(serde_json::to_string(&Value::Number(3.0)).unwrap(), "3");
+    }
+
+    #[test]
+    fn test_serialize_bools_round_trip() {
+        for b in [true, false] {
+            let value = Value::Bool(b);
+            let json = serde_json::to_string(&value).unwrap();
+            assert_eq!(json, value.to_string());
+            assert_eq!(serde_json::from_str::<bool>(&json).unwrap(), b);
+        }
+
+        let row = Value::Array(Array::from((
+            vec![Value::Text("ok".to_string()), Value::Bool(true)],
+            Level::Word,
+        )));
+        let json = serde_json::to_string(&row).unwrap();
+        assert_eq!(json, r#"["ok",true]"#);
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&json).unwrap(),
+            serde_json::json!(["ok", true])
+        );
+        assert_eq!(row.to_string(), "ok true");
+    }
+
+    #[test]
+    fn test_formatted_display() {
+        let numbers = NumberFormat {
+            precision: Some(1),
+            ..NumberFormat::default()
+        };
+        let value = Value::Array(Array::from((
+            vec![
+                Value::Text("avg".to_string()),
+                Value::Number(2.0 / 3.0),
+                Value::Number(7.0),
+            ],
+            Level::Word,
+        )));
+        assert_eq!(value.formatted(&numbers).to_string(), "avg 0.7 7");
+        assert_eq!(
+            value.to_string(),
+            value.formatted(&NumberFormat::default()).to_string()
+        );
+    }
+
     #[test]
     fn test_value_replace() {
         let cases = [
@@ -557,6 +1099,45 @@ mod tests {
         assert_eq!(arr.elements[2], Value::Number(1.0));
     }
 
+    #[test]
+    fn test_array_sort_is_stable() {
+        // Arrays compare by their elements alone, so these differ only in level
+        let row = |key: f64, level| Value::Array(Array::from((vec![Value::Number(key)], level)));
+        let input = || {
+            vec![
+                row(1.0, Level::Word),
+                row(2.0, Level::Word),
+                row(1.0, Level::Char),
+                row(2.0, Level::Char),
+            ]
+        };
+        let levels = |arr: &Array| {
+            arr.elements
+                .iter()
+                .map(|v| match v {
+                    Value::Array(a) => a.level,
+                    _ => panic!("expected array"),
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let mut arr = Array::from((input(), Level::Line));
+        arr.sort(true);
+        assert_eq!(
+            levels(&arr),
+            [Level::Word, Level::Char, Level::Word, Level::Char]
+        );
+        assert_eq!(arr.elements[0], row(2.0, Level::Word));
+
+        let mut arr = Array::from((input(), Level::Line));
+        arr.sort(false);
+        assert_eq!(
+            levels(&arr),
+            [Level::Word, Level::Char, Level::Word, Level::Char]
+        );
+        assert_eq!(arr.elements[0], row(1.0, Level::Word));
+    }
+
     #[test]
     fn test_array_sort_ascending() {
         let mut arr = Array::from((
@@ -591,7 +1172,7 @@ mod tests {
     fn test_array_from_reader_line_level() {
         let input = "line1\nline2\nline3";
         let reader = std::io::BufReader::new(input.as_bytes());
-        let arr = Array::from_reader(reader, Level::Line).unwrap();
+        let arr = Array::from_reader(reader, Level::Line, None).unwrap();
 
         assert_eq!(arr.level, Level::Line);
         assert_eq!(arr.len(), 3);
@@ -600,11 +1181,44 @@ mod tests {
         assert_eq!(arr.elements[2], Value::Text("line3".to_string()));
     }
 
+    #[test]
+    fn test_array_from_reader_line_limit() {
+        let input = "line1\nline2\nline3";
+        let reader = std::io::BufReader::new(input.as_bytes());
+        let arr = Array::from_reader(reader, Level::Line, Some(2)).unwrap();
+
+        assert_eq!(arr.len(), 2);
+        assert_eq!(arr.elements[1], Value::Text("line2".to_string()));
+    }
+
+    #[test]
+    fn test_array_from_files_line_limit() {
+        let dir = std::env::temp_dir();
+        let path1 = dir.join("t_test_limit1.txt");
+        let path2 = dir.join("t_test_limit2.txt");
+
+        std::fs::write(&path1, "file1 line1\nfile1 line2").unwrap();
+        std::fs::write(&path2, "file2 line1").unwrap();
+
+        // The limit stops inside the first file
+        let arr = Array::from_files(&[&path1, &path2], Level::Line, Some(1)).unwrap();
+        assert_eq!(arr.len(), 1);
+        assert_eq!(arr.elements[0], Value::Text("file1 line1".to_string()));
+
+        // ... or spans files
+        let arr = Array::from_files(&[&path1, &path2], Level::Line, Some(3)).unwrap();
+        assert_eq!(arr.len(), 3);
+        assert_eq!(arr.elements[2], Value::Text("file2 line1".to_string()));
+
+        std::fs::remove_file(&path1).unwrap();
+        std::fs::remove_file(&path2).unwrap();
+    }
+
     #[test]
     fn test_array_from_reader_file_level() {
         let input = "line1\nline2\nline3";
         let reader = std::io::BufReader::new(input.as_bytes());
-        let arr = Array::from_reader(reader, Level::File).unwrap();
+        let arr = Array::from_reader(reader, Level::File, None).unwrap();
 
         assert_eq!(arr.level, Level::File);
         assert_eq!(arr.len(), 1);
@@ -618,11 +1232,43 @@ mod tests {
     fn test_array_from_reader_strips_trailing_newline() {
         let input = "content\n";
         let reader = std::io::BufReader::new(input.as_bytes());
-        let arr = Array::from_reader(reader, Level::File).unwrap();
+        let arr = Array::from_reader(reader, Level::File, None).unwrap();
 
         assert_eq!(arr.elements[0], Value::Text("content".to_string()));
     }
 
+    #[test]
+    fn test_read_csv_records_multiline_field() {
+        let input = "id,note\n1,\"two\nlines, \"\"quoted\"\"\"\n2,plain\n";
+        let mut elements = Vec::new();
+        read_csv_records(input.as_bytes(), CsvFormat::default(), None, &mut elements).unwrap();
+
+        let row = |fields: &[&str]| {
+            Value::Array(Array::from((
+                fields.iter().map(|f| Value::Text(f.to_string())).collect(),
+                Level::Word,
+            )))
+        };
+        assert_eq!(
+            elements,
+            vec![
+                row(&["id", "note"]),
+                row(&["1", "two\nlines, \"quoted\""]),
+                row(&["2", "plain"]),
+            ]
+        );
+
+        let mut elements = Vec::new();
+        read_csv_records(
+            input.as_bytes(),
+            CsvFormat::default(),
+            Some(2),
+            &mut elements,
+        )
+        .unwrap();
+        assert_eq!(elements.len(), 2);
+    }
+
     #[test]
     fn test_array_from_files() {
         let dir = std::env::temp_dir();
@@ -633,14 +1279,14 @@ mod tests {
         std::fs::write(&path2, "file2 line1").unwrap();
 
         // Line level: all lines concatenated
-        let arr = Array::from_files(&[&path1, &path2], Level::Line).unwrap();
+        let arr = Array::from_files(&[&path1, &path2], Level::Line, None).unwrap();
         assert_eq!(arr.len(), 3);
         assert_eq!(arr.elements[0], Value::Text("file1 line1".to_string()));
         assert_eq!(arr.elements[1], Value::Text("file1 line2".to_string()));
         assert_eq!(arr.elements[2], Value::Text("file2 line1".to_string()));
 
         // File level: each file as one element
-        let arr = Array::from_files(&[&path1, &path2], Level::File).unwrap();
+        let arr = Array::from_files(&[&path1, &path2], Level::File, None).unwrap();
         assert_eq!(arr.len(), 2);
         assert_eq!(
             arr.elements[0],
@@ -651,4 +1297,100 @@ mod tests {
         std::fs::remove_file(&path1).unwrap();
         std::fs::remove_file(&path2).unwrap();
     }
+
+    #[test]
+    fn test_array_from_files_zipped() {
+        let dir = std::env::temp_dir();
+        let path1 = dir.join("t_test_zip1.txt");
+        let path2 = dir.join("t_test_zip2.txt");
+
+        std::fs::write(&path1, "a1\na2\na3\n").unwrap();
+        std::fs::write(&path2, "b1\n").unwrap();
+
+        let row = |a: &str, b: &str| {
+            Value::Array(Array::from((
+                vec![Value::Text(a.to_string()), Value::Text(b.to_string())],
+                Level::Word,
+            )))
+        };
+
+        // The shorter file is padded with empty text
+        let arr = Array::from_files_zipped(&[&path1, &path2], None).unwrap();
+        assert_eq!(arr.level, Level::Line);
+        assert_eq!(
+            arr.elements,
+            vec![row("a1", "b1"), row("a2", ""), row("a3", "")]
+        );
+
+        let arr = Array::from_files_zipped(&[&path2, &path1], Some(2)).unwrap();
+        assert_eq!(arr.elements, vec![row("b1", "a1"), row("", "a2")]);
+
+        std::fs::remove_file(&path1).unwrap();
+        std::fs::remove_file(&path2).unwrap();
+    }
+
+    fn lines(lines: &[&str]) -> Array {
+        Array::from((
+            lines.iter().map(|s| Value::Text(s.to_string())).collect(),
+            Level::Line,
+        ))
+    }
+
+    #[test]
+    fn test_into_paragraphs() {
+        let paragraphs =
+            lines(&["", "one", "two", "", " ", "", "three", "\t", ""]).into_paragraphs();
+        assert_eq!(paragraphs.level, Level::File);
+        assert_eq!(
+            paragraphs.elements,
+            vec![
+                Value::Text("one\ntwo".to_string()),
+                Value::Text("three".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_into_paragraphs_blank_input() {
+        assert!(lines(&["", "  ", ""]).into_paragraphs().is_empty());
+        assert!(lines(&[]).into_paragraphs().is_empty());
+    }
+
+    #[test]
+    fn test_combine_sets() {
+        let a = || lines(&["x", "b", "a", "b", "X"]);
+        let b = || lines(&["a", "c", "x", "c"]);
+        let texts =
+            |arr: Array| -> Vec<String> { arr.elements.iter().map(|v| v.to_string()).collect() };
+        assert_eq!(texts(a().combine(b(), SetOp::Difference)), ["b", "X"]);
+        assert_eq!(texts(a().combine(b(), SetOp::Intersection)), ["x", "a"]);
+        assert_eq!(
+            texts(a().combine(b(), SetOp::Union)),
+            ["x", "b", "a", "X", "c"]
+        );
+    }
+
+    fn rows(rows: &[&[&str]]) -> Array {
+        Array::from((
+            rows.iter()
+                .map(|r| {
+                    Value::Array(Array::from((
+                        r.iter().map(|s| Value::Text(s.to_string())).collect(),
+                        Level::Word,
+                    )))
+                })
+                .collect(),
+            Level::Line,
+        ))
+    }
+
+    #[test]
+    fn test_join_on() {
+        let users = rows(&[&["1", "alice"], &["2", "bob"], &["3", "carol"], &[]]);
+        let orders = rows(&[&["2", "pen"], &["1", "ink"], &["2", "pad"], &["4", "cup"]]);
+        let joined = users.join_on(orders, 0);
+        let texts: Vec<String> = joined.elements.iter().map(|v| v.to_string()).collect();
+        assert_eq!(texts, ["1 alice ink", "2 bob pen", "2 bob pad"]);
+        assert_eq!(joined.level, Level::Line);
+    }
 }
@@ -0,0 +1,110 @@
+//! Tests that run the `t` binary end to end.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Run `t` with the given arguments on `input`, returning its stdout.
+fn t(args: &[&str], input: &str) -> String {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_t"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("failed to run t");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success(), "t {:?} failed", args);
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn whole_input_splits_into_lines_first() {
+    let input = "the cat\nsat down\n";
+
+    // Read whole, the first `s` gives the lines that are read by default
+    assert_eq!(t(&["-w", "-j", "sf"], input), t(&["-j", ""], input));
+    assert_eq!(
+        t(&["-w", "-j", "s"], input),
+        "[\n  [\"the cat\",\"sat down\"]\n]\n"
+    );
+    // ... and splitting those lines gives the words
+    assert_eq!(t(&["-w", "-j", "s@s^f"], input), t(&["-j", "s"], input));
+    assert_eq!(t(&["-w", "#"], input), "1\n");
+}
+
+#[test]
+fn csv_delimiter_and_quote() {
+    let input = "name;price\n'Smith; J';'1,50'\n";
+    let args = ["-c", "--csv-delimiter", ";", "--csv-quote", "'"];
+
+    // Fields are split on `;`, and quoted `;` and `,` are kept
+    assert_eq!(t(&[&args[..], &["s@1^"]].concat(), input), "price\n1,50\n");
+    // Joining quotes only the fields that need it
+    assert_eq!(
+        t(&[&args[..], &["sj"]].concat(), input),
+        "name;price\n'Smith; J';1,50\n"
+    );
+}
+
+#[test]
+fn csv_records_span_lines() {
+    let input = "id,note\n1,\"two\nlines\"\n2,plain\n";
+
+    // The quoted newline stays inside its field, so there are three records
+    assert_eq!(t(&["-c", "#"], input), "3\n");
+    assert_eq!(t(&["-c", "@1^"], input), "note\ntwo\nlines\nplain\n");
+    // ... and joining quotes it again
+    assert_eq!(t(&["-c", "j"], input), input);
+}
+
+#[test]
+fn auto_detects_delimiter() {
+    let csv = "name,note\nfig,\"ripe, sweet\"\nkiwi,green\n";
+    assert_eq!(t(&["--auto", "s@1^"], csv), "note\nripe, sweet\ngreen\n");
+    assert_eq!(t(&["--auto", "sj"], csv), csv);
+
+    let tsv = "name\tnote\nfig\tripe, sweet\nkiwi\tgreen\n";
+    assert_eq!(t(&["--auto", "s@1^"], tsv), "note\nripe, sweet\ngreen\n");
+    assert_eq!(t(&["--auto", "sj"], tsv), tsv);
+
+    // An explicit delimiter wins
+    assert_eq!(
+        t(&["--auto", "-d", " ", "s@0^"], tsv),
+        "name\tnote\nfig\tripe,\nkiwi\tgreen\n"
+    );
+}
+
+#[test]
+fn group_digits_in_text_output_only() {
+    let input = "1234567\n-2500.5\n12\n";
+    assert_eq!(
+        t(&["--group-digits", "n"], input),
+        "1,234,567\n-2,500.5\n12\n"
+    );
+    assert_eq!(t(&["--group-digits=_", "n+"], input), "1_232_078.5\n");
+    // JSON needs valid numbers, so it is never grouped
+    assert_eq!(
+        t(&["--group-digits", "-j", "n"], input),
+        "[\n  1234567,\n  -2500.5,\n  12\n]\n"
+    );
+}
+
+#[test]
+fn percentages_follow_precision() {
+    let input = "a\nb\nb\n";
+
+    assert_eq!(
+        t(&["d%"], input),
+        "66.66666666666666 b\n33.33333333333333 a\n"
+    );
+    assert_eq!(t(&["--precision", "1", "d%"], input), "66.7 b\n33.3 a\n");
+    assert_eq!(
+        t(&["--precision", "1", "--compact-json", "d%"], input),
+        "[[66.7,\"b\"],[33.3,\"a\"]]\n"
+    );
+}